@@ -1,69 +1,77 @@
 use crate::errors;
 use crate::errors::AppError;
-use crate::settings::Auth;
-use axum::extract::{Request, State};
+use crate::settings::PublicAccess;
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
 use axum::middleware::Next;
 use axum::response::IntoResponse;
-use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
-use serde::{Deserialize, Serialize};
-use tokio::sync::OnceCell;
+use ulid::Ulid;
+use zylo_common::auth::JwtKeyStore;
 
-#[allow(dead_code)]
-#[derive(Deserialize, Serialize)]
-struct Claims {
-    sub: String,
-    aud: String,
-    exp: usize,
-    iss: String,
-    nbf: usize,
-    email_verified: String,
+/// Who is making the request. Anonymous viewers only reach routes listed in `public_access`.
+#[derive(Debug, Clone)]
+pub enum Viewer {
+    Authenticated(String),
+    Anonymous,
 }
 
-static VALIDATION: OnceCell<Validation> = OnceCell::const_new();
-static DECODING_KEY: OnceCell<DecodingKey> = OnceCell::const_new();
+impl Viewer {
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, Viewer::Anonymous)
+    }
 
-pub async fn authorization_middleware(
-    State(config): State<Auth>,
-    req: Request,
-    next: Next,
-) -> Result<impl IntoResponse, AppError> {
-    let auth_token = req
-        .headers()
-        .get("Authorization")
-        .ok_or(errors::AuthError::TokenNotFound)?
-        .to_str()
-        .map_err(|_| errors::AuthError::TokenNotFound)?;
+    pub fn user_id(&self) -> Option<Ulid> {
+        match self {
+            Viewer::Authenticated(sub) => sub.parse().ok(),
+            Viewer::Anonymous => None,
+        }
+    }
+}
 
-    let auth_token = auth_token.trim_start_matches("Bearer ").trim();
+#[derive(Clone)]
+pub struct AuthorizationState {
+    pub key_store: JwtKeyStore,
+    pub public_access: PublicAccess,
+}
 
-    authorize_user(auth_token, &config).await?;
-    Ok(next.run(req).await)
+impl AuthorizationState {
+    pub fn new(key_store: JwtKeyStore, public_access: PublicAccess) -> Self {
+        Self {
+            key_store,
+            public_access,
+        }
+    }
 }
 
-async fn authorize_user(auth_token: &str, auth_config: &Auth) -> Result<(), AppError> {
-    let validation = VALIDATION
-        .get_or_init(|| create_validation(auth_config))
-        .await;
-    let decoding_key = DECODING_KEY
-        .get_or_init(|| async { DecodingKey::from_secret(auth_config.secret.as_ref()) })
-        .await;
+pub async fn authorization_middleware(
+    State(state): State<AuthorizationState>,
+    mut req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(auth_header) = req.headers().get(header::AUTHORIZATION) else {
+        let is_public = req
+            .extensions()
+            .get::<MatchedPath>()
+            .is_some_and(|path| state.public_access.is_public(req.method(), path.as_str()));
 
-    let claims = decode::<Claims>(auth_token, decoding_key, validation)
-        .map_err(|_| errors::AuthError::InvalidToken)?;
+        if !is_public {
+            return Err(errors::AuthError::TokenNotFound)?;
+        }
 
-    if claims.claims.email_verified.eq_ignore_ascii_case("false") {
-        return Err(errors::AuthError::UnverifiedEmail)?;
-    }
+        req.extensions_mut().insert(Viewer::Anonymous);
+        return Ok(next.run(req).await);
+    };
 
-    Ok(())
-}
+    let auth_token = auth_header
+        .to_str()
+        .map_err(|_| errors::AuthError::TokenNotFound)?;
 
-async fn create_validation(auth_config: &Auth) -> Validation {
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.set_audience(&[auth_config.audience.clone()]);
-    validation.set_issuer(&[auth_config.issuer.clone()]);
+    let auth_token = auth_token.trim_start_matches("Bearer ").trim();
 
-    validation.set_required_spec_claims(&["sub", "aud", "iss", "nbf", "exp"]);
+    let principal = state.key_store.authorize(auth_token).await?;
+    req.extensions_mut()
+        .insert(Viewer::Authenticated(principal.user_id.clone()));
+    req.extensions_mut().insert(principal);
 
-    validation
+    Ok(next.run(req).await)
 }
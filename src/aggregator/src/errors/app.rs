@@ -1,40 +1,13 @@
 ﻿use crate::errors;
+use crate::errors::ProblemResponse;
 use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::Json;
-use serde_json::json;
 use std::env::VarError;
-use opentelemetry::trace::TraceContextExt;
 use thiserror::Error;
-use tracing::{error, Span};
-use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing::error;
 use reqwest::Error as ReqwestError;
 
-pub trait ProblemResponse {
-    fn status_code(&self) -> StatusCode;
-    fn title(&self) -> &str;
-    fn detail(&self) -> String;
-
-    fn public_detail(&self) -> &str {
-        "An unexpected server error occurred. Please try again later."
-    }
-
-    fn to_response(&self) -> Response {
-        let context = Span::current().context();
-        let trace_id = context.span().span_context().trace_id().to_string();
-        
-        let body = json!({
-            "type": format!("https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/{}", self.status_code()),
-            "title": self.title(),
-            "status": self.status_code().as_u16(),
-            "detail": self.public_detail(),
-            "traceId": trace_id
-        });
-
-        (self.status_code(), Json(body)).into_response()
-    }
-}
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error(transparent)]
@@ -72,12 +45,12 @@ impl ProblemResponse for AppError {
         self.to_string()
     }
 
-    fn public_detail(&self) -> &str {
+    fn public_detail(&self) -> String {
         match self {
             AppError::Grpc(err) => err.public_detail(),
             AppError::Auth(err) => err.public_detail(),
             
-            _ => "An unexpected server error occurred. Please try again later."
+            _ => String::from("An unexpected server error occurred. Please try again later.")
         }
     }
 }
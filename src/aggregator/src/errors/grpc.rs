@@ -60,11 +60,11 @@ impl ProblemResponse for GrpcError {
         }
     }
 
-    fn public_detail(&self) -> &str {
+    fn public_detail(&self) -> String {
         match self {
-            NotFound(err) => err,
-            BadRequest(err) => err,
-            _ => "An unexpected server error occurred. Please try again later.",
+            NotFound(err) => err.clone(),
+            BadRequest(err) => err.clone(),
+            _ => String::from("An unexpected server error occurred. Please try again later."),
         }
     }
 }
\ No newline at end of file
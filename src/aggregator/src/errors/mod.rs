@@ -1,9 +1,6 @@
 ﻿mod app;
-mod auth;
 mod grpc;
 
-pub use app::ProblemResponse;
-
-pub use auth::AuthError;
+pub use zylo_common::errors::{AuthError, ProblemResponse};
 pub use grpc::GrpcError;
 pub use app::AppError;
@@ -1,4 +1,4 @@
-use crate::app::{init_logs, init_metrics, init_trace, init_traces, run_app};
+use crate::app::run_app;
 use crate::models::app_state::AppState;
 use crate::services::aggregator::feed_service_client::FeedServiceClient;
 use crate::services::aggregator::post_service_client::PostServiceClient;
@@ -9,9 +9,11 @@ use crate::services::feed_service::FeedServiceImpl;
 use crate::services::post_service::PostsServiceImpl;
 use crate::services::user_service::UserServiceImpl;
 use crate::settings::AppConfig;
+use crate::utils::constants::OTEL_SERVICE_NAME;
 use dotenv::dotenv;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use zylo_common::telemetry::{init_logs, init_metrics, init_traces, init_tracing};
 
 mod app;
 mod auth;
@@ -28,11 +30,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = AppConfig::new().await;
     let collector_address = &config.otel_collector.address;
 
-    let trace_provider = init_traces(collector_address);
-    let meter_provider = init_metrics(collector_address);
-    let logger_provider = init_logs(collector_address);
+    let trace_provider = init_traces(OTEL_SERVICE_NAME, collector_address);
+    let meter_provider = init_metrics(OTEL_SERVICE_NAME, collector_address);
+    let logger_provider = init_logs(OTEL_SERVICE_NAME, collector_address);
 
-    init_trace(&logger_provider, &trace_provider);
+    init_tracing(&logger_provider, &trace_provider);
 
     let post_client =
         PostServiceClient::connect(config.external_grpc_servers.media_service.clone()).await?;
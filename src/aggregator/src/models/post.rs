@@ -1,10 +1,12 @@
 use std::ops::Not;
 use std::collections::{HashMap};
 use std::sync::Arc;
-use crate::services::aggregator::{BatchOfPostInteractionsResponse, FileMetadataResponse, GrpcUserPreview, PaginatedPostsResponse, PostInteractionsResponse, PostResponse, ReplyResponse, UserImage};
+use crate::services::aggregator::{BatchOfPostInteractionsResponse, FileMetadataResponse, GrpcUserPreview, LikeResponse, PaginatedPostsResponse, PostInteractionsResponse, PostResponse, ReplyResponse, UserImage};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+const PUBLIC_VISIBILITY: &str = "public";
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginatedResponse<T> {
@@ -17,6 +19,17 @@ pub struct PaginatedResponse<T> {
 }
 
 impl PaginatedResponse<Post> {
+    /// Drops posts that are not public and strips viewer specific state from the rest.
+    pub fn into_public(mut self) -> Self {
+        self.data = self
+            .data
+            .into_iter()
+            .filter(Post::is_public)
+            .map(Post::into_public)
+            .collect();
+        self
+    }
+
     pub fn new(data: Vec<Post>, per_page: u32, next: String, has_next_page: bool, is_stale: bool) -> Self {
         Self {
             data,
@@ -73,15 +86,32 @@ pub struct Post {
     user: Arc<UserSummary>,
     content: String,
     files: Vec<FileMetadata>,
+    visibility: String,
     replies: Vec<Reply>,
     likes: u64,
     views: u64,
-    user_interacted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_interacted: Option<bool>,
+    reactions: HashMap<String, u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_reaction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bookmarked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_replies_cursor: Option<String>,
     created_at: String,
     updated_at: String,
 }
 
 impl Post {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.visibility == PUBLIC_VISIBILITY
+    }
+
     pub fn map_posts(
         posts: Vec<PostResponse>,
         batch_posts_interactions: BatchOfPostInteractionsResponse,
@@ -99,10 +129,19 @@ impl Post {
                 let post_interaction = interactions_map
                     .remove(&post.id)
                     .unwrap_or_default();
-                Post::from(post, post_interaction, &users_map)
+                Post::from(post, post_interaction, users_map)
             })
             .collect()
     }
+
+    /// Strips viewer specific state so the post can be served to anonymous visitors.
+    pub fn into_public(mut self) -> Self {
+        self.user_interacted = None;
+        self.user_reaction = None;
+        self.bookmarked = None;
+        self.replies = self.replies.into_iter().map(Reply::into_public).collect();
+        self
+    }
     
     pub fn from(
         post_response: PostResponse,
@@ -124,10 +163,17 @@ impl Post {
                 .into_iter()
                 .map(FileMetadata::from)
                 .collect(),
+            visibility: post_response.visibility,
             replies,
             likes: post_interaction.likes,
             views: post_interaction.views,
-            user_interacted: post_interaction.user_interacted,
+            user_interacted: Some(post_interaction.user_interacted),
+            reactions: post_interaction.reactions,
+            user_reaction: (!post_interaction.user_reaction.is_empty())
+                .then_some(post_interaction.user_reaction),
+            bookmarked: Some(post_interaction.bookmarked),
+            next_replies_cursor: (!post_interaction.next_cursor.is_empty())
+                .then_some(post_interaction.next_cursor),
             created_at: post_response.created_at,
             updated_at: post_response.updated_at,
         }
@@ -170,11 +216,28 @@ pub struct Reply {
     views: u64,
     likes: u64,
     nested_replies: Vec<Reply>,
-    user_interacted: bool,
+    has_more_children: bool,
+    deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_interacted: Option<bool>,
+    reactions: HashMap<String, u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_reaction: Option<String>,
     created_at: String,
 }
 
 impl Reply {
+    pub fn into_public(mut self) -> Self {
+        self.user_interacted = None;
+        self.user_reaction = None;
+        self.nested_replies = self
+            .nested_replies
+            .into_iter()
+            .map(Reply::into_public)
+            .collect();
+        self
+    }
+
     pub fn from(value: ReplyResponse, users_map: &HashMap<String, Arc<UserSummary>>) -> Self {
         Self {
             id: value.id,
@@ -188,8 +251,126 @@ impl Reply {
                 .into_iter()
                 .map(|reply| Reply::from(reply, users_map))
                 .collect(),
-            user_interacted: value.user_interacted,
+            has_more_children: value.has_more_children,
+            deleted: value.deleted,
+            user_interacted: Some(value.user_interacted),
+            reactions: value.reactions,
+            user_reaction: (!value.user_reaction.is_empty()).then_some(value.user_reaction),
             created_at: DateTime::<Utc>::from_timestamp_nanos(value.created_at).to_rfc3339(),
         }
     }
 }
+
+/// A page of likes, newest first. `next` is absent on the last page.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LikesPage<T> {
+    data: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<String>,
+}
+
+impl<T> LikesPage<T> {
+    pub fn new(data: Vec<T>, next: String) -> Self {
+        Self {
+            data,
+            next: (!next.is_empty()).then_some(next),
+        }
+    }
+}
+
+impl LikesPage<LikedPost> {
+    pub fn into_public(mut self) -> Self {
+        self.data = self
+            .data
+            .into_iter()
+            .filter(|liked| liked.post.is_public())
+            .map(LikedPost::into_public)
+            .collect();
+        self
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Liker {
+    user: Arc<UserSummary>,
+    liked_at: String,
+}
+
+impl Liker {
+    pub fn from(like: LikeResponse, users_map: &HashMap<String, Arc<UserSummary>>) -> Self {
+        Self {
+            user: users_map.get(&like.user_id).unwrap_or(&Arc::<UserSummary>::default()).clone(),
+            liked_at: liked_at(like.liked_at),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LikedPost {
+    post: Post,
+    liked_at: String,
+}
+
+impl LikedPost {
+    pub fn new(post: Post, like: &LikeResponse) -> Self {
+        Self {
+            post,
+            liked_at: liked_at(like.liked_at),
+        }
+    }
+
+    pub fn into_public(mut self) -> Self {
+        self.post = self.post.into_public();
+        self
+    }
+}
+
+fn liked_at(seconds: i64) -> String {
+    DateTime::<Utc>::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(id: &str, visibility: &str) -> Post {
+        let post_response = PostResponse {
+            id: id.to_string(),
+            visibility: visibility.to_string(),
+            ..Default::default()
+        };
+        let interactions = PostInteractionsResponse {
+            user_interacted: true,
+            bookmarked: true,
+            ..Default::default()
+        };
+
+        Post::from(post_response, interactions, &HashMap::new())
+    }
+
+    #[test]
+    fn into_public_keeps_only_public_posts_without_viewer_state() {
+        let page = PaginatedResponse::new(
+            vec![
+                post("public", "public"),
+                post("members", "members"),
+                post("unknown", ""),
+            ],
+            3,
+            String::new(),
+            false,
+            false,
+        )
+        .into_public();
+
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.data[0].id(), "public");
+        assert_eq!(page.data[0].user_interacted, None);
+        assert_eq!(page.data[0].bookmarked, None);
+    }
+}
@@ -37,25 +37,47 @@ impl User {
             relationships,
         }
     }
+
+    /// Hides interaction state and private relationships from anonymous visitors.
+    pub fn into_public(mut self) -> Self {
+        self.posts = self.posts.into_public();
+        self.relationships.friend_requests = None;
+        self.relationships.blocks = None;
+        self
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UserRelationships {
     friends: UserRelationshipData,
-    friend_requests: UserFriendRequests,
-    blocks: UserRelationshipData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    friend_requests: Option<UserFriendRequests>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blocks: Option<UserRelationshipData>,
     follows: UserFollowRequests,
     #[serde(skip_serializing_if = "<&bool>::not")]
     pub is_stale: bool
 }
 
+impl Default for UserRelationships {
+    fn default() -> Self {
+        Self {
+            friends: UserRelationshipData::default(),
+            friend_requests: Some(UserFriendRequests::default()),
+            blocks: Some(UserRelationshipData::default()),
+            follows: UserFollowRequests::default(),
+            is_stale: false,
+        }
+    }
+}
+
 impl UserRelationships {
     pub fn from(friends: UserRelationshipData, friend_requests: UserFriendRequests, blocks: UserRelationshipData, follows: UserFollowRequests, is_stale: bool) -> Self {
         Self {
             friends,
-            friend_requests,
-            blocks ,
+            friend_requests: Some(friend_requests),
+            blocks: Some(blocks),
             follows,
             is_stale
         }
@@ -1,12 +1,13 @@
+use crate::auth::Viewer;
 use crate::errors;
 use crate::models::app_state::AppState;
-use crate::models::post::{PaginatedResponse, Post};
+use crate::models::post::{LikedPost, Liker, LikesPage, PaginatedResponse, Post};
 use crate::services::post_service::PostsService;
 use crate::services::user_service::UserService;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::routing::get;
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use serde::Deserialize;
 use ulid::Ulid;
 use crate::services::feed_service::FeedService;
@@ -21,6 +22,8 @@ where
         .route("/api/posts/{postId}", get(get_post))
         .route("/api/posts", get(get_recent_posts))
         .route("/api/users/{userId}/feed", get(get_feed))
+        .route("/api/posts/{postId}/likes", get(get_post_likes))
+        .route("/api/users/{userId}/likes/posts", get(get_liked_posts))
         .with_state(state)
 }
 
@@ -37,6 +40,7 @@ pub struct PaginationParams {
 pub async fn get_recent_posts<P, U, F>(
     Query(params): Query<PaginationParams>,
     State(state): State<AppState<P, U, F>>,
+    Extension(viewer): Extension<Viewer>,
 ) -> Result<(StatusCode, Json<PaginatedResponse<Post>>), errors::AppError>
 where
     P: PostsService + 'static,
@@ -49,7 +53,8 @@ where
         .await
         .get_paginated_posts(
             params.per_page.unwrap_or(10),
-            params.user_interaction_id,
+            viewer.user_id().unwrap_or(params.user_interaction_id),
+            viewer.user_id(),
             params.next.map(|next| next.to_string()),
         )
         .await?;
@@ -57,24 +62,27 @@ where
     Ok((StatusCode::OK, Json(paginated_response)))
 }
 
-#[derive(Debug, Deserialize)]
-pub struct GetPostByIdQueryParams {
-    #[serde(rename = "userInteractionId")]
-    pub user_interaction_id: Ulid,
-}
-
-
 async fn get_post<P, U, F>(
     State(state): State<AppState<P, U, F>>,
+    Extension(viewer): Extension<Viewer>,
     Path(post_id): Path<Ulid>,
-    Query(params): Query<GetPostByIdQueryParams>,
 ) -> Result<(StatusCode, Json<Post>), errors::AppError>
 where
     P: PostsService + 'static,
     U: UserService + 'static,
     F: FeedService + 'static,
 {
-    let post = state.posts_service.lock().await.get_post_by_id(post_id,params.user_interaction_id).await?;
+    if viewer.is_anonymous() {
+        let post = state.posts_service.lock().await.get_post_by_id(post_id, None, None).await?;
+        if !post.is_public() {
+            return Err(errors::GrpcError::NotFound(String::from("Post with given id does not exists")))?;
+        }
+
+        return Ok((StatusCode::OK, Json(post.into_public())));
+    }
+
+    // Interactions and bookmarks are private, so they are only ever looked up for the viewer.
+    let post = state.posts_service.lock().await.get_post_by_id(post_id, viewer.user_id(), viewer.user_id()).await?;
     Ok((StatusCode::OK, Json(post)))
 }
 
@@ -88,6 +96,7 @@ pub struct FeedParams {
 
 async fn get_feed<P, U, F>(
     State(state): State<AppState<P, U, F>>,
+    Extension(viewer): Extension<Viewer>,
     Path(user_id): Path<Ulid>,
     Query(params): Query<FeedParams>,
 ) -> Result<(StatusCode, Json<PaginatedResponse<Post>>), errors::AppError>
@@ -96,6 +105,59 @@ where
     U: UserService + 'static,
     F: FeedService + 'static,
 {
-    let post = state.feed_service.lock().await.get_feed_by_user_id(user_id, params.per_page, params.next.map(|id| id.to_string())).await?;
+    let post = state.feed_service.lock().await.get_feed_by_user_id(user_id, viewer.user_id(), params.per_page, params.next.map(|id| id.to_string())).await?;
     Ok((StatusCode::OK, Json(post)))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct LikesParams {
+    #[serde(rename = "next")]
+    pub next: Option<String>,
+    #[serde(rename = "perPage")]
+    pub per_page: Option<u32>,
+}
+
+async fn get_post_likes<P, U, F>(
+    State(state): State<AppState<P, U, F>>,
+    Path(post_id): Path<Ulid>,
+    Query(params): Query<LikesParams>,
+) -> Result<(StatusCode, Json<LikesPage<Liker>>), errors::AppError>
+where
+    P: PostsService + 'static,
+    U: UserService + 'static,
+    F: FeedService + 'static,
+{
+    let likes = state
+        .posts_service
+        .lock()
+        .await
+        .get_post_likes(post_id, params.next, params.per_page)
+        .await?;
+    Ok((StatusCode::OK, Json(likes)))
+}
+
+async fn get_liked_posts<P, U, F>(
+    State(state): State<AppState<P, U, F>>,
+    Extension(viewer): Extension<Viewer>,
+    Path(user_id): Path<Ulid>,
+    Query(params): Query<LikesParams>,
+) -> Result<(StatusCode, Json<LikesPage<LikedPost>>), errors::AppError>
+where
+    P: PostsService + 'static,
+    U: UserService + 'static,
+    F: FeedService + 'static,
+{
+    let interaction_user_id = viewer.user_id().unwrap_or(user_id);
+    let likes = state
+        .posts_service
+        .lock()
+        .await
+        .get_liked_posts(user_id, interaction_user_id, viewer.user_id(), params.next, params.per_page)
+        .await?;
+
+    if viewer.is_anonymous() {
+        return Ok((StatusCode::OK, Json(likes.into_public())));
+    }
+
+    Ok((StatusCode::OK, Json(likes)))
+}
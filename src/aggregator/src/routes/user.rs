@@ -1,3 +1,4 @@
+use crate::auth::Viewer;
 use crate::errors;
 use crate::models::app_state::AppState;
 use crate::services::post_service::PostsService;
@@ -5,7 +6,7 @@ use crate::services::user_service::UserService;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::routing::get;
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use serde::Deserialize;
 use ulid::Ulid;
 use crate::models::user::User;
@@ -29,6 +30,7 @@ pub struct GetUserParams {
 }
 async fn get_user<P, U, F>(
     State(state): State<AppState<P, U, F>>,
+    Extension(viewer): Extension<Viewer>,
     Path(user_id): Path<Ulid>,
     Query(params): Query<GetUserParams>
 ) -> Result<(StatusCode, Json<User>), errors::AppError>
@@ -37,6 +39,11 @@ where
     U: UserService + 'static,
     F: FeedService + 'static,
 {
-    let user = state.users_service.lock().await.get_by_id(user_id, params.interaction_user_id).await?;
+    if viewer.is_anonymous() {
+        let user = state.users_service.lock().await.get_by_id(user_id, None, None).await?;
+        return Ok((StatusCode::OK, Json(user.into_public())));
+    }
+
+    let user = state.users_service.lock().await.get_by_id(user_id, params.interaction_user_id, viewer.user_id()).await?;
     Ok((StatusCode::OK, Json(user)))
 }
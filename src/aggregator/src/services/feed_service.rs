@@ -3,7 +3,7 @@ use crate::models::post::{PaginatedResponse, Post};
 use crate::services::aggregator::feed_service_client::FeedServiceClient;
 use crate::services::aggregator::GetRecommendedPostsRequest;
 use crate::services::post_service::PostsService;
-use crate::services::InjectTraceContext;
+use zylo_common::grpc::InjectTraceContext;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -16,6 +16,7 @@ pub trait FeedService: Send + Sync {
     async fn get_feed_by_user_id(
         &mut self,
         id: Ulid,
+        viewer_id: Option<Ulid>,
         per_page: Option<u32>,
         last_post_id: Option<String>,
     ) -> Result<PaginatedResponse<Post>, errors::GrpcError>;
@@ -40,6 +41,7 @@ impl<P: PostsService + 'static> FeedService for FeedServiceImpl<P> {
     async fn get_feed_by_user_id(
         &mut self,
         id: Ulid,
+        viewer_id: Option<Ulid>,
         per_page: Option<u32>,
         last_post_id: Option<String>,
     ) -> Result<PaginatedResponse<Post>, errors::GrpcError> {
@@ -66,7 +68,7 @@ impl<P: PostsService + 'static> FeedService for FeedServiceImpl<P> {
             .posts_service
             .lock()
             .await
-            .get_posts_by_id(recommended_posts.post_ids, id)
+            .get_posts_by_id(recommended_posts.post_ids, id, viewer_id)
             .await?;
         
         Ok(PaginatedResponse::<Post>::new(
@@ -1,12 +1,12 @@
 use crate::errors;
-use crate::models::post::{PaginatedResponse, Post};
-use crate::services::InjectTraceContext;
+use crate::models::post::{LikedPost, Liker, LikesPage, PaginatedResponse, Post};
+use zylo_common::grpc::InjectTraceContext;
 use crate::services::aggregator::post_service_client::PostServiceClient;
 use crate::services::aggregator::reply_service_client::ReplyServiceClient;
 use crate::services::aggregator::user_profile_service_client::UserProfileServiceClient;
-use crate::services::aggregator::{BatchOfPostInteractionsResponse, BatchPostsRequest, GetPostInteractionsRequest, PostInteractionsResponse, PostRequest, PostsRequest};
+use crate::services::aggregator::{BatchOfPostInteractionsResponse, BatchPostsRequest, GetLikesRequest, GetPostInteractionsRequest, PostInteractionsResponse, PostRequest, PostsRequest};
 use async_trait::async_trait;
-use std::collections::{HashMap};
+use std::collections::{HashMap, HashSet};
 use tonic::IntoRequest;
 use tonic::transport::Channel;
 use tracing::log::{error, warn};
@@ -19,20 +19,43 @@ pub trait PostsService: Send + Sync {
         &mut self,
         per_page: u32,
         interaction_user_id: Ulid,
+        viewer_id: Option<Ulid>,
         last_post_id: Option<String>,
     ) -> Result<PaginatedResponse<Post>, errors::GrpcError>;
 
+    /// Reactions are those of `interaction_user_id`, bookmarks only ever those of `viewer_id`.
     async fn get_post_by_id(
         &mut self,
         id: Ulid,
-        interaction_user_id: Ulid,
+        interaction_user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<Post, errors::GrpcError>;
 
     async fn get_posts_by_id(
         &mut self,
         id: Vec<String>,
         interaction_user_id: Ulid,
+        viewer_id: Option<Ulid>,
     ) -> Result<Vec<Post>, errors::GrpcError>;
+
+    /// Users who liked a post, most recent like first.
+    async fn get_post_likes(
+        &mut self,
+        post_id: Ulid,
+        next: Option<String>,
+        per_page: Option<u32>,
+    ) -> Result<LikesPage<Liker>, errors::GrpcError>;
+
+    /// Posts a user liked, most recent like first. Liked posts that no longer exist are left out
+    /// of the page.
+    async fn get_liked_posts(
+        &mut self,
+        user_id: Ulid,
+        interaction_user_id: Ulid,
+        viewer_id: Option<Ulid>,
+        next: Option<String>,
+        per_page: Option<u32>,
+    ) -> Result<LikesPage<LikedPost>, errors::GrpcError>;
 }
 
 pub struct PostsServiceImpl {
@@ -61,6 +84,7 @@ impl PostsService for PostsServiceImpl {
         &mut self,
         per_page: u32,
         interaction_user_id: Ulid,
+        viewer_id: Option<Ulid>,
         last_post_id: Option<String>,
     ) -> Result<PaginatedResponse<Post>, errors::GrpcError> {
         let request = PostsRequest {
@@ -82,7 +106,7 @@ impl PostsService for PostsServiceImpl {
         }
         
         let mut interactions_stale = false;
-        let interactions = get_posts_interactions(&mut self.reply_client, &paginated_posts.posts, interaction_user_id.to_string())
+        let interactions = get_posts_interactions(&mut self.reply_client, &paginated_posts.posts, interaction_user_id.to_string(), viewer_id)
             .await
             .unwrap_or_else(|e| {
                 error!("Failed to retrieve post interactions: {:?}", e);
@@ -109,7 +133,8 @@ impl PostsService for PostsServiceImpl {
     async fn get_post_by_id(
         &mut self,
         id: Ulid,
-        interaction_user_id: Ulid,
+        interaction_user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<Post, errors::GrpcError> {
         let post_id = id.to_string();
         let request = PostRequest {
@@ -121,7 +146,11 @@ impl PostsService for PostsServiceImpl {
         let post_response = self.post_client.get_post_by_id(request).await?.into_inner();
         let request = GetPostInteractionsRequest {
             post_id,
-            interaction_user_id: interaction_user_id.to_string(),
+            interaction_user_id: interaction_user_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            viewer_id: viewer_id.map(|id| id.to_string()).unwrap_or_default(),
+            ..Default::default()
         }
         .into_request()
         .inject_trace_context();
@@ -151,6 +180,7 @@ impl PostsService for PostsServiceImpl {
         &mut self,
         ids: Vec<String>,
         interaction_user_id: Ulid,
+        viewer_id: Option<Ulid>,
     ) -> Result<Vec<Post>, errors::GrpcError> {
         let request = BatchPostsRequest { post_ids: ids }
             .into_request()
@@ -166,7 +196,7 @@ impl PostsService for PostsServiceImpl {
             return Ok(Vec::new())
         }
 
-        let interactions = get_posts_interactions(&mut self.reply_client, &posts_response.posts, interaction_user_id.to_string())
+        let interactions = get_posts_interactions(&mut self.reply_client, &posts_response.posts, interaction_user_id.to_string(), viewer_id)
             .await
             .unwrap_or_else(|e| {
                 warn!("Failed to retrieve post interactions: {:?}", e);
@@ -187,4 +217,95 @@ impl PostsService for PostsServiceImpl {
             &users_map,
         ))
     }
+
+    async fn get_post_likes(
+        &mut self,
+        post_id: Ulid,
+        next: Option<String>,
+        per_page: Option<u32>,
+    ) -> Result<LikesPage<Liker>, errors::GrpcError> {
+        let request = GetLikesRequest {
+            id: post_id.to_string(),
+            next: next.unwrap_or_default(),
+            limit: per_page.unwrap_or_default(),
+        }
+        .into_request()
+        .inject_trace_context();
+
+        let likes = self
+            .reply_client
+            .get_post_likes(request)
+            .await?
+            .into_inner();
+
+        let user_ids: HashSet<String> = likes
+            .likes
+            .iter()
+            .map(|like| like.user_id.clone())
+            .collect();
+        let users_map = fetch_user_summaries(&mut self.user_client, user_ids)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to retrieve user data: {:?}", e);
+                HashMap::default()
+            });
+
+        let likers = likes
+            .likes
+            .into_iter()
+            .map(|like| Liker::from(like, &users_map))
+            .collect();
+
+        Ok(LikesPage::new(likers, likes.next))
+    }
+
+    async fn get_liked_posts(
+        &mut self,
+        user_id: Ulid,
+        interaction_user_id: Ulid,
+        viewer_id: Option<Ulid>,
+        next: Option<String>,
+        per_page: Option<u32>,
+    ) -> Result<LikesPage<LikedPost>, errors::GrpcError> {
+        let request = GetLikesRequest {
+            id: user_id.to_string(),
+            next: next.unwrap_or_default(),
+            limit: per_page.unwrap_or_default(),
+        }
+        .into_request()
+        .inject_trace_context();
+
+        let likes = self
+            .reply_client
+            .get_user_liked_posts(request)
+            .await?
+            .into_inner();
+        if likes.likes.is_empty() {
+            return Ok(LikesPage::new(Vec::new(), likes.next));
+        }
+
+        let post_ids = likes
+            .likes
+            .iter()
+            .map(|like| like.post_id.clone())
+            .collect();
+        let mut posts: HashMap<String, Post> = self
+            .get_posts_by_id(post_ids, interaction_user_id, viewer_id)
+            .await?
+            .into_iter()
+            .map(|post| (post.id().to_string(), post))
+            .collect();
+
+        let liked_posts = likes
+            .likes
+            .iter()
+            .filter_map(|like| {
+                posts
+                    .remove(&like.post_id)
+                    .map(|post| LikedPost::new(post, like))
+            })
+            .collect();
+
+        Ok(LikesPage::new(liked_posts, likes.next))
+    }
 }
@@ -5,7 +5,7 @@ use crate::models::user::{
 use std::collections::{HashMap, HashSet};
 
 use crate::models::post::{PaginatedResponse, Post};
-use crate::services::InjectTraceContext;
+use zylo_common::grpc::InjectTraceContext;
 use crate::services::aggregator::post_service_client::PostServiceClient;
 use crate::services::aggregator::relationship_service_client::RelationshipServiceClient;
 use crate::services::aggregator::reply_service_client::ReplyServiceClient;
@@ -24,6 +24,7 @@ pub trait UserService: Send + Sync {
         &mut self,
         id: Ulid,
         interaction_user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<User, errors::GrpcError>;
 }
 
@@ -57,6 +58,7 @@ impl UserService for UserServiceImpl {
         &mut self,
         id: Ulid,
         interaction_user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<User, errors::GrpcError> {
         let user_id = id.to_string();
         let request = GetUserByIdRequest { user_id: user_id.clone() }
@@ -89,6 +91,7 @@ impl UserService for UserServiceImpl {
             let interaction_request = GetBatchOfPostInteractionsRequest {
                 posts_ids: paginated_posts.posts.iter().map(|p| p.id.clone()).collect(),
                 interaction_user_id: interaction_user_id.unwrap_or(id).to_string(),
+                viewer_id: viewer_id.map(|id| id.to_string()).unwrap_or_default(),
             }
                 .into_request()
                 .inject_trace_context();
@@ -128,13 +131,12 @@ impl UserService for UserServiceImpl {
         }
 
         if let Some(ref rel_data) = relationships.relationships {
-            if let Some(ref follows) = rel_data.follows {
-                if let (Some(followers), Some(following)) =
+            if let Some(ref follows) = rel_data.follows
+                && let (Some(followers), Some(following)) =
                     (follows.followers.as_ref(), follows.following.as_ref())
-                {
-                    user_ids.extend(followers.ids.iter().cloned());
-                    user_ids.extend(following.ids.iter().cloned());
-                }
+            {
+                user_ids.extend(followers.ids.iter().cloned());
+                user_ids.extend(following.ids.iter().cloned());
             }
             if let Some(ref blocks) = rel_data.blocks {
                 user_ids.extend(blocks.ids.iter().cloned());
@@ -142,13 +144,12 @@ impl UserService for UserServiceImpl {
             if let Some(ref friends) = rel_data.friends {
                 user_ids.extend(friends.ids.iter().cloned());
             }
-            if let Some(ref friend_requests) = rel_data.friend_requests {
-                if let (Some(received), Some(sent)) =
+            if let Some(ref friend_requests) = rel_data.friend_requests
+                && let (Some(received), Some(sent)) =
                     (friend_requests.received.as_ref(), friend_requests.sent.as_ref())
-                {
-                    user_ids.extend(received.ids.iter().cloned());
-                    user_ids.extend(sent.ids.iter().cloned());
-                }
+            {
+                user_ids.extend(received.ids.iter().cloned());
+                user_ids.extend(sent.ids.iter().cloned());
             }
         }
 
@@ -1,11 +1,14 @@
-use crate::services::key_vault::KeyVault;
 use crate::utils::constants::{
-    EXPOSED_PORT, FEED_SERVICE_GRPC_SERVER_ADDRESS, JWT_AUDIENCE, JWT_ISSUER, JWT_SECRET,
-    MEDIA_SERVICE_GRPC_SERVER_ADDRESS, OTEL_COLLECTOR_ADDRESS, SOCIAL_GRAPH_GRPC_SERVER_ADDRESS,
-    USER_INTERACTION_GRPC_SERVER_ADDRESS, USER_MANAGEMENT_GRPC_SERVER_ADDRESS,
+    EXPOSED_PORT, FEED_SERVICE_GRPC_SERVER_ADDRESS,
+    MEDIA_SERVICE_GRPC_SERVER_ADDRESS, OTEL_COLLECTOR_ADDRESS, PUBLIC_ROUTES, RATE_LIMIT_ROUTES,
+    REDIS_URL_SECRET, SOCIAL_GRAPH_GRPC_SERVER_ADDRESS, USER_INTERACTION_GRPC_SERVER_ADDRESS,
+    USER_MANAGEMENT_GRPC_SERVER_ADDRESS,
 };
+use axum::http::Method;
 use serde::Deserialize;
 use std::fs;
+use zylo_common::config::{Auth, RateLimits};
+use zylo_common::key_vault::KeyVault;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Server {
@@ -26,18 +29,50 @@ impl Server {
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct Auth {
-    pub secret: String,
-    pub issuer: String,
-    pub audience: String,
+pub struct PublicRoute {
+    pub method: String,
+    pub path: String,
 }
 
-impl Auth {
+impl PublicRoute {
+    fn parse(value: &str) -> Option<Self> {
+        let (method, path) = value.trim().split_once(' ')?;
+        Some(Self {
+            method: method.trim().to_uppercase(),
+            path: path.trim().to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublicAccess {
+    pub routes: Vec<PublicRoute>,
+}
+
+impl PublicAccess {
+    pub fn is_public(&self, method: &Method, path: &str) -> bool {
+        self.routes
+            .iter()
+            .any(|route| route.method.eq_ignore_ascii_case(method.as_str()) && route.path == path)
+    }
+
+    async fn from_key_vault(key_vault: &KeyVault) -> Self {
+        let routes = key_vault.get_secret(PUBLIC_ROUTES).await.unwrap();
+        Self {
+            routes: routes.split(',').filter_map(PublicRoute::parse).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Redis {
+    pub uri: String,
+}
+
+impl Redis {
     async fn from_key_vault(key_vault: &KeyVault) -> Self {
         Self {
-            secret: key_vault.get_secret(JWT_SECRET).await.unwrap(),
-            issuer: key_vault.get_secret(JWT_ISSUER).await.unwrap(),
-            audience: key_vault.get_secret(JWT_AUDIENCE).await.unwrap(),
+            uri: key_vault.get_secret(REDIS_URL_SECRET).await.unwrap(),
         }
     }
 }
@@ -95,6 +130,9 @@ impl OtelCollector {
 pub struct AppConfig {
     pub server: Server,
     pub auth: Auth,
+    pub public_access: PublicAccess,
+    pub redis: Redis,
+    pub rate_limits: RateLimits,
     pub external_grpc_servers: ExternalGrpcServers,
     pub otel_collector: OtelCollector,
 }
@@ -117,6 +155,9 @@ impl AppConfig {
         Self {
             server: Server::from_key_vault(key_vault).await,
             auth: Auth::from_key_vault(key_vault).await,
+            public_access: PublicAccess::from_key_vault(key_vault).await,
+            redis: Redis::from_key_vault(key_vault).await,
+            rate_limits: RateLimits::from_key_vault(key_vault, RATE_LIMIT_ROUTES).await,
             external_grpc_servers: ExternalGrpcServers::from_key_vault(key_vault).await,
             otel_collector: OtelCollector::from_key_vault(key_vault).await,
         }
@@ -8,8 +8,9 @@ pub const SOCIAL_GRAPH_GRPC_SERVER_ADDRESS: &str = "Aggregator-Servers--SocialGr
 pub const FEED_SERVICE_GRPC_SERVER_ADDRESS: &str = "Aggregator-Servers--FeedService";
 
 pub const EXPOSED_PORT: &str= "Aggregator-API--ExposedPort";
-pub const JWT_SECRET: &str = "Zylo-Jwt--Secret";
-pub const JWT_ISSUER: &str = "Zylo-Jwt--Issuer";
-pub const JWT_AUDIENCE: &str = "Zylo-Jwt--Audience";
+
+pub const PUBLIC_ROUTES: &str = "Aggregator-PublicAccess--Routes";
+pub const REDIS_URL_SECRET: &str = "Aggregator-Redis--ConnectionString";
+pub const RATE_LIMIT_ROUTES: &str = "Aggregator-RateLimit--Routes";
 
 pub const REQUEST_ID_HEADER: &str = "x-request-id";
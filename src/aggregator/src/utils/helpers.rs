@@ -1,27 +1,15 @@
 ﻿use std::collections::{HashMap, HashSet};
-use std::fs;
 use std::sync::Arc;
 use tonic::IntoRequest;
 use tonic::transport::Channel;
+use ulid::Ulid;
 use crate::errors;
 use crate::models::post::UserSummary;
 use crate::services::aggregator::{BatchOfPostInteractionsResponse, BatchUsersSummaryResponse, GetBatchOfPostInteractionsRequest, GetBatchUsersByIdsRequest, PostInteractionsResponse, PostResponse, ReplyResponse};
 use crate::services::aggregator::reply_service_client::ReplyServiceClient;
 use crate::services::aggregator::user_profile_service_client::UserProfileServiceClient;
-use crate::services::InjectTraceContext;
+use zylo_common::grpc::InjectTraceContext;
 
-pub fn get_container_id() -> Option<String> {
-    if let Ok(cgroup) = fs::read_to_string("/proc/self/cgroup") {
-        for line in cgroup.lines() {
-            if let Some(id) = line.split('/').last() {
-                if id.len() >= 12 {
-                    return Some(id.to_string());
-                }
-            }
-        }
-    }
-    None
-}
 pub fn collect_user_ids_from_posts(
     posts: &[PostResponse],
     interactions: &BatchOfPostInteractionsResponse,
@@ -55,7 +43,9 @@ pub fn collect_user_ids_from_post(
 }
 
 pub fn collect_user_ids_from_reply(reply: &ReplyResponse, ids: &mut HashSet<String>) {
-    ids.insert(reply.user_id.clone());
+    if !reply.deleted {
+        ids.insert(reply.user_id.clone());
+    }
     for nested in &reply.nested_replies {
         collect_user_ids_from_reply(nested, ids);
     }
@@ -96,10 +86,12 @@ pub async fn get_posts_interactions(
     reply_client: &mut ReplyServiceClient<Channel>,
     posts: &[PostResponse],
     interaction_user_id: String,
+    viewer_id: Option<Ulid>,
 ) -> Result<BatchOfPostInteractionsResponse, errors::GrpcError> {
     let request = GetBatchOfPostInteractionsRequest {
         posts_ids: posts.iter().map(|p| p.id.clone()).collect(),
         interaction_user_id,
+        viewer_id: viewer_id.map(|id| id.to_string()).unwrap_or_default(),
     }
         .into_request()
         .inject_trace_context();
@@ -37,8 +37,25 @@ func (s *Server) wrapMessageHandler(handler func(amqp.Delivery) error) func(amqp
 	}
 }
 
+// eventEnvelope wraps the events published by the Rust services, see src/schemas/events.
+type eventEnvelope struct {
+	ID      string          `json:"id"`
+	Type    string          `json:"type"`
+	Version int             `json:"version"`
+	Payload json.RawMessage `json:"payload"`
+}
+
+// unmarshalMessage decodes the payload of an enveloped event, or the whole body of a message
+// published without an envelope.
 func unmarshalMessage[T any](delivery amqp.Delivery, target T) error {
-	if err := json.Unmarshal(delivery.Body, target); err != nil {
+	body := delivery.Body
+
+	var envelope eventEnvelope
+	if err := json.Unmarshal(body, &envelope); err == nil && envelope.Type != "" && envelope.Payload != nil {
+		body = envelope.Payload
+	}
+
+	if err := json.Unmarshal(body, target); err != nil {
 		return err
 	}
 	return nil
@@ -2,7 +2,6 @@ use crate::decorators::trace_server_error;
 use crate::errors;
 use crate::services::cache_service::CacheService;
 use crate::utils::constants::OTEL_SERVICE_NAME;
-use crate::utils::helpers::get_container_id;
 use async_trait::async_trait;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::{KeyValue, global};
@@ -11,6 +10,7 @@ use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
+use zylo_common::utils::get_container_id;
 
 pub struct ObservableCacheService<C: CacheService + 'static> {
     inner: C,
@@ -10,7 +10,7 @@ use tonic::{Code, Request, Response, Status};
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use crate::utils::constants::OTEL_SERVICE_NAME;
-use crate::utils::helpers::get_container_id;
+use zylo_common::utils::get_container_id;
 
 pub struct ObservablePostServer<P: PostService + 'static> {
     inner: P,
@@ -5,7 +5,6 @@ use crate::models::post::{DeletedPostsIds, Post};
 use crate::repositories::post_repo::{MongoPostRepository, PostRepository};
 use crate::services::cache_service::CacheService;
 use crate::services::s3_service::S3Service;
-use crate::utils::helpers::get_container_id;
 use crate::utils::request::{CreatePostRequest, PaginatedResponse, UpdatePostRequest};
 use async_trait::async_trait;
 use mongodb::Database;
@@ -17,7 +16,9 @@ use tokio::time::Instant;
 use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
+use zylo_common::auth::Principal;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedPostRepositoryBuilder<P: PostRepository> {
     post_repo: P,
@@ -231,14 +232,18 @@ impl<P: PostRepository + 'static> PostRepository for ObservablePostRepository<P>
         .await
     }
 
-    async fn delete(&self, post_id: &Ulid) -> Result<(), AppError> {
+    async fn delete(
+        &self,
+        post_id: &Ulid,
+        moderator: Option<&Principal>,
+    ) -> Result<Post, AppError> {
         self.track_method(
             "delete",
             "mongo.find_one_and_delete posts",
             "find_one_and_delete",
             "posts",
             Some(&post_id.to_string()),
-            self.inner.delete(post_id),
+            self.inner.delete(post_id, moderator),
         )
         .await
     }
@@ -305,7 +310,7 @@ impl<P: PostRepository, C: CacheService> PostRepository for CachedPostRepository
 
     async fn get(&self, post_id: &Ulid) -> Result<Post, AppError> {
         let cache_key = &post_id.to_string();
-        if let Some(post) = self.cache_service.hget::<Post>("posts", &cache_key).await? {
+        if let Some(post) = self.cache_service.hget::<Post>("posts", cache_key).await? {
             return Ok(post);
         }
 
@@ -381,8 +386,12 @@ impl<P: PostRepository, C: CacheService> PostRepository for CachedPostRepository
         Ok(posts)
     }
 
-    async fn delete(&self, post_id: &Ulid) -> Result<(), AppError> {
-        self.inner.delete(post_id).await?;
+    async fn delete(
+        &self,
+        post_id: &Ulid,
+        moderator: Option<&Principal>,
+    ) -> Result<Post, AppError> {
+        let deleted_post = self.inner.delete(post_id, moderator).await?;
 
         self.cache_service
             .hdelete_all("batch-posts", &format!("*{}*", post_id))
@@ -396,7 +405,7 @@ impl<P: PostRepository, C: CacheService> PostRepository for CachedPostRepository
             .hdelete("posts", &post_id.to_string())
             .await?;
 
-        Ok(())
+        Ok(deleted_post)
     }
 
     async fn delete_all_from_user(&self, user_id: &Ulid) -> Result<DeletedPostsIds, AppError> {
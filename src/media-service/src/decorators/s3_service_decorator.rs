@@ -4,7 +4,6 @@ use crate::models::file::{File, FileMetadata, PresignedUrl};
 use crate::services::cache_service::CacheService;
 use crate::services::s3_service::{S3FileService, S3Service};
 use crate::settings::S3Settings;
-use crate::utils::helpers::get_container_id;
 use async_trait::async_trait;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::{KeyValue, global};
@@ -15,6 +14,7 @@ use tracing::log::warn;
 use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedS3ServiceBuilder<S: S3Service> {
     s3_service: S,
@@ -3,7 +3,6 @@ use crate::errors;
 use crate::errors::AppError;
 use crate::repositories::user_repo::{MongoUserRepository, UsersRepository};
 use crate::services::cache_service::CacheService;
-use crate::utils::helpers::get_container_id;
 use async_trait::async_trait;
 use mongodb::Database;
 use opentelemetry::metrics::{Counter, Histogram};
@@ -15,6 +14,7 @@ use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedUserRepository<U: UsersRepository> {
     user_repo: U,
@@ -1,4 +1,4 @@
-use crate::errors::app::ProblemResponse;
+use crate::errors::ProblemResponse;
 use axum::http::StatusCode;
 use thiserror::Error;
 
@@ -9,6 +9,15 @@ pub enum AmqError {
 
     #[error("Failed to deserialize message: {0}")]
     DeserializeError(#[from] serde_json::Error),
+
+    #[error("Message {0} was not confirmed by RabbitMQ")]
+    NotConfirmed(String),
+
+    #[error("RabbitMQ is not connected")]
+    Disconnected,
+
+    #[error("RabbitMQ topology is inconsistent: {0}")]
+    Topology(String),
 }
 
 impl ProblemResponse for AmqError {
@@ -16,6 +25,9 @@ impl ProblemResponse for AmqError {
         match self {
             AmqError::ConnectionError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AmqError::DeserializeError(_) => StatusCode::BAD_REQUEST,
+            AmqError::NotConfirmed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AmqError::Disconnected => StatusCode::SERVICE_UNAVAILABLE,
+            AmqError::Topology(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
@@ -23,6 +35,9 @@ impl ProblemResponse for AmqError {
         match self {
             AmqError::ConnectionError(_) => "Internal Server Error",
             AmqError::DeserializeError(_) => "Internal Server Error",
+            AmqError::NotConfirmed(_) => "Internal Server Error",
+            AmqError::Disconnected => "Service Unavailable",
+            AmqError::Topology(_) => "Internal Server Error",
         }
     }
 
@@ -1,40 +1,14 @@
 use std::env::VarError;
 use axum::extract::rejection::JsonRejection;
 use crate::errors;
+use crate::errors::ProblemResponse;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::Json;
-use opentelemetry::trace::TraceContextExt;
-use serde_json::json;
 use thiserror::Error;
 use reqwest::Error as ReqwestError;
 use tonic::Status;
-use tracing::{error, Span};
-use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing::error;
 
-pub trait ProblemResponse {
-    fn status_code(&self) -> StatusCode;
-    fn title(&self) -> &str;
-    fn detail(&self) -> String;
-
-    fn public_detail(&self) -> String {
-        String::from("An unexpected server error occurred. Please try again later.")
-    }
-
-    fn to_response(&self) -> Response {
-        let context = Span::current().context();
-        let trace_id = context.span().span_context().trace_id().to_string();
-        let body = json!({
-            "type": format!("https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/{}", self.status_code()),
-            "title": self.title(),
-            "status": self.status_code().as_u16(),
-            "detail": self.public_detail(),
-            "traceId": trace_id
-        });
-
-        (self.status_code(), Json(body)).into_response()
-    }
-}
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error(transparent)]
@@ -3,15 +3,12 @@ mod amq;
 mod mongo;
 mod redis;
 mod s3;
-mod auth;
 mod validation;
-mod request;
 
 pub use amq::*;
 pub use app::AppError;
-pub use app::ProblemResponse;
 pub use mongo::MongoError;
-pub use auth::AuthError;
+pub use zylo_common::errors::{AuthError, ProblemResponse};
 pub use validation::ValidationError;
 pub use s3::S3Error;
 
@@ -1,4 +1,4 @@
-use crate::errors::app::ProblemResponse;
+use crate::errors::ProblemResponse;
 use axum::http::StatusCode;
 use thiserror::Error;
 
@@ -1,20 +1,24 @@
-use crate::app::{init_logs, init_metrics, init_trace, init_traces, run_app};
+use crate::app::run_app;
 use crate::decorators::cache_service_decorator::ObservableCacheService;
 use crate::decorators::grpc_server_decorator::ObservablePostServer;
 use crate::decorators::post_repo_decorator::DecoratedPostRepositoryBuilder;
 use crate::decorators::s3_service_decorator::DecoratedS3ServiceBuilder;
 use crate::decorators::user_repo_decorator::DecoratedUserRepository;
+use crate::repositories::outbox_repo::MongoOutboxRepository;
+use crate::repositories::processed_message_repo::MongoProcessedMessagesRepository;
 use crate::services::amq::{AmqClient, RabbitMqClient};
 use crate::services::cache_service::RedisCacheService;
 use crate::services::grpc_server::GrpcPostServer;
+use crate::utils::constants::OTEL_SERVICE_NAME;
 use crate::utils::helpers::init_db;
 use dotenv::dotenv;
 use models::app_state::AppState;
 use settings::AppConfig;
 use std::sync::Arc;
+use zylo_common::outbox::OutboxRelay;
+use zylo_common::telemetry::{init_logs, init_metrics, init_traces, init_tracing};
 
 mod app;
-mod auth;
 mod decorators;
 mod errors;
 mod models;
@@ -24,15 +28,18 @@ mod services;
 mod settings;
 mod utils;
 
+#[cfg(test)]
+mod test_support;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
     let config = AppConfig::new().await;
 
-    let trace_provider = init_traces(&config.otel_collector.address);
-    let meter_provider = init_metrics(&config.otel_collector.address);
-    let logger_provider = init_logs(&config.otel_collector.address);
-    init_trace(&logger_provider, &trace_provider);
+    let trace_provider = init_traces(OTEL_SERVICE_NAME, &config.otel_collector.address);
+    let meter_provider = init_metrics(OTEL_SERVICE_NAME, &config.otel_collector.address);
+    let logger_provider = init_logs(OTEL_SERVICE_NAME, &config.otel_collector.address);
+    init_tracing(&logger_provider, &trace_provider);
 
     let mongo_db = init_db(&config.database).await;
     let cache_service = Arc::new(ObservableCacheService::new(RedisCacheService::new(
@@ -54,6 +61,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .build(),
     );
 
+    let outbox_repo = Arc::new(MongoOutboxRepository::new(&mongo_db));
+    outbox_repo.ensure_indexes().await?;
+
+    let processed_messages = Arc::new(MongoProcessedMessagesRepository::new(&mongo_db));
+    processed_messages.ensure_indexes().await?;
+
     let user_repo = Arc::new(
         DecoratedUserRepository::new(mongo_db)
             .observable()
@@ -63,10 +76,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let grpc_server = ObservablePostServer::new(GrpcPostServer::new(post_repo.clone()));
 
-    let amq_client = Arc::new(RabbitMqClient::new(&config.amq).await?);
+    let amq_client = Arc::new(RabbitMqClient::new(&config.amq, processed_messages).await?);
     amq_client
         .setup_listeners(user_repo.clone(), post_repo.clone())
         .await?;
+    amq_client.spawn_supervisor();
+
+    OutboxRelay::new(outbox_repo, amq_client.clone()).spawn();
 
     let app_state = AppState::new(post_repo, user_repo, cache_service, amq_client, config).await;
 
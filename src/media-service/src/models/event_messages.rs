@@ -1,6 +1,9 @@
 use crate::models::post::Post;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
+use zylo_common::auth::Principal;
+use zylo_common::events::{Event, format_timestamp};
 
 #[derive(Debug, Serialize)]
 pub struct PostCreatedMessage {
@@ -23,6 +26,11 @@ impl From<&Post> for PostCreatedMessage {
     }
 }
 
+impl Event for PostCreatedMessage {
+    const TYPE: &'static str = "post.created";
+    const VERSION: u32 = 1;
+}
+
 #[derive(Debug, Serialize)]
 pub struct PostUpdatedMessage {
     pub id: Ulid,
@@ -44,6 +52,11 @@ impl From<&Post> for PostUpdatedMessage {
     }
 }
 
+impl Event for PostUpdatedMessage {
+    const TYPE: &'static str = "post.updated";
+    const VERSION: u32 = 1;
+}
+
 #[derive(Debug, Serialize)]
 pub struct PostDeletedMessage {
     pub id: Ulid,
@@ -57,12 +70,189 @@ impl PostDeletedMessage {
     }
 }
 
+impl Event for PostDeletedMessage {
+    const TYPE: &'static str = "post.deleted";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEventMessage {
+    #[serde(rename = "actorId")]
+    pub actor_id: String,
+    #[serde(rename = "actorRoles")]
+    pub actor_roles: Vec<String>,
+    pub action: String,
+    #[serde(rename = "resourceType")]
+    pub resource_type: String,
+    #[serde(rename = "resourceId")]
+    pub resource_id: Ulid,
+    #[serde(rename = "resourceOwnerId")]
+    pub resource_owner_id: Ulid,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: String,
+}
+
+impl AuditEventMessage {
+    pub fn new(actor: &Principal, action: &str, resource_type: &str, resource_id: Ulid, resource_owner_id: Ulid) -> Self {
+        Self {
+            actor_id: actor.user_id.clone(),
+            actor_roles: actor.roles.clone(),
+            action: action.to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id,
+            resource_owner_id,
+            occurred_at: format_timestamp(Utc::now()),
+        }
+    }
+}
+
+impl Event for AuditEventMessage {
+    const TYPE: &'static str = "audit.moderation";
+    const VERSION: u32 = 1;
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UserDeletedMessage {
     pub id: Ulid,
 }
 
+impl Event for UserDeletedMessage {
+    const TYPE: &'static str = "user.deleted";
+    const VERSION: u32 = 1;
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserCreatedMessage {
     pub id: Ulid,
 }
+
+impl Event for UserCreatedMessage {
+    const TYPE: &'static str = "user.created";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::{Value, json};
+    use zylo_common::events::{EventEnvelope, decode};
+
+    fn validator(schema_name: &str) -> jsonschema::Validator {
+        let path = format!(
+            "{}/../schemas/events/{}.schema.json",
+            env!("CARGO_MANIFEST_DIR"),
+            schema_name
+        );
+        let schema: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        jsonschema::options()
+            .should_validate_formats(true)
+            .build(&schema)
+            .unwrap()
+    }
+
+    fn assert_valid(schema_name: &str, instance: &Value) {
+        let validator = validator(schema_name);
+        let errors: Vec<String> = validator
+            .iter_errors(instance)
+            .map(|error| error.to_string())
+            .collect();
+
+        assert!(
+            errors.is_empty(),
+            "{} does not match {}: {}",
+            instance,
+            schema_name,
+            errors.join(", ")
+        );
+    }
+
+    fn payload_schema<T: Event>() -> String {
+        format!("{}.v{}", T::TYPE, T::VERSION)
+    }
+
+    /// Publishes `event` the way the outbox does and checks the envelope and payload schemas.
+    fn assert_published_matches_schema<T: Event + Serialize>(event: T) -> Value {
+        let envelope = serde_json::to_value(EventEnvelope::new(event)).unwrap();
+        assert_valid("envelope", &envelope);
+        assert_valid(&payload_schema::<T>(), &envelope["payload"]);
+
+        envelope["payload"].clone()
+    }
+
+    /// Wraps a payload valid against the schema of `T` in an envelope and decodes it.
+    fn decode_schema_example<T: Event + DeserializeOwned>(payload: Value) -> T {
+        assert_valid(&payload_schema::<T>(), &payload);
+        let envelope = json!({
+            "id": Ulid::new(),
+            "type": T::TYPE,
+            "version": T::VERSION,
+            "occurredAt": format_timestamp(Utc::now()),
+            "payload": payload,
+        });
+        assert_valid("envelope", &envelope);
+
+        decode::<T>(&serde_json::to_vec(&envelope).unwrap())
+            .unwrap()
+            .payload
+    }
+
+    #[test]
+    fn post_events_match_their_schemas() {
+        let id = Ulid::new();
+        let user_id = Ulid::new();
+        let created_at = format_timestamp(Utc::now());
+
+        let created = assert_published_matches_schema(PostCreatedMessage {
+            id,
+            user_id,
+            content: String::from("Hello"),
+            created_at: created_at.clone(),
+        });
+        assert_eq!(created["userId"], user_id.to_string());
+
+        let updated = assert_published_matches_schema(PostUpdatedMessage {
+            id,
+            content: String::from("Hello again"),
+            created_at: created_at.clone(),
+            updated_at: format_timestamp(Utc::now()),
+        });
+        assert_eq!(updated["createdAt"], created_at);
+
+        let deleted = assert_published_matches_schema(PostDeletedMessage::new(id, user_id));
+        assert_eq!(deleted["id"], id.to_string());
+    }
+
+    #[test]
+    fn audit_event_matches_its_schema() {
+        let moderator = Principal {
+            user_id: Ulid::new().to_string(),
+            roles: vec![String::from("moderator")],
+            scopes: Vec::new(),
+        };
+
+        let audit = assert_published_matches_schema(AuditEventMessage::new(
+            &moderator,
+            "delete",
+            "post",
+            Ulid::new(),
+            Ulid::new(),
+        ));
+        assert_eq!(audit["actorRoles"], json!(["moderator"]));
+    }
+
+    #[test]
+    fn user_events_from_the_schemas_decode() {
+        let id = Ulid::new();
+
+        let created: UserCreatedMessage = decode_schema_example(json!({ "id": id }));
+        assert_eq!(created.id, id);
+
+        let deleted: UserDeletedMessage = decode_schema_example(json!({ "id": id }));
+        assert_eq!(deleted.id, id);
+
+        let published = assert_published_matches_schema(created);
+        assert_eq!(published["id"], id.to_string());
+    }
+}
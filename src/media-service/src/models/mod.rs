@@ -1,4 +1,5 @@
 pub mod app_state;
 pub mod event_messages;
 pub mod file;
+pub mod outbox;
 pub mod post;
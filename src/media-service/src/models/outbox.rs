@@ -0,0 +1,56 @@
+use crate::errors;
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use zylo_common::events::{Event, EventEnvelope};
+use zylo_common::outbox::OutboxEntry;
+
+/// Event waiting in the `outbox` collection until the relay publishes it to RabbitMQ.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    #[serde(rename = "_id")]
+    pub id: Ulid,
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: String,
+    pub created_at: DateTime,
+    pub sent_at: Option<DateTime>,
+    pub locked_until: Option<DateTime>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl OutboxMessage {
+    pub fn new<T: Event + Serialize>(
+        exchange: &str,
+        routing_key: &str,
+        event: T,
+    ) -> Result<Self, errors::AmqError> {
+        let envelope = EventEnvelope::new(event);
+        Ok(Self {
+            id: envelope.id,
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            payload: serde_json::to_string(&envelope)?,
+            created_at: DateTime::now(),
+            sent_at: None,
+            locked_until: None,
+            attempts: 0,
+            last_error: None,
+        })
+    }
+}
+
+impl OutboxEntry for OutboxMessage {
+    fn id(&self) -> Ulid {
+        self.id
+    }
+
+    fn routing_key(&self) -> &str {
+        &self.routing_key
+    }
+
+    fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
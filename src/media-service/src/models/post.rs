@@ -1,9 +1,44 @@
+use crate::errors;
 use crate::models::file::{FileMetadata, FileMetadataResponse};
 use crate::utils::request::{CreatePostRequest};
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use ulid::Ulid;
 
+/// Who may see a post. Anonymous visitors are only ever shown public posts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Members,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Members => write!(f, "members"),
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = errors::ValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "public" => Ok(Visibility::Public),
+            "members" => Ok(Visibility::Members),
+            _ => Err(errors::ValidationError::Failed(
+                "Visibility must be either public or members".to_string(),
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Post {
     #[serde(rename = "_id")]
@@ -11,6 +46,8 @@ pub struct Post {
     pub user_id: Ulid,
     pub text: String,
     pub files_metadata: Vec<FileMetadata>,
+    #[serde(default)]
+    pub visibility: Visibility,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -24,6 +61,7 @@ impl From<CreatePostRequest> for Post {
             user_id: value.user_id,
             text: value.text.clone(),
             files_metadata: value.files.into_iter().map(FileMetadata::from).collect(),
+            visibility: value.visibility,
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
         }
@@ -39,6 +77,7 @@ pub struct PostResponse {
     pub text: String,
     #[serde(rename = "filesMetadata")]
     pub files_metadata: Vec<FileMetadataResponse>,
+    pub visibility: Visibility,
     #[serde(rename = "createdAt")]
     pub created_at: String,
     #[serde(rename = "updatedAt")]
@@ -52,6 +91,7 @@ impl From<Post> for PostResponse {
             user_id: value.user_id.to_string(),
             text: value.text,
             files_metadata: value.files_metadata.into_iter().map(FileMetadataResponse::from).collect(),
+            visibility: value.visibility,
             created_at: value.created_at,
             updated_at: value.updated_at,
         }
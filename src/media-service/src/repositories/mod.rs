@@ -1,2 +1,4 @@
+pub mod outbox_repo;
 pub mod post_repo;
+pub mod processed_message_repo;
 pub mod user_repo;
\ No newline at end of file
@@ -0,0 +1,132 @@
+use crate::errors;
+use crate::models::outbox::OutboxMessage;
+use async_trait::async_trait;
+use mongodb::bson::{doc, DateTime};
+use mongodb::options::ReturnDocument;
+use mongodb::{Collection, Database, IndexModel};
+use std::time::Duration;
+use ulid::Ulid;
+use zylo_common::outbox::OutboxStore;
+
+pub struct MongoOutboxRepository {
+    collection: Collection<OutboxMessage>,
+}
+
+impl MongoOutboxRepository {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            collection: db.collection("outbox"),
+        }
+    }
+
+    pub async fn ensure_indexes(&self) -> Result<(), errors::AppError> {
+        let pending_messages = IndexModel::builder()
+            .keys(doc! { "sent_at": 1, "locked_until": 1, "_id": 1 })
+            .build();
+
+        self.collection
+            .create_index(pending_messages)
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(())
+    }
+
+    /// Locks the oldest pending message for `lock_for`, so that other relays skip it.
+    async fn claim_next(&self, lock_for: Duration) -> Result<Option<OutboxMessage>, errors::AppError> {
+        let filter = doc! {
+            "sent_at": null,
+            "$or": [
+                { "locked_until": null },
+                { "locked_until": { "$lte": DateTime::now() } },
+            ],
+        };
+        let update = doc! {
+            "$set": { "locked_until": from_now(lock_for) },
+            "$inc": { "attempts": 1 },
+        };
+
+        let message = self
+            .collection
+            .find_one_and_update(filter, update)
+            .sort(doc! { "_id": 1 })
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(message)
+    }
+}
+
+fn from_now(duration: Duration) -> DateTime {
+    DateTime::from_millis(DateTime::now().timestamp_millis() + duration.as_millis() as i64)
+}
+
+#[async_trait]
+impl OutboxStore for MongoOutboxRepository {
+    type Message = OutboxMessage;
+    type Error = errors::AppError;
+
+    /// Claims the messages one at a time, as every claim has to be a single atomic update.
+    async fn claim_batch(
+        &self,
+        limit: usize,
+        lock_for: Duration,
+    ) -> Result<Vec<OutboxMessage>, errors::AppError> {
+        let mut batch = Vec::new();
+        while batch.len() < limit {
+            let Some(message) = self.claim_next(lock_for).await? else {
+                break;
+            };
+            batch.push(message);
+        }
+
+        Ok(batch)
+    }
+
+    async fn mark_sent(&self, id: &Ulid) -> Result<(), errors::AppError> {
+        self.collection
+            .update_one(
+                doc! { "_id": id.to_string() },
+                doc! {
+                    "$set": { "sent_at": DateTime::now(), "locked_until": null, "last_error": null },
+                },
+            )
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: &Ulid,
+        error: &str,
+        retry_in: Duration,
+    ) -> Result<(), errors::AppError> {
+        self.collection
+            .update_one(
+                doc! { "_id": id.to_string() },
+                doc! {
+                    "$set": { "locked_until": from_now(retry_in), "last_error": error },
+                },
+            )
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(())
+    }
+
+    async fn delete_sent_before(&self, older_than: Duration) -> Result<u64, errors::AppError> {
+        let cutoff = DateTime::from_millis(
+            DateTime::now().timestamp_millis() - older_than.as_millis() as i64,
+        );
+        let result = self
+            .collection
+            .delete_many(doc! { "sent_at": { "$lt": cutoff } })
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(result.deleted_count)
+    }
+}
@@ -1,14 +1,20 @@
 use crate::errors;
+use crate::models::event_messages::{
+    AuditEventMessage, PostCreatedMessage, PostDeletedMessage, PostUpdatedMessage,
+};
+use crate::models::outbox::OutboxMessage;
 use crate::models::post::{DeletedPostsIds, Post};
 use crate::services::s3_service::S3Service;
+use crate::utils::constants::{AUDIT_EXCHANGE_NAME, POST_EXCHANGE_NAME};
 use crate::utils::request::{CreatePostRequest, PaginatedResponse, UpdatePostRequest};
 use async_trait::async_trait;
 use futures_util::TryStreamExt;
 use mongodb::bson::doc;
-use mongodb::{Collection,  Database};
+use mongodb::{Client, ClientSession, Collection, Database};
 use std::sync::Arc;
 use mongodb::options::ReturnDocument;
 use ulid::Ulid;
+use zylo_common::auth::Principal;
 
 #[async_trait]
 pub trait PostRepository: Send + Sync {
@@ -22,7 +28,13 @@ pub trait PostRepository: Send + Sync {
         last_post_id: Option<Ulid>,
     ) -> Result<PaginatedResponse<Post>, errors::AppError>;
     async fn get_batch_posts(&self, post_ids: Vec<Ulid>) -> Result<Vec<Post>, errors::AppError>;
-    async fn delete(&self, post_id: &Ulid) -> Result<(), errors::AppError>;
+    /// Deletes a post and returns it. A deletion by a moderator is added to the audit trail in
+    /// the transaction removing the post.
+    async fn delete(
+        &self,
+        post_id: &Ulid,
+        moderator: Option<&Principal>,
+    ) -> Result<Post, errors::AppError>;
     async fn delete_all_from_user(
         &self,
         user_id: &Ulid,
@@ -31,18 +43,39 @@ pub trait PostRepository: Send + Sync {
 
 #[derive(Debug, Clone)]
 pub struct MongoPostRepository<S: S3Service + 'static> {
+    client: Client,
     collection: Collection<Post>,
+    outbox: Collection<OutboxMessage>,
     s3_service: Arc<S>,
 }
 
 impl<S: S3Service + 'static> MongoPostRepository<S> {
     pub fn new(db: &Database, s3_service: Arc<S>) -> Self {
         Self {
+            client: db.client().clone(),
             collection: db.collection("posts"),
+            outbox: db.collection("outbox"),
             s3_service,
         }
     }
 
+    /// Post changes and their events are written in one transaction, the outbox relay
+    /// publishes the events afterwards.
+    async fn start_transaction(&self) -> Result<ClientSession, errors::MongoError> {
+        let mut session = self.client.start_session().await?;
+        session.start_transaction().await?;
+        Ok(session)
+    }
+
+    async fn enqueue_event(
+        &self,
+        session: &mut ClientSession,
+        event: OutboxMessage,
+    ) -> Result<(), errors::MongoError> {
+        self.outbox.insert_one(event).session(session).await?;
+        Ok(())
+    }
+
     async fn attach_presigned_urls(&self, posts: &mut [Post]) -> Result<(), errors::AppError> {
         for post in posts {
             for file_metadata in &mut post.files_metadata {
@@ -62,9 +95,22 @@ impl<S: S3Service + 'static> PostRepository for MongoPostRepository<S> {
     async fn create(&self, request: CreatePostRequest) -> Result<Post, errors::AppError> {
         let files = request.files.clone();
         let post = Post::from(request);
+        let event = OutboxMessage::new(
+            POST_EXCHANGE_NAME,
+            "post.created",
+            PostCreatedMessage::from(&post),
+        )?;
 
+        let mut session = self.start_transaction().await?;
         self.collection
             .insert_one(&post)
+            .session(&mut session)
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        self.enqueue_event(&mut session, event).await?;
+        session
+            .commit_transaction()
             .await
             .map_err(errors::MongoError::DatabaseError)?;
 
@@ -76,19 +122,34 @@ impl<S: S3Service + 'static> PostRepository for MongoPostRepository<S> {
     }
 
     async fn update(&self, request: UpdatePostRequest) -> Result<Post, errors::AppError> {
+        let mut changes = doc! { "text": request.text.to_string() };
+        if let Some(visibility) = request.visibility {
+            changes.insert("visibility", visibility.to_string());
+        }
+
+        let mut session = self.start_transaction().await?;
         let update = self
             .collection
-            .find_one_and_update(
-                doc! {"_id": request.id.to_string()},
-                doc! {"$set": { "text": request.text.to_string() }},
-            )
+            .find_one_and_update(doc! {"_id": request.id.to_string()}, doc! {"$set": changes})
             .return_document(ReturnDocument::After)
+            .session(&mut session)
             .await
             .map_err(errors::MongoError::DatabaseError)?
             .ok_or(errors::MongoError::NotFound(String::from(
                 "Post with given id could not be found",
             )))?;
 
+        let event = OutboxMessage::new(
+            POST_EXCHANGE_NAME,
+            "post.updated",
+            PostUpdatedMessage::from(&update),
+        )?;
+        self.enqueue_event(&mut session, event).await?;
+        session
+            .commit_transaction()
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
         for file in request.files {
             self.s3_service.upload(file).await?;
         }
@@ -195,23 +256,54 @@ impl<S: S3Service + 'static> PostRepository for MongoPostRepository<S> {
         Ok(posts)
     }
 
-    async fn delete(&self, post_id: &Ulid) -> Result<(), errors::AppError> {
+    async fn delete(
+        &self,
+        post_id: &Ulid,
+        moderator: Option<&Principal>,
+    ) -> Result<Post, errors::AppError> {
+        let mut session = self.start_transaction().await?;
         let deleted_post = self
             .collection
             .find_one_and_delete(doc! {"_id": post_id.to_string()})
+            .session(&mut session)
             .await
             .map_err(errors::MongoError::DatabaseError)?
             .ok_or(errors::MongoError::NotFound(
                 "Post with given id could not be found".to_string(),
             ))?;
 
+        let event = OutboxMessage::new(
+            POST_EXCHANGE_NAME,
+            "post.deleted",
+            PostDeletedMessage::new(deleted_post.id, deleted_post.user_id),
+        )?;
+        self.enqueue_event(&mut session, event).await?;
+        if let Some(moderator) = moderator {
+            let audit = OutboxMessage::new(
+                AUDIT_EXCHANGE_NAME,
+                "post.moderated",
+                AuditEventMessage::new(
+                    moderator,
+                    "post.deleted",
+                    "post",
+                    deleted_post.id,
+                    deleted_post.user_id,
+                ),
+            )?;
+            self.enqueue_event(&mut session, audit).await?;
+        }
+        session
+            .commit_transaction()
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
         for file_metadata in &deleted_post.files_metadata {
             self.s3_service
                 .delete(&format!("media_images/{}", file_metadata.id))
                 .await?;
         }
 
-        Ok(())
+        Ok(deleted_post)
     }
 
     async fn delete_all_from_user(
@@ -247,3 +339,66 @@ impl<S: S3Service + 'static> PostRepository for MongoPostRepository<S> {
         Ok(deleted_posts_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{NoFiles, test_db};
+
+    fn principal(user_id: Ulid, roles: &[&str]) -> Principal {
+        Principal {
+            user_id: user_id.to_string(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            scopes: Vec::new(),
+        }
+    }
+
+    async fn routing_keys(db: &Database) -> Vec<String> {
+        let outbox: Vec<OutboxMessage> = db
+            .collection::<OutboxMessage>("outbox")
+            .find(doc! {})
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        outbox
+            .into_iter()
+            .map(|message| message.routing_key)
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires MongoDB running as a replica set"]
+    async fn deletion_by_moderator_is_audited_with_the_deletion() {
+        let db = test_db().await;
+        let repo = MongoPostRepository::new(&db, Arc::new(NoFiles));
+        let owner_id = Ulid::new();
+
+        let own_post = repo.create(CreatePostRequest::new(owner_id)).await.unwrap();
+        repo.delete(&own_post.id, None).await.unwrap();
+        assert!(
+            !routing_keys(&db)
+                .await
+                .contains(&String::from("post.moderated"))
+        );
+
+        let moderated_post = repo.create(CreatePostRequest::new(owner_id)).await.unwrap();
+        let moderator = principal(Ulid::new(), &["moderator"]);
+        let deleted = repo
+            .delete(&moderated_post.id, Some(&moderator))
+            .await
+            .unwrap();
+        assert_eq!(deleted.user_id, owner_id);
+
+        let keys = routing_keys(&db).await;
+        assert_eq!(
+            keys.iter().filter(|key| *key == "post.moderated").count(),
+            1
+        );
+        assert_eq!(keys.iter().filter(|key| *key == "post.deleted").count(), 2);
+
+        db.drop().await.unwrap();
+    }
+}
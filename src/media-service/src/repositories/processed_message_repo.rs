@@ -0,0 +1,101 @@
+use crate::errors;
+use async_trait::async_trait;
+use mongodb::bson::{DateTime, doc};
+use mongodb::options::IndexOptions;
+use mongodb::{Collection, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const PROCESSED_MESSAGES_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Ledger of message ids each consumer has already handled, used to skip redeliveries.
+#[async_trait]
+pub trait ProcessedMessagesRepository: Send + Sync {
+    async fn is_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<bool, errors::AppError>;
+    async fn mark_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<(), errors::AppError>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProcessedMessage {
+    consumer: String,
+    message_id: String,
+    processed_at: DateTime,
+}
+
+pub struct MongoProcessedMessagesRepository {
+    collection: Collection<ProcessedMessage>,
+}
+
+impl MongoProcessedMessagesRepository {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            collection: db.collection("processed_messages"),
+        }
+    }
+
+    pub async fn ensure_indexes(&self) -> Result<(), errors::AppError> {
+        let processed_at_ttl = IndexModel::builder()
+            .keys(doc! { "processed_at": 1 })
+            .options(
+                IndexOptions::builder()
+                    .expire_after(PROCESSED_MESSAGES_TTL)
+                    .build(),
+            )
+            .build();
+
+        let consumer_message = IndexModel::builder()
+            .keys(doc! { "consumer": 1, "message_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+
+        self.collection
+            .create_indexes([processed_at_ttl, consumer_message])
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProcessedMessagesRepository for MongoProcessedMessagesRepository {
+    async fn is_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<bool, errors::AppError> {
+        let count = self
+            .collection
+            .count_documents(doc! { "consumer": consumer, "message_id": message_id })
+            .limit(1)
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(count > 0)
+    }
+
+    async fn mark_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<(), errors::AppError> {
+        self.collection
+            .update_one(
+                doc! { "consumer": consumer, "message_id": message_id },
+                doc! { "$setOnInsert": { "processed_at": DateTime::now() } },
+            )
+            .upsert(true)
+            .await
+            .map_err(errors::MongoError::DatabaseError)?;
+
+        Ok(())
+    }
+}
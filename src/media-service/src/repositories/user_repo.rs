@@ -34,7 +34,8 @@ impl MongoUserRepository {
 impl UsersRepository for MongoUserRepository {
     async fn create(&self, user_id: Ulid) -> Result<(), errors::AppError> {
         self.collection
-            .insert_one(UserIdRow{_id: user_id})
+            .replace_one(doc! { "_id": user_id.to_string() }, UserIdRow { _id: user_id })
+            .upsert(true)
             .await
             .map_err(errors::MongoError::DatabaseError)?;
 
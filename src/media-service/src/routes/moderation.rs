@@ -0,0 +1,52 @@
+use crate::errors;
+use crate::models::app_state::AppState;
+use crate::repositories::post_repo::PostRepository;
+use crate::repositories::user_repo::UsersRepository;
+use crate::services::amq::AmqClient;
+use crate::services::cache_service::CacheService;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::delete;
+use axum::{middleware, Router};
+use tracing::info;
+use ulid::Ulid;
+use zylo_common::auth::{require_scope, Principal, RequireScope};
+
+pub fn create_router<P, U, C, A>(app_state: AppState<P, U, C, A>) -> Router
+where
+    P: PostRepository + 'static,
+    U: UsersRepository + 'static,
+    C: CacheService + 'static,
+    A: AmqClient + 'static,
+{
+    Router::new()
+        .route("/api/moderation/posts/{postId}", delete(delete_post))
+        .route_layer(middleware::from_fn_with_state(
+            RequireScope("posts:moderate"),
+            require_scope,
+        ))
+        .with_state(app_state)
+}
+
+/// The audit event is written to the outbox in the transaction deleting the post.
+async fn delete_post<P, U, C, A>(
+    State(state): State<AppState<P, U, C, A>>,
+    principal: Principal,
+    Path(post_id): Path<Ulid>,
+) -> Result<StatusCode, errors::AppError>
+where
+    P: PostRepository + 'static,
+    U: UsersRepository + 'static,
+    C: CacheService + 'static,
+    A: AmqClient + 'static,
+{
+    let post = state.post_repo.delete(&post_id, Some(&principal)).await?;
+
+    state
+        .cache_service
+        .hdelete_all("users-posts", &format!("*{}*", post.user_id))
+        .await?;
+
+    info!("Post {} was deleted by moderator {}", post_id, principal.user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
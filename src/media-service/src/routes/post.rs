@@ -1,12 +1,10 @@
 use crate::errors;
 use crate::models::app_state::AppState;
-use crate::models::event_messages::{PostCreatedMessage, PostDeletedMessage, PostUpdatedMessage};
 use crate::models::post::PostResponse;
 use crate::repositories::post_repo::PostRepository;
 use crate::repositories::user_repo::UsersRepository;
 use crate::services::amq::AmqClient;
 use crate::services::cache_service::CacheService;
-use crate::utils::constants::POST_EXCHANGE_NAME;
 use crate::utils::request::{
     ConstructableRequest, CreatePostRequest, PaginatedResponse, PaginationParams,
     UpdatePostRequest, Validate,
@@ -54,15 +52,6 @@ where
     }
     
     let post = state.post_repo.create(request).await?;
-    state
-        .amq_client
-        .publish_event(
-            POST_EXCHANGE_NAME,
-            "post.created",
-            &PostCreatedMessage::from(&post),
-        )
-        .await?;
-
     Ok((StatusCode::OK, Json(PostResponse::from(post))))
 }
 
@@ -142,15 +131,6 @@ where
     A: AmqClient + 'static,
 {
     let updated_post = state.post_repo.update(request).await?;
-    state
-        .amq_client
-        .publish_event(
-            POST_EXCHANGE_NAME,
-            "post.updated",
-            &PostUpdatedMessage::from(&updated_post),
-        )
-        .await?;
-
     Ok((StatusCode::OK, Json(PostResponse::from(updated_post))))
 }
 
@@ -164,21 +144,12 @@ where
     C: CacheService + 'static,
     A: AmqClient + 'static,
 {
-    state.post_repo.delete(&post_id).await?;
+    state.post_repo.delete(&post_id, None).await?;
 
     state
         .cache_service
         .hdelete_all("users-posts", &format!("*{}*", user_id))
         .await?;
-   
-    state
-        .amq_client
-        .publish_event(
-            POST_EXCHANGE_NAME,
-            "post.deleted",
-            &PostDeletedMessage::new(post_id, user_id),
-        )
-        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,24 @@
+use crate::utils::constants::{AUDIT_EXCHANGE_NAME, POST_EXCHANGE_NAME, USER_EXCHANGE_NAME};
+use zylo_common::amq::{QueueBinding, Topology};
+
+pub const USER_CREATED_QUEUE: QueueBinding = QueueBinding {
+    queue: "user-created-media-service-queue-v2",
+    exchange: USER_EXCHANGE_NAME,
+    routing_key: "user.created",
+    legacy_queue: Some("user-created-media-service-queue"),
+};
+
+pub const USER_DELETED_QUEUE: QueueBinding = QueueBinding {
+    queue: "user-deleted-media-service-queue-v2",
+    exchange: USER_EXCHANGE_NAME,
+    routing_key: "user.deleted",
+    legacy_queue: Some("user-deleted-media-service-queue"),
+};
+
+/// Everything media-service declares on RabbitMQ. Every queue must have a consumer,
+/// which `AmqClient::setup_listeners` verifies at startup. The `-v2` queues replace the ones
+/// declared before dead-lettering was added, see `zylo_common::amq::migrate_legacy_queue`.
+pub const TOPOLOGY: Topology = Topology {
+    exchanges: &[POST_EXCHANGE_NAME, USER_EXCHANGE_NAME, AUDIT_EXCHANGE_NAME],
+    queues: &[USER_CREATED_QUEUE, USER_DELETED_QUEUE],
+};
@@ -3,7 +3,6 @@ use crate::repositories::post_repo::PostRepository;
 use crate::services::grpc_server::post_server::post_service_server::PostService;
 use crate::services::grpc_server::post_server::{BatchPostsRequest, FileMetadataResponse, PaginatedPostsResponse, PostRequest, PostResponse, PostsRequest, PostsResponse};
 use std::sync::Arc;
-use opentelemetry::propagation::Extractor;
 use tonic::{Request, Response, Status};
 use ulid::Ulid;
 
@@ -11,24 +10,6 @@ pub mod post_server {
     tonic::include_proto!("post_server");
 }
 
-pub struct MetadataMap<'a>(pub &'a tonic::metadata::MetadataMap);
-
-impl Extractor for MetadataMap<'_> {
-    fn get(&self, key: &str) -> Option<&str> {
-        self.0.get(key).and_then(|metadata| metadata.to_str().ok())
-    }
-
-    fn keys(&self) -> Vec<&str> {
-        self.0
-            .keys()
-            .map(|key| match key {
-                tonic::metadata::KeyRef::Ascii(v) => v.as_str(),
-                tonic::metadata::KeyRef::Binary(v) => v.as_str(),
-            })
-            .collect::<Vec<_>>()
-    }
-}
-
 #[derive(Debug)]
 pub struct GrpcPostServer<P>
 where
@@ -138,6 +119,7 @@ impl From<Post> for PostResponse {
                     url: file.url.unwrap_or_default().url,
                 })
                 .collect(),
+            visibility: value.visibility.to_string(),
             created_at: value.created_at,
             updated_at: value.updated_at,
         }
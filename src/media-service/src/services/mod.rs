@@ -1,5 +1,5 @@
 pub mod amq;
-pub mod key_vault;
+pub mod amq_topology;
 pub mod cache_service;
 pub mod s3_service;
 pub mod grpc_server;
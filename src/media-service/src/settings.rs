@@ -1,7 +1,8 @@
-use crate::services::key_vault::KeyVault;
-use crate::utils::constants::{GRPC_SERVER_ADDR, JWT_AUDIENCE, JWT_ISSUER, JWT_SECRET, MONGO_URL_SECRET, OTEL_COLLECTOR_ADDR, RABBITMQ_URL_SECRET, REDIS_EXPIRE, REDIS_URL_SECRET, S3_BUCKET_NAME, S3_BUCKET_PRESIGNED_URL_EXPIRE_TIME};
+use crate::utils::constants::{GRPC_SERVER_ADDR, MONGO_URL_SECRET, OTEL_COLLECTOR_ADDR, RABBITMQ_URL_SECRET, RATE_LIMIT_ROUTES, REDIS_EXPIRE, REDIS_URL_SECRET, S3_BUCKET_NAME, S3_BUCKET_PRESIGNED_URL_EXPIRE_TIME};
 use serde::Deserialize;
 use std::fs;
+use zylo_common::config::{AmqConsumers, Auth, RateLimits};
+use zylo_common::key_vault::KeyVault;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Global {
@@ -23,23 +24,6 @@ impl Database {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct Auth {
-    pub secret: String,
-    pub issuer: String,
-    pub audience: String,
-}
-
-impl Auth {
-    async fn from_key_vault(key_vault: &KeyVault) -> Self {
-        Self {
-            secret: key_vault.get_secret(JWT_SECRET).await.unwrap(),
-            issuer: key_vault.get_secret(JWT_ISSUER).await.unwrap(),
-            audience: key_vault.get_secret(JWT_AUDIENCE).await.unwrap(),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Deserialize)]
 pub struct Redis {
     pub uri: String,
@@ -58,12 +42,15 @@ impl Redis {
 #[derive(Debug, Clone, Deserialize)]
 pub struct RabbitMq {
     pub uri: String,
+    #[serde(default)]
+    pub consumers: AmqConsumers,
 }
 
 impl RabbitMq {
     async fn from_key_vault(key_vault: &KeyVault) -> Self {
         Self {
             uri: key_vault.get_secret(RABBITMQ_URL_SECRET).await.unwrap(),
+            consumers: AmqConsumers::from_key_vault(key_vault).await,
         }
     }
 }
@@ -122,7 +109,9 @@ pub struct AppConfig {
     pub amq: RabbitMq,
     pub s3_config: S3Settings,
     pub grpc_server: GrpcServer,
-    pub otel_collector: OtelCollector
+    pub otel_collector: OtelCollector,
+    #[serde(default)]
+    pub rate_limits: RateLimits,
 }
 
 impl AppConfig {
@@ -158,6 +147,7 @@ impl AppConfig {
             amq: RabbitMq::from_key_vault(key_vault).await,
             grpc_server: GrpcServer::from_key_vault(key_vault).await,
             otel_collector: OtelCollector::from_key_vault(key_vault).await,
+            rate_limits: RateLimits::from_key_vault(key_vault, RATE_LIMIT_ROUTES).await,
         }
     }
 
@@ -0,0 +1,40 @@
+//! Fixtures shared by the tests that run against MongoDB and RabbitMQ.
+
+use crate::errors;
+use crate::models::file::{File, FileMetadata, PresignedUrl};
+use crate::services::s3_service::S3Service;
+use crate::settings::Database;
+use crate::utils::helpers::init_db;
+use async_trait::async_trait;
+use ulid::Ulid;
+
+pub fn env_or(key: &str, default: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+/// A database of its own for the test, to be dropped when it is done.
+pub async fn test_db() -> mongodb::Database {
+    init_db(&Database {
+        uri: env_or("MONGO_URI", "mongodb://localhost:27017"),
+        name: format!("media-test-{}", Ulid::new()),
+    })
+    .await
+}
+
+/// Posts created by the tests have no files, so S3 is never reached.
+pub struct NoFiles;
+
+#[async_trait]
+impl S3Service for NoFiles {
+    async fn upload(&self, _file: File) -> Result<FileMetadata, errors::S3Error> {
+        unreachable!("the test does not upload files")
+    }
+
+    async fn delete(&self, _key: &str) -> Result<(), errors::S3Error> {
+        unreachable!("the test does not upload files")
+    }
+
+    async fn get_presigned_url(&self, _key: &str) -> Result<PresignedUrl, errors::S3Error> {
+        unreachable!("the test does not upload files")
+    }
+}
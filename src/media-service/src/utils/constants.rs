@@ -6,6 +6,7 @@ pub const REDIS_EXPIRE: &str = "Zylo-Redis--Expire";
 pub const RABBITMQ_URL_SECRET: &str = "Zylo-RabbitMq--ConnectionString";
 
 pub const GRPC_SERVER_ADDR: &str = "Media-gRPC--ServerAddr";
+pub const RATE_LIMIT_ROUTES: &str = "Media-RateLimit--Routes";
 pub const OTEL_COLLECTOR_ADDR: &str = "Zylo-OTEL--CollectorAddress";
 
 pub const S3_BUCKET_NAME: &str = "Zylo-S3--BucketName";
@@ -13,8 +14,8 @@ pub const S3_BUCKET_PRESIGNED_URL_EXPIRE_TIME: &str = "Zylo-S3--PresignedUrlExpi
 
 pub const POST_EXCHANGE_NAME: &str = "post-exchange";
 pub const USER_EXCHANGE_NAME: &str = "user-exchange";
+pub const AUDIT_EXCHANGE_NAME: &str = "audit-exchange";
 
-pub const JWT_SECRET: &str = "Zylo-Jwt--Secret";
-pub const JWT_ISSUER: &str = "Zylo-Jwt--Issuer";
-pub const JWT_AUDIENCE: &str = "Zylo-Jwt--Audience";
-pub const REQUEST_ID_HEADER: &str = "x-request-id";
\ No newline at end of file
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+pub const AMQ_HEALTH_CHECK_INTERVAL_SECONDS: u64 = 5;
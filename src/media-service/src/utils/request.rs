@@ -1,4 +1,5 @@
 use crate::models::file::File;
+use crate::models::post::Visibility;
 use async_trait::async_trait;
 use axum::extract::{FromRequest, Multipart, Request};
 use serde::{Deserialize, Serialize};
@@ -62,6 +63,7 @@ pub struct CreatePostRequest {
     pub user_id: Ulid,
     pub text: String,
     pub files: Vec<File>,
+    pub visibility: Visibility,
 }
 
 impl CreatePostRequest {
@@ -70,6 +72,7 @@ impl CreatePostRequest {
             user_id,
             text: String::new(),
             files: Vec::new(),
+            visibility: Visibility::default(),
         }
     }
 
@@ -87,6 +90,15 @@ impl CreatePostRequest {
                     let file = File::from_field(field).await?;
                     request.files.push(file);
                 }
+                Some("visibility") => {
+                    request.visibility = field
+                        .text()
+                        .await
+                        .map_err(|_| {
+                            errors::AppError::BadRequest("Invalid visibility".to_string())
+                        })?
+                        .parse()?
+                }
                 _ => warn!("Unknown field"),
             }
         }
@@ -111,11 +123,10 @@ impl Validate for CreatePostRequest {
 
 impl Validate for PaginationParams {
     fn validate(&self) -> Result<(), errors::ValidationError> {
-        if let Some(per_page) = self.per_page {
-            if per_page < 1 {
-                return Err(errors::ValidationError::Failed("perPage cannot be less than 1".to_string()));
-            }
-            
+        if let Some(per_page) = self.per_page
+            && per_page < 1
+        {
+            return Err(errors::ValidationError::Failed("perPage cannot be less than 1".to_string()));
         }
         
         Ok(())
@@ -151,24 +162,24 @@ impl ConstructableRequest for CreatePostRequest {
         CreatePostRequest::from_multipart(multipart, user_id).await
     }
 }
-fn extract_user_id(req: &Request) -> Result<Ulid, errors::AppError> {
+fn extract_user_id(req: &Request) -> Result<Ulid, errors::ValidationError> {
     let uri_path = req.uri().path();
     let user_id_str = uri_path
         .split('/')
         .nth(3)
         .ok_or_else(|| errors::ValidationError::InvalidUri("Could not find user id".to_string()))?;
 
-    Ulid::from_string(user_id_str).map_err(|_| errors::ValidationError::InvalidUserId.into())
+    Ulid::from_string(user_id_str).map_err(|_| errors::ValidationError::InvalidUserId)
 }
 
-fn extract_post_id(req: &Request) -> Result<Ulid, errors::AppError> {
+fn extract_post_id(req: &Request) -> Result<Ulid, errors::ValidationError> {
     let uri_path = req.uri().path();
     let post_id_str = uri_path
         .split('/')
         .nth(5)
         .ok_or_else(|| errors::ValidationError::InvalidUri("Could not find post id".to_string()))?;
 
-    Ulid::from_string(post_id_str).map_err(|_| errors::ValidationError::InvalidPostId.into())
+    Ulid::from_string(post_id_str).map_err(|_| errors::ValidationError::InvalidPostId)
 }
 
 #[derive(Debug, Clone)]
@@ -176,6 +187,7 @@ pub struct UpdatePostRequest {
     pub id: Ulid,
     pub text: String,
     pub files: Vec<File>,
+    pub visibility: Option<Visibility>,
 }
 
 impl UpdatePostRequest {
@@ -187,6 +199,7 @@ impl UpdatePostRequest {
             id: post_id,
             text: String::new(),
             files: Vec::new(),
+            visibility: None,
         };
 
         while let Some(field) = multipart
@@ -200,6 +213,12 @@ impl UpdatePostRequest {
                     let file = File::from_field(field).await?;
                     request.files.push(file);
                 }
+                Some("visibility") => {
+                    let visibility = field.text().await.map_err(|_| {
+                        errors::ValidationError::Failed("Invalid visibility".to_string())
+                    })?;
+                    request.visibility = Some(visibility.parse()?)
+                }
                 _ => (),
             }
         }
@@ -220,4 +239,43 @@ impl ConstructableRequest for UpdatePostRequest {
 
         UpdatePostRequest::from_multipart(multipart, post_id).await
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::header;
+
+    fn multipart(body: &'static str) -> Request {
+        Request::builder()
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=X")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn unreadable_visibility_is_a_bad_request() {
+        let body = "--X\r\nContent-Disposition: form-data; name=\"visibility\"\r\n\r\npubl";
+        let multipart = Multipart::from_request(multipart(body), &()).await.unwrap();
+
+        let result = CreatePostRequest::from_multipart(multipart, Ulid::new()).await;
+
+        assert!(
+            matches!(&result, Err(errors::AppError::BadRequest(detail)) if detail == "Invalid visibility"),
+            "{:?}",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn visibility_is_read_from_its_field() {
+        let body = "--X\r\nContent-Disposition: form-data; name=\"visibility\"\r\n\r\nmembers\r\n--X--\r\n";
+        let multipart = Multipart::from_request(multipart(body), &()).await.unwrap();
+
+        let request = CreatePostRequest::from_multipart(multipart, Ulid::new())
+            .await
+            .unwrap();
+
+        assert_eq!(request.visibility, Visibility::Members);
+    }
+}
@@ -4,6 +4,7 @@ using Microsoft.Extensions.DependencyInjection;
 using Microsoft.Extensions.Logging;
 using Microsoft.Extensions.Options;
 using Newtonsoft.Json;
+using Newtonsoft.Json.Linq;
 using NotificationService.Application.Settings;
 using NotificationService.Application.Transport;
 using NotificationService.Infrastructure.Transport.Factories;
@@ -59,7 +60,7 @@ internal sealed class RabbitMqBus : IBus, IAsyncDisposable
                 {
                     string messageJson = Encoding.UTF8.GetString(ea.Body.ToArray());
                     var messageType = consumerSettings.MessageType;
-                    object? message = JsonConvert.DeserializeObject(messageJson, messageType);
+                    object? message = DeserializeMessage(messageJson, messageType);
                     if (message is null)
                     {
                         await channel.BasicNackAsync(ea.DeliveryTag, false, false, cancellationToken);
@@ -91,6 +92,20 @@ internal sealed class RabbitMqBus : IBus, IAsyncDisposable
         }
     }
 
+    /// <summary>
+    /// Deserializes the payload of an enveloped event, or the whole message when it was published without an envelope.
+    /// </summary>
+    private static object? DeserializeMessage(string messageJson, Type messageType)
+    {
+        var token = JToken.Parse(messageJson);
+        if (token is JObject envelope && envelope["type"] is not null && envelope["payload"] is JObject payload)
+        {
+            return payload.ToObject(messageType);
+        }
+
+        return token.ToObject(messageType);
+    }
+
     public async Task StopAsync(CancellationToken cancellationToken)
     {
         foreach (var channel in _channels.Values)
@@ -0,0 +1,63 @@
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ulid::Ulid;
+use user_interaction::models::reply::{Reply, ReplyResponse};
+use user_interaction::utils::helpers::map_nested;
+
+const REPLIES: usize = 50_000;
+
+fn hex(id: &Ulid) -> String {
+    id.to_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// One post with `REPLIES` replies. Every reply answers one of the replies before it, picked by
+/// a fixed pseudo random sequence, which gives a mix of deep chains and wide levels.
+fn thread() -> Vec<ReplyResponse> {
+    let post_id = Ulid::new();
+    let post_path = format!("/{}/", hex(&post_id));
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+
+    let mut replies: Vec<ReplyResponse> = Vec::with_capacity(REPLIES);
+    for index in 0..REPLIES {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let id = Ulid::new();
+        let (reply_to_id, parent_path) = match state % 4 {
+            0 => (post_id, post_path.clone()),
+            _ if index > 0 => {
+                let parent = &replies[(state as usize >> 2) % index];
+                (parent.id, parent.path.clone())
+            }
+            _ => (post_id, post_path.clone()),
+        };
+
+        replies.push(ReplyResponse::from(Reply {
+            id,
+            post_id,
+            reply_to_id,
+            user_id: Some(Ulid::new()),
+            content: String::from("reply"),
+            created_at: Utc::now().naive_utc(),
+            deleted_at: None,
+            path: format!("{}{}/", parent_path, hex(&id)),
+        }));
+    }
+
+    replies
+}
+
+fn bench_map_nested(c: &mut Criterion) {
+    let replies = thread();
+
+    c.bench_function("map_nested 50k replies", |b| {
+        b.iter_batched(|| replies.clone(), map_nested, BatchSize::LargeInput)
+    });
+}
+
+criterion_group!(benches, bench_map_nested);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS outbox
+(
+    id           BYTEA PRIMARY KEY,
+    exchange     VARCHAR(250) NOT NULL,
+    routing_key  VARCHAR(250) NOT NULL,
+    payload      JSONB        NOT NULL,
+    created_at   TIMESTAMP    NOT NULL DEFAULT NOW(),
+    available_at TIMESTAMP    NOT NULL DEFAULT NOW(),
+    sent_at      TIMESTAMP    NULL,
+    attempts     INTEGER      NOT NULL DEFAULT 0,
+    last_error   TEXT         NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_pending ON outbox (available_at) WHERE sent_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_outbox_sent_at ON outbox (sent_at) WHERE sent_at IS NOT NULL;
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS processed_messages
+(
+    consumer     VARCHAR(250) NOT NULL,
+    message_id   VARCHAR(250) NOT NULL,
+    processed_at TIMESTAMP    NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (consumer, message_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_processed_messages_processed_at ON processed_messages (processed_at);
@@ -0,0 +1,3 @@
+CREATE INDEX IF NOT EXISTS idx_replies_reply_to_id ON replies (reply_to_id);
+
+DROP INDEX IF EXISTS idx_replies_children;
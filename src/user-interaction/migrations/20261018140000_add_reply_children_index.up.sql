@@ -0,0 +1,3 @@
+CREATE INDEX IF NOT EXISTS idx_replies_children ON replies (reply_to_id, created_at, id);
+
+DROP INDEX IF EXISTS idx_replies_reply_to_id;
@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS idx_replies_tombstones;
+
+DELETE FROM replies
+WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE replies
+    DROP COLUMN IF EXISTS deleted_at;
+
+ALTER TABLE replies
+    ALTER COLUMN user_id SET NOT NULL;
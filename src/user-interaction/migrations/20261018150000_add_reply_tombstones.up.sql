@@ -0,0 +1,9 @@
+ALTER TABLE replies
+    ALTER COLUMN user_id DROP NOT NULL;
+
+ALTER TABLE replies
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_replies_tombstones
+    ON replies (deleted_at)
+    WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS view_checkpoints;
+DROP TABLE IF EXISTS likes;
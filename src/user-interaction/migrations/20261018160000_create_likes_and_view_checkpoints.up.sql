@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS likes
+(
+    entity_id  BYTEA     NOT NULL,
+    user_id    BYTEA     NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (entity_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_likes_user_id_created_at ON likes (user_id, created_at, entity_id);
+CREATE INDEX IF NOT EXISTS idx_likes_entity_id_created_at ON likes (entity_id, created_at, user_id);
+
+CREATE TABLE IF NOT EXISTS view_checkpoints
+(
+    entity_id       BYTEA PRIMARY KEY,
+    views           BIGINT    NOT NULL,
+    sketch          BYTEA     NOT NULL,
+    checkpointed_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
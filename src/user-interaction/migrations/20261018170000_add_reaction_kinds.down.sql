@@ -0,0 +1,7 @@
+DELETE FROM reactions WHERE kind <> 'like';
+
+ALTER INDEX IF EXISTS idx_reactions_entity_id_created_at RENAME TO idx_likes_entity_id_created_at;
+ALTER INDEX IF EXISTS idx_reactions_user_id_created_at RENAME TO idx_likes_user_id_created_at;
+
+ALTER TABLE reactions DROP COLUMN IF EXISTS kind;
+ALTER TABLE reactions RENAME TO likes;
@@ -0,0 +1,7 @@
+-- A like becomes one kind of reaction. A user keeps at most one reaction per entity, and the
+-- likes recorded so far keep the `like` kind.
+ALTER TABLE likes RENAME TO reactions;
+ALTER TABLE reactions ADD COLUMN IF NOT EXISTS kind VARCHAR(32) NOT NULL DEFAULT 'like';
+
+ALTER INDEX IF EXISTS idx_likes_user_id_created_at RENAME TO idx_reactions_user_id_created_at;
+ALTER INDEX IF EXISTS idx_likes_entity_id_created_at RENAME TO idx_reactions_entity_id_created_at;
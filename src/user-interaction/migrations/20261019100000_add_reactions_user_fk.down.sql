@@ -0,0 +1 @@
+ALTER TABLE reactions DROP CONSTRAINT IF EXISTS fk_reactions_user;
@@ -0,0 +1,7 @@
+-- Reactions go away with the user who gave them, like bookmarks do. Reactions of users that
+-- were already deleted are removed first.
+DELETE FROM reactions r
+WHERE NOT EXISTS (SELECT 1 FROM users u WHERE u.id = r.user_id);
+
+ALTER TABLE reactions
+    ADD CONSTRAINT fk_reactions_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_replies_children_likes;
+
+DROP TRIGGER IF EXISTS trg_reactions_reply_likes ON reactions;
+DROP FUNCTION IF EXISTS count_reply_likes();
+
+ALTER TABLE replies
+    DROP COLUMN IF EXISTS like_count;
@@ -0,0 +1,35 @@
+-- Replies keep their number of likes, so that the most liked ones can be paged from an index.
+-- The count follows the reactions table through a trigger, whatever removes or changes them.
+ALTER TABLE replies
+    ADD COLUMN IF NOT EXISTS like_count BIGINT NOT NULL DEFAULT 0;
+
+UPDATE replies r
+SET like_count = (SELECT COUNT(*) FROM reactions WHERE entity_id = r.id AND kind = 'like');
+
+CREATE OR REPLACE FUNCTION count_reply_likes() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        IF OLD.kind = 'like' THEN
+            UPDATE replies SET like_count = like_count - 1 WHERE id = OLD.entity_id;
+        END IF;
+    END IF;
+
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        IF NEW.kind = 'like' THEN
+            UPDATE replies SET like_count = like_count + 1 WHERE id = NEW.entity_id;
+        END IF;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_reactions_reply_likes ON reactions;
+CREATE TRIGGER trg_reactions_reply_likes
+    AFTER INSERT OR DELETE OR UPDATE OF kind
+    ON reactions
+    FOR EACH ROW
+EXECUTE FUNCTION count_reply_likes();
+
+CREATE INDEX IF NOT EXISTS idx_replies_children_likes ON replies (reply_to_id, like_count, id);
@@ -1,4 +1,3 @@
-use crate::auth::authorization_middleware;
 use crate::models::app_state::AppState;
 use crate::repositories::interaction_repo::InteractionRepository;
 use crate::{routes};
@@ -9,31 +8,16 @@ use crate::services::grpc_server::reply_server::reply_service_server::{
 use crate::services::post_interactions_service::PostInteractionsService;
 use crate::services::reply_service::ReplyService;
 use crate::utils::constants::{OTEL_SERVICE_NAME, REQUEST_ID_HEADER};
-use crate::utils::helpers::get_container_id;
-use axum::extract::{MatchedPath, Request as AxRequest, State};
+use axum::extract::MatchedPath;
 use axum::http::{header, HeaderName, Request};
 use axum::middleware;
-use axum::middleware::Next;
-use axum::response::IntoResponse;
 use axum::Router;
-use opentelemetry::metrics::{Counter, Histogram};
-use opentelemetry::trace::TracerProvider as _;
-use opentelemetry::{global, KeyValue};
-use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry::global;
 use opentelemetry_http::HeaderExtractor;
-use opentelemetry_otlp::{LogExporter, MetricExporter, SpanExporter, WithExportConfig};
-use opentelemetry_sdk::logs::SdkLoggerProvider;
-use opentelemetry_sdk::metrics::SdkMeterProvider;
-use opentelemetry_sdk::propagation::TraceContextPropagator;
-use opentelemetry_sdk::trace::SdkTracerProvider;
-use opentelemetry_sdk::Resource;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, OnceLock};
-use std::time::Duration;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::signal;
-use tokio::time::Instant;
 use tonic::transport::Server;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
@@ -43,171 +27,10 @@ use tower_http::trace;
 use tower_http::trace::DefaultOnRequest;
 use tracing::log::{error, info};
 use tracing::{field, info_span};
-use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{fmt, EnvFilter};
-
-
-
-#[derive(Clone)]
-pub struct ServerMetrics {
-    pub request_counter: Counter<u64>,
-    pub request_latency: Histogram<f64>,
-    pub active_requests: Arc<AtomicU64>,
-    pub attributes: Vec<KeyValue>,
-}
-
-impl ServerMetrics {
-    pub fn new(service_name: &'static str) -> Self {
-        let meter = global::meter(service_name);
-        let boundaries = vec![
-            0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
-        ];
-
-        let request_counter = meter
-            .u64_counter("http_server_requests_total")
-            .with_description("Total number of HTTP requests")
-            .build();
-
-        let request_latency = meter
-            .f64_histogram("http_server_request_duration_seconds")
-            .with_description("HTTP request duration")
-            .with_boundaries(boundaries)
-            .build();
-
-        let active_requests = Arc::new(AtomicU64::new(0));
-        let active_requests_clone = active_requests.clone();
-
-        let host_name = get_container_id().unwrap_or(String::from("0.0.0.0"));
-        let attributes = vec![
-            KeyValue::new("service", OTEL_SERVICE_NAME),
-            KeyValue::new("instance", host_name),
-        ];
-        let attributes_clone = attributes.clone();
-
-        meter
-            .u64_observable_gauge("http_server_active_requests")
-            .with_description("Active HTTP requests")
-            .with_callback(move |observer| {
-                let value = active_requests_clone.load(Ordering::Relaxed);
-                observer.observe(value, &attributes_clone);
-            })
-            .build();
-
-        Self {
-            request_counter,
-            request_latency,
-            active_requests,
-            attributes,
-        }
-    }
-}
-
-fn get_resource() -> Resource {
-    static RESOURCE: OnceLock<Resource> = OnceLock::new();
-    RESOURCE
-        .get_or_init(|| {
-            Resource::builder()
-                .with_service_name(OTEL_SERVICE_NAME)
-                .build()
-        })
-        .clone()
-}
-
-pub fn init_traces(endpoint: &str) -> SdkTracerProvider {
-    let exporter = SpanExporter::builder()
-        .with_tonic()
-        .with_endpoint(endpoint)
-        .build()
-        .expect("Failed to create span exporter");
-
-    let provider = SdkTracerProvider::builder()
-        .with_resource(get_resource())
-        .with_batch_exporter(exporter)
-        .build();
-
-    global::set_tracer_provider(provider.clone());
-    global::set_text_map_propagator(TraceContextPropagator::new());
-    provider
-}
-
-pub fn init_tracing(logger_provider: &SdkLoggerProvider, tracer_provider: &SdkTracerProvider) {
-    tracing_subscriber::registry()
-        .with(OpenTelemetryTracingBridge::new(logger_provider))
-        .with(OpenTelemetryLayer::new(
-            tracer_provider.tracer("tracing-jaeger"),
-        ))
-        .with(fmt::layer().pretty())
-        .with(EnvFilter::from_default_env())
-        .init();
-}
-
-pub fn init_metrics(endpoint: &str) -> SdkMeterProvider {
-    let exporter = MetricExporter::builder()
-        .with_tonic()
-        .with_endpoint(endpoint)
-        .build()
-        .expect("Failed to create metric exporter");
-
-    let reader = opentelemetry_sdk::metrics::PeriodicReader::builder(exporter)
-        .with_interval(Duration::from_secs(10))
-        .build();
-
-    let provider = SdkMeterProvider::builder()
-        .with_reader(reader.clone())
-        .with_resource(get_resource())
-        .build();
-
-    global::set_meter_provider(provider.clone());
-    provider
-}
-
-pub fn init_logs(endpoint: &str) -> SdkLoggerProvider {
-    let exporter = LogExporter::builder()
-        .with_tonic()
-        .with_endpoint(endpoint)
-        .build()
-        .expect("Failed to create log exporter");
-
-    SdkLoggerProvider::builder()
-        .with_resource(get_resource())
-        .with_batch_exporter(exporter)
-        .build()
-}
-
-async fn track_metrics(
-    State(metrics): State<Arc<ServerMetrics>>,
-    req: AxRequest,
-    next: Next,
-) -> impl IntoResponse {
-    let path = if let Some(matched_path) = req.extensions().get::<MatchedPath>() {
-        matched_path.as_str().to_owned()
-    } else {
-        req.uri().path().to_owned()
-    };
-
-    let method = req.method().clone();
-    let start = Instant::now();
-    metrics.active_requests.fetch_add(1, Ordering::Relaxed);
-
-    let response = next.run(req).await;
-
-    metrics.active_requests.fetch_sub(1, Ordering::Relaxed);
-    let latency = start.elapsed().as_secs_f64();
-    let status = response.status().as_u16().to_string();
-
-    let mut labels = vec![
-        KeyValue::new("method", method.to_string()),
-        KeyValue::new("path", path),
-        KeyValue::new("status", status),
-    ];
-    labels.extend_from_slice(&metrics.attributes);
-
-    metrics.request_counter.add(1, &labels);
-    metrics.request_latency.record(latency, &labels);
-    response
-}
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use zylo_common::auth::{JwtKeyStore, authorization_middleware};
+use zylo_common::rate_limit::{RateLimiter, rate_limit_middleware};
+use zylo_common::telemetry::{ServerMetrics, track_metrics};
 
 pub async fn create_router<A, I, RS, PS>(app_state: AppState<A, I, RS, PS>) -> Router
 where
@@ -216,6 +39,17 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
+    let key_store = JwtKeyStore::new(app_state.config.auth.clone()).await;
+    key_store.spawn_refresh();
+
+    // The cache service has already opened a client with the same uri at startup.
+    let rate_limiter = RateLimiter::new(
+        &app_state.config.redis.uri,
+        app_state.config.rate_limits.clone(),
+        OTEL_SERVICE_NAME,
+    )
+    .expect("Invalid Redis connection string");
+
     let x_request_id = HeaderName::from_static(REQUEST_ID_HEADER);
     let middleware = ServiceBuilder::new()
         .layer(SetRequestIdLayer::new(
@@ -275,9 +109,15 @@ where
     Router::new()
         .merge(routes::reply::create_router(app_state.clone()))
         .merge(routes::interaction::create_router(app_state.clone()))
+        .merge(routes::moderation::create_router(app_state.clone()))
+        .merge(routes::bookmark::create_router(app_state.clone()))
+        .layer(middleware::from_fn_with_state(
+            rate_limiter,
+            rate_limit_middleware,
+        ))
         .layer(middleware)
         .layer(middleware::from_fn_with_state(
-            app_state.config.auth.clone(),
+            key_store,
             authorization_middleware,
         ))
         .layer(middleware::from_fn_with_state(
@@ -364,8 +204,11 @@ where
         .serve(grpc_address);
 
     info!("Starting Axum HTTP API server on {}", axum_address);
-    let axum_server_future = axum::serve(TcpListener::bind(axum_address).await?, axum_app)
-        .with_graceful_shutdown(shutdown_signal(app_state.clone()));
+    let axum_server_future = axum::serve(
+        TcpListener::bind(axum_address).await?,
+        axum_app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(app_state.clone()));
 
     tokio::select! {
         result = grpc_server_future => {
@@ -1,16 +1,14 @@
 ﻿use crate::services::cache_service::{CacheService, RedisCacheService};
-use crate::utils::helpers::get_container_id;
 use crate::{errors, settings};
 use async_trait::async_trait;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::{global, KeyValue};
 use redis::aio::MultiplexedConnection;
-use serde::de::DeserializeOwned;
-use serde::Serialize;
 use std::collections::HashMap;
 use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedCacheService<C: CacheService> {
     cache_service: C,
@@ -142,85 +140,33 @@ impl<C: CacheService + 'static> CacheService for ObservableCacheService<C> {
         .await
     }
 
-    async fn hfind<T: DeserializeOwned>(
-        &self,
-        key: &str,
-        pattern: &str,
-    ) -> Result<Option<T>, errors::RedisError> {
-        self.track_method(
-            "hfind",
-            &format!("HSCAN {} {}", key, pattern),
-            "HSCAN",
-            key,
-            self.inner.hfind(key, pattern),
-        )
-        .await
-    }
-
-    async fn hfind_keys(
-        &self,
-        key: &str,
-        pattern: &str,
-    ) -> Result<Vec<String>, errors::RedisError> {
-        self.track_method(
-            "HSCAN",
-            &format!("HSCAN {} {} NOVALUES", key, pattern),
-            "HSCAN",
-            key,
-            self.inner.hfind_keys(key, pattern),
-        )
-        .await
-    }
-
-    async fn hget<T: DeserializeOwned>(
-        &self,
-        key: &str,
-        field: &str,
-    ) -> Result<Option<T>, errors::RedisError> {
+    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError> {
         self.track_method(
-            "hget",
-            &format!("HGET {} {}", key, field),
-            "HGET",
+            "pfadd",
+            &format!("PFADD {} {}", key, element),
+            "PFADD",
             key,
-            self.inner.hget(key, field),
+            self.inner.pfadd(key, element),
         )
         .await
     }
 
-    async fn hset<T: Serialize + Sync + Send>(
+    async fn pfadd_expire_at(
         &self,
-        key: &str,
-        field: &str,
-        value: &T,
+        keys: &[(String, i64)],
+        element: &str,
     ) -> Result<(), errors::RedisError> {
+        let namespace = keys
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
         self.track_method(
-            "hset",
-            &format!("HSET {} {} HEXPIRE", key, field),
-            "HSET HEXPIRE",
-            key,
-            self.inner.hset(key, field, value),
-        )
-        .await
-    }
-
-    async fn hdel(&self, key: &str, fields: &[String]) -> Result<(), errors::RedisError> {
-        self.track_method(
-            "hdel",
-            &format!("HDEL {} {}", key, fields.join(" ")),
-            "HDEL",
-            key,
-            self.inner.hdel(key, fields),
-        )
-        .await
-    }
-
-    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError> {
-        self.track_method(
-            "pfadd",
-            &format!("PFADD {} {}", key, element),
-            "PFADD",
-            key,
-            self.inner.pfadd(key, element),
+            "pfadd_expire_at",
+            &format!("PIPE PFADD {} {}", &namespace, element),
+            "PIPE PFADD",
+            &namespace,
+            self.inner.pfadd_expire_at(keys, element),
         )
         .await
     }
@@ -273,17 +219,6 @@ impl<C: CacheService + 'static> CacheService for ObservableCacheService<C> {
         .await
     }
 
-    async fn scard(&self, key: &str) -> Result<u64, errors::RedisError> {
-        self.track_method(
-            "scard",
-            &format!("SCARD {}", key),
-            "SCARD",
-            key,
-            self.inner.scard(key),
-        )
-        .await
-    }
-
     async fn scard_many(
         &self,
         keys: &[String],
@@ -299,17 +234,6 @@ impl<C: CacheService + 'static> CacheService for ObservableCacheService<C> {
         .await
     }
 
-    async fn sismember(&self, key: &str, member: &str) -> Result<bool, errors::RedisError> {
-        self.track_method(
-            "sismember",
-            &format!("SISMEMBER {} {}", key, member),
-            "SISMEMBER",
-            key,
-            self.inner.sismember(key, member),
-        )
-        .await
-    }
-
     async fn sismember_many(
         &self,
         keys: &[String],
@@ -326,6 +250,96 @@ impl<C: CacheService + 'static> CacheService for ObservableCacheService<C> {
         .await
     }
 
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, errors::RedisError> {
+        self.track_method(
+            "get_raw",
+            &format!("GET {}", key),
+            "GET",
+            key,
+            self.inner.get_raw(key),
+        )
+        .await
+    }
+
+    async fn exists_many(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, bool>, errors::RedisError> {
+        let namespace = keys.join(" ");
+        self.track_method(
+            "exists_many",
+            &format!("PIPE EXISTS {}", &namespace),
+            "PIPE EXISTS",
+            &namespace,
+            self.inner.exists_many(keys),
+        )
+        .await
+    }
+
+    async fn pfmerge_raw(&self, key: &str, dump: &[u8]) -> Result<(), errors::RedisError> {
+        self.track_method(
+            "pfmerge_raw",
+            &format!("PFMERGE {}", key),
+            "PFMERGE",
+            key,
+            self.inner.pfmerge_raw(key, dump),
+        )
+        .await
+    }
+
+    async fn sadd_exclusive(
+        &self,
+        key: &str,
+        others: &[String],
+        member: &str,
+    ) -> Result<bool, errors::RedisError> {
+        self.track_method(
+            "sadd_exclusive",
+            &format!("PIPE SADD {} {}", key, member),
+            "PIPE SADD",
+            key,
+            self.inner.sadd_exclusive(key, others, member),
+        )
+        .await
+    }
+
+    async fn fill_sets(
+        &self,
+        sets: &[(String, Vec<String>)],
+        marker: &str,
+    ) -> Result<(), errors::RedisError> {
+        self.track_method(
+            "fill_sets",
+            &format!("PIPE SADD {}", marker),
+            "PIPE SADD",
+            marker,
+            self.inner.fill_sets(sets, marker),
+        )
+        .await
+    }
+
+    async fn smembers(&self, key: &str) -> Result<Vec<String>, errors::RedisError> {
+        self.track_method(
+            "smembers",
+            &format!("SMEMBERS {}", key),
+            "SMEMBERS",
+            key,
+            self.inner.smembers(key),
+        )
+        .await
+    }
+
+    async fn spop_many(&self, key: &str, count: usize) -> Result<Vec<String>, errors::RedisError> {
+        self.track_method(
+            "spop_many",
+            &format!("SPOP {}", key),
+            "SPOP",
+            key,
+            self.inner.spop_many(key, count),
+        )
+        .await
+    }
+
     async fn del(&self, keys: &[String]) -> Result<(), errors::RedisError> {
         let namespace = keys.join(" ");
         self.track_method(
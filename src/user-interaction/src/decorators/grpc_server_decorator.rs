@@ -1,13 +1,12 @@
 ﻿use crate::services::grpc_server::reply_server::reply_service_server::ReplyService as GrpcReplyService;
 use crate::services::grpc_server::reply_server::{
-    BatchOfPostInteractionsResponse, GetBatchOfPostInteractionsRequest, GetPostInteractionsRequest,
-    GetReplyByIdRequest, PostInteractionsResponse as GrpcPostInteractionResponse,
-    ReplyResponse as GrpcReplyResponse,
+    BatchOfPostInteractionsResponse, GetBatchOfPostInteractionsRequest, GetLikesRequest,
+    GetPostInteractionsRequest, GetReplyByIdRequest, LikesResponse,
+    PostInteractionsResponse as GrpcPostInteractionResponse, ReplyResponse as GrpcReplyResponse,
 };
 use crate::services::grpc_server::GrpcReplyServer;
 use crate::services::post_interactions_service::PostInteractionsService;
 use crate::services::reply_service::ReplyService;
-use crate::utils::helpers::get_container_id;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::{global, KeyValue};
 use std::sync::Arc;
@@ -16,6 +15,7 @@ use tonic::{Code, Request, Response, Status};
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedGrpcServer<S: GrpcReplyService> {
     reply_server: S,
@@ -178,4 +178,23 @@ impl<S: GrpcReplyService> GrpcReplyService for ObservableGrpcReplyServer<S> {
         )
         .await
     }
+
+    async fn get_post_likes(
+        &self,
+        request: Request<GetLikesRequest>,
+    ) -> Result<Response<LikesResponse>, Status> {
+        self.track_method("get_post_likes", self.inner.get_post_likes(request))
+            .await
+    }
+
+    async fn get_user_liked_posts(
+        &self,
+        request: Request<GetLikesRequest>,
+    ) -> Result<Response<LikesResponse>, Status> {
+        self.track_method(
+            "get_user_liked_posts",
+            self.inner.get_user_liked_posts(request),
+        )
+        .await
+    }
 }
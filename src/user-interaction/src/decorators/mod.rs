@@ -2,4 +2,5 @@
 pub mod grpc_server_decorator;
 pub mod reply_repo_decorator;
 pub mod users_repo_decorator;
-pub mod posts_repo_decorator;
\ No newline at end of file
+pub mod posts_repo_decorator;
+pub mod bookmarks_repo_decorator;
\ No newline at end of file
@@ -3,7 +3,6 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use crate::errors;
 use crate::errors::DatabaseError;
 use crate::repositories::posts_repo::{PostgresPostsRepository, PostsRepository};
-use crate::utils::helpers::get_container_id;
 use async_trait::async_trait;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::{global, KeyValue};
@@ -13,6 +12,7 @@ use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedPostsRepository<P: PostsRepository> {
     posts_repo: P,
@@ -94,6 +94,7 @@ impl<P: PostsRepository> ObservablePostsRepository<P> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn track_method<T, F, E: errors::ProblemResponse + ToString>(
         &self,
         method_name: &str,
@@ -179,4 +180,17 @@ impl<P: PostsRepository + 'static> PostsRepository for ObservablePostsRepository
         )
         .await
     }
+
+    async fn get_author(&self, post_id: &Ulid) -> Result<Option<Ulid>, DatabaseError> {
+        self.track_method(
+            "get_author",
+            "SELECT FROM posts",
+            "SELECT",
+            "posts",
+            &post_id.to_string(),
+            None,
+            self.inner.get_author(post_id),
+        )
+        .await
+    }
 }
@@ -1,8 +1,7 @@
 ﻿use crate::errors;
-use crate::models::reply::Reply;
+use crate::models::reply::{Reply, ReplyActor, ReplyCursor, ThreadReply};
 use crate::models::Finalizer;
 use crate::repositories::reply_repo::{PostgresReplyRepository, ReplyRepository};
-use crate::utils::helpers::get_container_id;
 use async_trait::async_trait;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::trace::SpanKind;
@@ -16,6 +15,7 @@ use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedReplyRepository<R: ReplyRepository> {
     reply_repo: R,
@@ -159,41 +159,69 @@ impl<R: ReplyRepository + 'static> Finalizer for ObservableReplyRepository<R> {
 
 #[async_trait]
 impl<R: ReplyRepository + 'static> ReplyRepository for ObservableReplyRepository<R> {
-    async fn get_all_from_post(&self, post_id: &Ulid) -> Result<Vec<Reply>, errors::DatabaseError> {
+    async fn get_children_page(
+        &self,
+        post_id: &Ulid,
+        parent_id: &Ulid,
+        newest_first: bool,
+        after: Option<ReplyCursor>,
+        limit: i64,
+    ) -> Result<Vec<Reply>, errors::DatabaseError> {
         self.track_method(
-            "get_all_from_post",
+            "get_children_page",
             "SELECT replies",
             "SELECT",
             "replies",
-            self.inner.get_all_from_post(post_id),
+            self.inner
+                .get_children_page(post_id, parent_id, newest_first, after, limit),
         )
         .await
     }
 
-    async fn get_all_from_posts(
+    async fn get_most_liked_children(
         &self,
-        post_ids: &[Ulid],
-    ) -> Result<HashMap<Ulid, Vec<Reply>>, errors::DatabaseError> {
+        post_id: &Ulid,
+        parent_id: &Ulid,
+        after: Option<ReplyCursor>,
+        limit: i64,
+    ) -> Result<Vec<ReplyCursor>, errors::DatabaseError> {
         self.track_method(
-            "get_all_from_posts",
+            "get_most_liked_children",
             "SELECT replies",
             "SELECT",
             "replies",
-            self.inner.get_all_from_posts(post_ids),
+            self.inner
+                .get_most_liked_children(post_id, parent_id, after, limit),
         )
         .await
     }
 
-    async fn get_with_nested_by_path_prefix(
+    async fn get_threads(
         &self,
-        prefix: &str,
-    ) -> Result<Vec<Reply>, errors::DatabaseError> {
+        root_ids: &[Ulid],
+        max_depth: u32,
+        max_children: u32,
+    ) -> Result<Vec<ThreadReply>, errors::DatabaseError> {
+        self.track_method(
+            "get_threads",
+            "WITH RECURSIVE SELECT replies",
+            "SELECT",
+            "replies",
+            self.inner.get_threads(root_ids, max_depth, max_children),
+        )
+        .await
+    }
+
+    async fn get_all_from_posts(
+        &self,
+        post_ids: &[Ulid],
+    ) -> Result<HashMap<Ulid, Vec<Reply>>, errors::DatabaseError> {
         self.track_method(
-            "get_with_nested_by_path_prefix",
+            "get_all_from_posts",
             "SELECT replies",
             "SELECT",
             "replies",
-            self.inner.get_with_nested_by_path_prefix(prefix),
+            self.inner.get_all_from_posts(post_ids),
         )
         .await
     }
@@ -226,35 +254,40 @@ impl<R: ReplyRepository + 'static> ReplyRepository for ObservableReplyRepository
         .await
     }
 
-    async fn update(&self, id: &Ulid, content: &str) -> Result<Reply, errors::DatabaseError> {
+    async fn update(
+        &self,
+        id: &Ulid,
+        content: &str,
+        actor: &ReplyActor,
+    ) -> Result<Reply, errors::DatabaseError> {
         self.track_method(
             "update",
             "UPDATE replies",
             "UPDATE",
             "replies",
-            self.inner.update(id, content),
+            self.inner.update(id, content, actor),
         )
         .await
     }
 
-    async fn delete(&self, id: &Ulid) -> Result<(), errors::DatabaseError> {
+    async fn delete(&self, id: &Ulid, actor: &ReplyActor) -> Result<(), errors::DatabaseError> {
         self.track_method(
             "delete",
             "DELETE replies",
             "DELETE",
             "replies",
-            self.inner.delete(id),
+            self.inner.delete(id, actor),
         )
         .await
     }
 
-    async fn delete_all_by_user_id(&self, id: &Ulid) -> Result<Vec<String>, errors::DatabaseError> {
+    async fn compact_tombstones(&self, limit: i64) -> Result<u64, errors::DatabaseError> {
         self.track_method(
-            "delete_all_by_user_id",
-            "DELETE replies by user_id",
+            "compact_tombstones",
+            "DELETE replies tombstones",
             "DELETE",
             "replies",
-            self.inner.delete_all_by_user_id(id),
+            self.inner.compact_tombstones(limit),
         )
         .await
     }
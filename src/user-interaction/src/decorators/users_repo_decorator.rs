@@ -2,8 +2,7 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 use crate::errors;
 use crate::errors::DatabaseError;
-use crate::repositories::users_repo::{DeletedPostIds, PostgresUsersRepository, UsersRepository};
-use crate::utils::helpers::get_container_id;
+use crate::repositories::users_repo::{DeletedEntityIds, PostgresUsersRepository, UsersRepository};
 use async_trait::async_trait;
 use opentelemetry::metrics::{Counter, Histogram};
 use opentelemetry::trace::SpanKind;
@@ -14,6 +13,7 @@ use tracing::{field, info_span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use ulid::Ulid;
 use crate::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::utils::get_container_id;
 
 pub struct DecoratedUsersRepository<U: UsersRepository> {
     users_repo: U,
@@ -166,7 +166,7 @@ impl<U: UsersRepository + 'static> UsersRepository for ObservableUsersRepository
         .await
     }
 
-    async fn delete(&self, user_id: &Ulid) -> Result<DeletedPostIds, DatabaseError> {
+    async fn delete(&self, user_id: &Ulid) -> Result<DeletedEntityIds, DatabaseError> {
         self.track_method(
             "delete",
             "DELETE FROM users",
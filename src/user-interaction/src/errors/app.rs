@@ -1,40 +1,14 @@
 ﻿use crate::errors;
+use crate::errors::ProblemResponse;
 use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::Json;
-use opentelemetry::trace::TraceContextExt;
 use reqwest::Error as ReqwestError;
-use serde_json::json;
 use std::env::VarError;
 use thiserror::Error;
 use tonic::Status;
-use tracing::{error, Span};
-use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing::error;
 
-pub trait ProblemResponse {
-    fn status_code(&self) -> StatusCode;
-    fn title(&self) -> &str;
-    fn detail(&self) -> String;
-
-    fn public_detail(&self) -> &str {
-        "An unexpected server error occurred. Please try again later."
-    }
-
-    fn to_response(&self) -> Response {
-        let context = Span::current().context();
-        let trace_id = context.span().span_context().trace_id().to_string();
-        let body = json!({
-            "type": format!("https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/{}", self.status_code()),
-            "title": self.title(),
-            "status": self.status_code().as_u16(),
-            "detail": self.public_detail(),
-            "traceId": trace_id
-        });
-
-        (self.status_code(), Json(body)).into_response()
-    }
-}
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error(transparent)]
@@ -56,6 +30,8 @@ pub enum AppError {
     EnvironmentVariableNotFound(#[from] VarError),
     #[error("{0}")]
     NotFound(String),
+    #[error("{0}")]
+    Forbidden(String),
 }
 
 impl ProblemResponse for AppError {
@@ -68,6 +44,7 @@ impl ProblemResponse for AppError {
             AppError::ValidationError(err) => err.status_code(),
             AppError::EnvironmentVariableNotFound(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -81,6 +58,7 @@ impl ProblemResponse for AppError {
             AppError::PostgresError(err) => err.title(),
 
             AppError::NotFound(_) => "Resource Not Found",
+            AppError::Forbidden(_) => "Forbidden",
             _ => "Internal Server Error",
         }
     }
@@ -89,16 +67,17 @@ impl ProblemResponse for AppError {
         self.to_string()
     }
 
-    fn public_detail(&self) -> &str {
+    fn public_detail(&self) -> String {
         match self {
             AppError::ValidationError(err) => err.public_detail(),
             AppError::AuthError(err) => err.public_detail(),
             AppError::RedisError(err) => err.public_detail(),
             AppError::PostgresError(err) => err.public_detail(),
             AppError::AmqError(err) => err.public_detail(),
-            AppError::NotFound(err) => err,
+            AppError::NotFound(err) => err.clone(),
+            AppError::Forbidden(err) => err.clone(),
 
-            _ => "An unexpected server error occurred. Please try again later.",
+            _ => String::from("An unexpected server error occurred. Please try again later."),
         }
     }
 }
@@ -117,6 +96,8 @@ impl From<AppError> for Status {
     fn from(value: AppError) -> Self {
         match value.status_code() {
             StatusCode::NOT_FOUND => Status::not_found(value.public_detail()),
+            StatusCode::BAD_REQUEST => Status::invalid_argument(value.public_detail()),
+            StatusCode::FORBIDDEN => Status::permission_denied(value.public_detail()),
             _ => Status::internal(value.public_detail()),
         }
     }
@@ -2,14 +2,12 @@
 mod app;
 mod postgres;
 mod redis;
-mod auth;
 mod validation;
 
 pub use amq::*;
 pub use app::AppError;
-pub use app::ProblemResponse;
 pub use postgres::DatabaseError;
 
-pub use auth::AuthError;
+pub use zylo_common::errors::{AuthError, ProblemResponse};
 pub use validation::ValidationError;
 pub use redis::{redis_op_error, RedisError};
\ No newline at end of file
@@ -1,4 +1,4 @@
-﻿use crate::errors::app::ProblemResponse;
+﻿use crate::errors::{ProblemResponse, ValidationError};
 use axum::http::StatusCode;
 use sqlx::Error;
 use sqlx::migrate::MigrateError;
@@ -17,6 +17,12 @@ pub enum DatabaseError {
     NotFound(String),
     #[error("{0}")]
     AlreadyExists(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("Stored id has {0} bytes instead of 16")]
+    InvalidId(usize),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
 }
 
 impl ProblemResponse for DatabaseError {
@@ -27,17 +33,23 @@ impl ProblemResponse for DatabaseError {
             DatabaseError::SqlxError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             DatabaseError::NotFound(_) => StatusCode::NOT_FOUND,
             DatabaseError::AlreadyExists(_) => StatusCode::BAD_REQUEST,
+            DatabaseError::Forbidden(_) => StatusCode::FORBIDDEN,
+            DatabaseError::InvalidId(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            DatabaseError::Validation(err) => err.status_code(),
 
         }
     }
 
-    fn title(&self) -> &'static str {
+    fn title(&self) -> &str {
         match self {
             DatabaseError::PoolCreationError(_) => "Internal Server Error",
             DatabaseError::MigrationError(_) => "Internal Server Error",
             DatabaseError::SqlxError(_) => "Internal Server Error",
             DatabaseError::NotFound(_) => "Not Found",
             DatabaseError::AlreadyExists(_) => "Bad Request",
+            DatabaseError::Forbidden(_) => "Forbidden",
+            DatabaseError::InvalidId(_) => "Internal Server Error",
+            DatabaseError::Validation(err) => err.title(),
         }
     }
 
@@ -45,13 +57,16 @@ impl ProblemResponse for DatabaseError {
         self.to_string()
     }
 
-    fn public_detail(&self) -> &str {
+    fn public_detail(&self) -> String {
         match self {
-            DatabaseError::PoolCreationError(_) => "Internal Server Error",
-            DatabaseError::MigrationError(_) => "Internal Server Error",
-            DatabaseError::SqlxError(_) => "Internal Server Error",
-            DatabaseError::NotFound(public_detail) => public_detail,
-            DatabaseError::AlreadyExists(_) => "Resource with given id already exists",
+            DatabaseError::PoolCreationError(_) => String::from("Internal Server Error"),
+            DatabaseError::MigrationError(_) => String::from("Internal Server Error"),
+            DatabaseError::SqlxError(_) => String::from("Internal Server Error"),
+            DatabaseError::NotFound(public_detail) => public_detail.clone(),
+            DatabaseError::AlreadyExists(_) => String::from("Resource with given id already exists"),
+            DatabaseError::Forbidden(public_detail) => public_detail.clone(),
+            DatabaseError::InvalidId(_) => String::from("Internal Server Error"),
+            DatabaseError::Validation(err) => err.public_detail(),
         }
     }
 }
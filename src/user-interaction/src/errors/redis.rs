@@ -1,4 +1,4 @@
-﻿use crate::errors::app::ProblemResponse;
+﻿use crate::errors::ProblemResponse;
 use axum::http::StatusCode;
 use thiserror::Error;
 
@@ -17,15 +17,11 @@ pub enum RedisError {
 
 impl ProblemResponse for RedisError {
     fn status_code(&self) -> StatusCode {
-        match self {
-            _ => StatusCode::INTERNAL_SERVER_ERROR
-        }
+        StatusCode::INTERNAL_SERVER_ERROR
     }
 
     fn title(&self) -> &'static str {
-        match self {
-            _ => "Internal Server Error"
-        }
+        "Internal Server Error"
     }
 
     fn detail(&self) -> String {
@@ -12,11 +12,23 @@ pub enum ValidationError {
     
     #[error("Invalid replied object ID")]
     InvalidReplyToId,
+
+    #[error("Post does not exist")]
+    PostNotFound,
+
+    #[error("Invalid pagination cursor")]
+    InvalidCursor,
+
+    #[error("Unknown reaction kind: {0}")]
+    InvalidReaction(String),
 }
 
 impl ProblemResponse for ValidationError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::BAD_REQUEST
+        match self {
+            ValidationError::PostNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_REQUEST,
+        }
     }
 
     fn title(&self) -> &'static str {
@@ -0,0 +1,12 @@
+pub mod app;
+pub mod decorators;
+pub mod errors;
+pub mod models;
+pub mod repositories;
+pub mod routes;
+pub mod services;
+pub mod settings;
+pub mod utils;
+
+#[cfg(test)]
+mod test_support;
@@ -1,38 +1,33 @@
-use crate::app::{init_logs, init_metrics, init_traces, init_tracing, run_app};
-use crate::decorators::cache_service_decorator::DecoratedCacheService;
-use crate::decorators::grpc_server_decorator::DecoratedGrpcServer;
-use crate::decorators::posts_repo_decorator::DecoratedPostsRepository;
-use crate::decorators::reply_repo_decorator::DecoratedReplyRepository;
-use crate::decorators::users_repo_decorator::DecoratedUsersRepository;
-use crate::models::app_state::AppState;
-use crate::repositories::init_db;
-use crate::repositories::interaction_repo::RedisInteractionRepository;
-use crate::services::amq_client::{AmqClient, RabbitMqClient};
-use crate::services::post_interactions_service::PostInteractionsServiceImpl;
-use crate::services::reply_service::ReplyServiceImpl;
-use crate::settings::AppConfig;
 use dotenv::dotenv;
 use std::sync::Arc;
-
-mod app;
-mod auth;
-mod decorators;
-mod errors;
-mod models;
-mod repositories;
-mod routes;
-mod services;
-mod settings;
-mod utils;
+use user_interaction::app::run_app;
+use user_interaction::decorators::bookmarks_repo_decorator::DecoratedBookmarksRepository;
+use user_interaction::decorators::cache_service_decorator::DecoratedCacheService;
+use user_interaction::decorators::grpc_server_decorator::DecoratedGrpcServer;
+use user_interaction::decorators::posts_repo_decorator::DecoratedPostsRepository;
+use user_interaction::decorators::reply_repo_decorator::DecoratedReplyRepository;
+use user_interaction::decorators::users_repo_decorator::DecoratedUsersRepository;
+use user_interaction::models::app_state::AppState;
+use user_interaction::repositories::init_db;
+use user_interaction::repositories::interaction_repo::PostgresInteractionRepository;
+use user_interaction::repositories::outbox_repo::PostgresOutboxRepository;
+use user_interaction::repositories::processed_messages_repo::PostgresProcessedMessagesRepository;
+use user_interaction::services::amq_client::{AmqClient, RabbitMqClient};
+use user_interaction::services::post_interactions_service::PostInteractionsServiceImpl;
+use user_interaction::services::reply_service::ReplyServiceImpl;
+use user_interaction::settings::AppConfig;
+use user_interaction::utils::constants::OTEL_SERVICE_NAME;
+use zylo_common::outbox::OutboxRelay;
+use zylo_common::telemetry::{init_logs, init_metrics, init_traces, init_tracing};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
     let config = AppConfig::new().await;
 
-    let trace_provider = init_traces(&config.otel_collector.address);
-    let meter_provider = init_metrics(&config.otel_collector.address);
-    let logger_provider = init_logs(&config.otel_collector.address);
+    let trace_provider = init_traces(OTEL_SERVICE_NAME, &config.otel_collector.address);
+    let meter_provider = init_metrics(OTEL_SERVICE_NAME, &config.otel_collector.address);
+    let logger_provider = init_logs(OTEL_SERVICE_NAME, &config.otel_collector.address);
     init_tracing(&logger_provider, &trace_provider);
 
     let pg_pool = init_db(&config.database).await?;
@@ -54,25 +49,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .build(),
     );
 
+    let bookmarks_repo = Arc::new(
+        DecoratedBookmarksRepository::new(pg_pool.clone())
+            .observable()
+            .build(),
+    );
+
     let cache_service = Arc::new(
         DecoratedCacheService::new(config.redis.clone())?
             .observable()
             .build(),
     );
 
-    let interaction_repo = Arc::new(RedisInteractionRepository::new(cache_service.clone()));
+    let interaction_repo = Arc::new(PostgresInteractionRepository::new(
+        pg_pool.clone(),
+        cache_service.clone(),
+        &config.reactions,
+    ));
+    interaction_repo.spawn_view_checkpoints();
     
     let reply_service = Arc::new(ReplyServiceImpl::new(
         reply_repo.clone(),
         interaction_repo.clone(),
+        config.replies,
     ));
+    reply_service.spawn_compaction();
 
     let post_interactions_service = Arc::new(PostInteractionsServiceImpl::new(
         reply_service.clone(),
         interaction_repo.clone(),
+        bookmarks_repo,
+        posts_repo.clone(),
     ));
 
-    let amq_client = Arc::new(RabbitMqClient::new(&config.amq).await?);
+    let processed_messages = Arc::new(PostgresProcessedMessagesRepository::new(pg_pool.clone()));
+    processed_messages.spawn_cleanup();
+
+    let amq_client = Arc::new(RabbitMqClient::new(&config.amq, processed_messages).await?);
     amq_client
         .setup_listeners(
             posts_repo.clone(),
@@ -80,6 +93,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             interaction_repo.clone(),
         )
         .await?;
+    amq_client.spawn_supervisor();
+
+    let outbox_repo = Arc::new(PostgresOutboxRepository::new(pg_pool.clone()));
+    OutboxRelay::new(outbox_repo, amq_client.clone()).spawn();
 
     let grpc_server =
         DecoratedGrpcServer::new(reply_service.clone(), post_interactions_service.clone())
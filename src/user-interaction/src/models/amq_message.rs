@@ -1,34 +1,70 @@
-﻿use chrono::NaiveDateTime;
+﻿use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
-use crate::models::reply::ReplyResponse;
+use crate::models::reply::{ReplyActor, ReplyResponse};
+use zylo_common::events::{format_timestamp, Event};
 
 pub fn format_datetime(naive: NaiveDateTime) -> String {
-    naive.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()
+    format_timestamp(naive.and_utc())
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct IdOnlyMessage {
+#[derive(Debug, Deserialize, Clone)]
+pub struct PostCreatedMessage {
     pub id: Ulid,
+    #[serde(rename = "userId")]
+    pub user_id: Ulid,
 }
 
-impl From<Ulid> for IdOnlyMessage {
-    fn from(value: Ulid) -> Self {
-        Self { id: value }
-    }
+impl Event for PostCreatedMessage {
+    const TYPE: &'static str = "post.created";
+    const VERSION: u32 = 1;
 }
 
 #[derive(Debug, Deserialize, Clone)]
-pub struct PostCreatedMessage {
+pub struct PostDeletedMessage {
+    pub id: Ulid,
+}
+
+impl Event for PostDeletedMessage {
+    const TYPE: &'static str = "post.deleted";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserCreatedMessage {
+    pub id: Ulid,
+}
+
+impl Event for UserCreatedMessage {
+    const TYPE: &'static str = "user.created";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserDeletedMessage {
     pub id: Ulid,
-    #[serde(rename = "userId")]
-    pub user_id: Ulid,
 }
 
-pub type PostDeletedMessage = IdOnlyMessage;
-pub type UserCreatedMessage = IdOnlyMessage;
-pub type UserDeletedMessage = IdOnlyMessage;
-pub type ReplyDeletedMessage = IdOnlyMessage;
+impl Event for UserDeletedMessage {
+    const TYPE: &'static str = "user.deleted";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplyDeletedMessage {
+    pub id: Ulid,
+}
+
+impl From<Ulid> for ReplyDeletedMessage {
+    fn from(value: Ulid) -> Self {
+        Self { id: value }
+    }
+}
+
+impl Event for ReplyDeletedMessage {
+    const TYPE: &'static str = "reply.deleted";
+    const VERSION: u32 = 1;
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReplyCreatedMessage {
@@ -42,18 +78,23 @@ pub struct ReplyCreatedMessage {
     pub created_at: String,
 }
 
-impl From<ReplyResponse> for ReplyCreatedMessage {
-    fn from(value: ReplyResponse) -> Self {
+impl ReplyCreatedMessage {
+    pub fn new(reply: ReplyResponse, user_id: Ulid) -> Self {
         Self {
-            id: value.id,
-            user_id: value.user_id,
-            reply_to_id: value.reply_to_id,
-            content: value.content,
-            created_at: value.created_at,
+            id: reply.id,
+            user_id,
+            reply_to_id: reply.reply_to_id,
+            content: reply.content,
+            created_at: reply.created_at,
         }
     }
 }
 
+impl Event for ReplyCreatedMessage {
+    const TYPE: &'static str = "reply.created";
+    const VERSION: u32 = 1;
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReplyUpdatedMessage {
     pub id: Ulid,
@@ -71,3 +112,249 @@ impl ReplyUpdatedMessage {
         }
     }
 }
+
+impl Event for ReplyUpdatedMessage {
+    const TYPE: &'static str = "reply.updated";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PostLikedMessage {
+    pub id: Ulid,
+    #[serde(rename = "userId")]
+    pub user_id: Ulid,
+    #[serde(rename = "likedAt")]
+    pub liked_at: String,
+}
+
+impl PostLikedMessage {
+    pub fn new(id: Ulid, user_id: Ulid) -> Self {
+        Self {
+            id,
+            user_id,
+            liked_at: format_datetime(Utc::now().naive_utc()),
+        }
+    }
+}
+
+impl Event for PostLikedMessage {
+    const TYPE: &'static str = "post.liked";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PostUnlikedMessage {
+    pub id: Ulid,
+    #[serde(rename = "userId")]
+    pub user_id: Ulid,
+}
+
+impl PostUnlikedMessage {
+    pub fn new(id: Ulid, user_id: Ulid) -> Self {
+        Self { id, user_id }
+    }
+}
+
+impl Event for PostUnlikedMessage {
+    const TYPE: &'static str = "post.unliked";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReplyLikedMessage {
+    pub id: Ulid,
+    #[serde(rename = "userId")]
+    pub user_id: Ulid,
+    #[serde(rename = "likedAt")]
+    pub liked_at: String,
+}
+
+impl ReplyLikedMessage {
+    pub fn new(id: Ulid, user_id: Ulid) -> Self {
+        Self {
+            id,
+            user_id,
+            liked_at: format_datetime(Utc::now().naive_utc()),
+        }
+    }
+}
+
+impl Event for ReplyLikedMessage {
+    const TYPE: &'static str = "reply.liked";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AuditEventMessage {
+    #[serde(rename = "actorId")]
+    pub actor_id: String,
+    #[serde(rename = "actorRoles")]
+    pub actor_roles: Vec<String>,
+    pub action: String,
+    #[serde(rename = "resourceType")]
+    pub resource_type: String,
+    #[serde(rename = "resourceId")]
+    pub resource_id: Ulid,
+    #[serde(rename = "resourceOwnerId")]
+    pub resource_owner_id: Ulid,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: String,
+}
+
+impl AuditEventMessage {
+    pub fn new(
+        actor: &ReplyActor,
+        action: &str,
+        resource_type: &str,
+        resource_id: Ulid,
+        resource_owner_id: Ulid,
+    ) -> Self {
+        Self {
+            actor_id: actor.user_id.to_string(),
+            actor_roles: actor.roles.clone(),
+            action: action.to_string(),
+            resource_type: resource_type.to_string(),
+            resource_id,
+            resource_owner_id,
+            occurred_at: format_timestamp(Utc::now()),
+        }
+    }
+}
+
+impl Event for AuditEventMessage {
+    const TYPE: &'static str = "audit.moderation";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use zylo_common::events::{decode, EventEnvelope};
+
+    fn validator(schema_name: &str) -> jsonschema::Validator {
+        let path = format!(
+            "{}/../schemas/events/{}.schema.json",
+            env!("CARGO_MANIFEST_DIR"),
+            schema_name
+        );
+        let schema: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        jsonschema::options()
+            .should_validate_formats(true)
+            .build(&schema)
+            .unwrap()
+    }
+
+    fn assert_valid(schema_name: &str, instance: &Value) {
+        let validator = validator(schema_name);
+        let errors: Vec<String> = validator
+            .iter_errors(instance)
+            .map(|error| error.to_string())
+            .collect();
+
+        assert!(
+            errors.is_empty(),
+            "{} does not match {}: {}",
+            instance,
+            schema_name,
+            errors.join(", ")
+        );
+    }
+
+    fn payload_schema<T: Event>() -> String {
+        format!("{}.v{}", T::TYPE, T::VERSION)
+    }
+
+    /// Publishes `event` the way the outbox does, checks the envelope and payload schemas and
+    /// decodes it again.
+    fn round_trip<T: Event + Serialize + DeserializeOwned>(event: T) -> T {
+        let envelope = serde_json::to_value(EventEnvelope::new(event)).unwrap();
+        assert_valid("envelope", &envelope);
+        assert_valid(&payload_schema::<T>(), &envelope["payload"]);
+
+        decode::<T>(&serde_json::to_vec(&envelope).unwrap())
+            .unwrap()
+            .payload
+    }
+
+    /// Wraps a payload valid against the schema of `T` in an envelope and decodes it.
+    fn decode_schema_example<T: Event + DeserializeOwned>(payload: Value) -> T {
+        assert_valid(&payload_schema::<T>(), &payload);
+        let envelope = json!({
+            "id": Ulid::new(),
+            "type": T::TYPE,
+            "version": T::VERSION,
+            "occurredAt": format_timestamp(Utc::now()),
+            "payload": payload,
+        });
+        assert_valid("envelope", &envelope);
+
+        decode::<T>(&serde_json::to_vec(&envelope).unwrap())
+            .unwrap()
+            .payload
+    }
+
+    #[test]
+    fn reply_events_round_trip_through_their_schemas() {
+        let id = Ulid::new();
+
+        let created = round_trip(ReplyCreatedMessage {
+            id,
+            user_id: Ulid::new(),
+            reply_to_id: Ulid::new(),
+            content: String::from("Hello"),
+            created_at: format_timestamp(Utc::now()),
+        });
+        assert_eq!(created.id, id);
+
+        let updated = round_trip(ReplyUpdatedMessage::new(
+            id,
+            String::from("Hello again"),
+            Utc::now().naive_utc(),
+        ));
+        assert_eq!(updated.content, "Hello again");
+
+        let deleted = round_trip(ReplyDeletedMessage::from(id));
+        assert_eq!(deleted.id, id);
+    }
+
+    #[test]
+    fn audit_event_matches_its_schema() {
+        let moderator = ReplyActor {
+            user_id: Ulid::new(),
+            roles: vec![String::from("moderator")],
+            is_moderator: true,
+        };
+        let event = AuditEventMessage::new(&moderator, "delete", "reply", Ulid::new(), Ulid::new());
+
+        let envelope = serde_json::to_value(EventEnvelope::new(event)).unwrap();
+        assert_valid("envelope", &envelope);
+        assert_valid(&payload_schema::<AuditEventMessage>(), &envelope["payload"]);
+    }
+
+    #[test]
+    fn consumed_events_from_the_schemas_decode() {
+        let id = Ulid::new();
+        let user_id = Ulid::new();
+
+        let created: PostCreatedMessage = decode_schema_example(json!({
+            "id": id,
+            "userId": user_id,
+            "content": "Hello",
+            "createdAt": format_timestamp(Utc::now()),
+        }));
+        assert_eq!(created.user_id, user_id);
+
+        let deleted: PostDeletedMessage =
+            decode_schema_example(json!({ "id": id, "userId": user_id }));
+        assert_eq!(deleted.id, id);
+
+        let user_created: UserCreatedMessage = decode_schema_example(json!({ "id": user_id }));
+        assert_eq!(user_created.id, user_id);
+
+        let user_deleted: UserDeletedMessage = decode_schema_example(json!({ "id": user_id }));
+        assert_eq!(user_deleted.id, user_id);
+    }
+}
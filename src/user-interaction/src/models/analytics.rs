@@ -0,0 +1,148 @@
+﻿use crate::errors;
+use crate::utils::constants::{
+    ANALYTICS_DAILY_RETENTION_SECONDS, ANALYTICS_HOURLY_RETENTION_SECONDS,
+};
+use chrono::{DateTime, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Granularity {
+    #[default]
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    pub const ALL: [Granularity; 2] = [Granularity::Hour, Granularity::Day];
+
+    pub fn step(&self) -> TimeDelta {
+        match self {
+            Granularity::Hour => TimeDelta::hours(1),
+            Granularity::Day => TimeDelta::days(1),
+        }
+    }
+
+    /// How long a bucket is kept once it has ended.
+    pub fn retention(&self) -> TimeDelta {
+        match self {
+            Granularity::Hour => TimeDelta::seconds(ANALYTICS_HOURLY_RETENTION_SECONDS),
+            Granularity::Day => TimeDelta::seconds(ANALYTICS_DAILY_RETENTION_SECONDS),
+        }
+    }
+
+    /// Range returned when the request does not set `from`.
+    fn default_range(&self) -> TimeDelta {
+        match self {
+            Granularity::Hour => TimeDelta::days(1),
+            Granularity::Day => TimeDelta::days(30),
+        }
+    }
+
+    /// Start of the bucket `at` falls in.
+    pub fn bucket_start(&self, at: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            Granularity::Hour => {
+                at.date().and_time(NaiveTime::MIN) + TimeDelta::hours(at.hour().into())
+            }
+            Granularity::Day => at.date().and_time(NaiveTime::MIN),
+        }
+    }
+
+    /// Identifies the bucket starting at `start` among the buckets of an entity.
+    pub fn bucket_id(&self, start: NaiveDateTime) -> String {
+        match self {
+            Granularity::Hour => start.format("h:%Y%m%d%H").to_string(),
+            Granularity::Day => start.format("d:%Y%m%d").to_string(),
+        }
+    }
+
+    /// Field passed to Postgres' `date_trunc` to group rows by bucket.
+    pub fn date_trunc_field(&self) -> &'static str {
+        match self {
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
+        }
+    }
+}
+
+/// Time range of the analytics of a post. `to` defaults to now and `from` to a day before `to`
+/// for hourly buckets, or thirty days before it for daily buckets.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyticsRequest {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub granularity: Granularity,
+}
+
+impl AnalyticsRequest {
+    /// Starts of the buckets covering the requested range, oldest first. Ranges reaching back
+    /// further than the buckets are kept are rejected.
+    pub fn buckets(
+        &self,
+        now: NaiveDateTime,
+    ) -> Result<Vec<NaiveDateTime>, errors::ValidationError> {
+        let granularity = self.granularity;
+        let to = self.to.map_or(now, |to| to.naive_utc()).min(now);
+        let from = self
+            .from
+            .map_or(to - granularity.default_range(), |from| from.naive_utc());
+
+        if from >= to {
+            return Err(errors::ValidationError::Failed(String::from(
+                "from must be before to",
+            )));
+        }
+
+        if from < granularity.bucket_start(now - granularity.retention()) {
+            return Err(errors::ValidationError::Failed(format!(
+                "{} buckets are only kept for {} days",
+                granularity.date_trunc_field(),
+                granularity.retention().num_days()
+            )));
+        }
+
+        let mut buckets = Vec::new();
+        let mut start = granularity.bucket_start(from);
+        while start < to {
+            buckets.push(start);
+            start += granularity.step();
+        }
+
+        Ok(buckets)
+    }
+}
+
+/// Unique views of an entity during a bucket and the likes given during it that still stand.
+#[derive(Debug, Clone)]
+pub struct AnalyticsBucket {
+    pub start: NaiveDateTime,
+    pub views: u64,
+    pub likes: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AnalyticsBucketResponse {
+    pub start: String,
+    pub views: u64,
+    pub likes: u64,
+}
+
+impl From<AnalyticsBucket> for AnalyticsBucketResponse {
+    fn from(bucket: AnalyticsBucket) -> Self {
+        Self {
+            start: bucket.start.and_utc().to_rfc3339(),
+            views: bucket.views,
+            likes: bucket.likes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PostAnalyticsResponse {
+    #[serde(rename = "postId")]
+    pub post_id: Ulid,
+    pub granularity: Granularity,
+    pub buckets: Vec<AnalyticsBucketResponse>,
+}
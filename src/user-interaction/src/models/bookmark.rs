@@ -0,0 +1,82 @@
+﻿use crate::errors;
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub post_id: Ulid,
+    pub created_at: NaiveDateTime,
+}
+
+/// Position after the last bookmark of a page: when it was created, with the post breaking ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookmarkCursor {
+    pub created_at: NaiveDateTime,
+    pub post_id: Ulid,
+}
+
+impl BookmarkCursor {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}.{}",
+            self.created_at.and_utc().timestamp_micros(),
+            self.post_id
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, errors::ValidationError> {
+        let (created_at, post_id) = cursor
+            .split_once('.')
+            .ok_or(errors::ValidationError::InvalidCursor)?;
+
+        let created_at = created_at
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or(errors::ValidationError::InvalidCursor)?;
+
+        Ok(Self {
+            created_at: created_at.naive_utc(),
+            post_id: Ulid::from_string(post_id)
+                .map_err(|_| errors::ValidationError::InvalidCursor)?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateBookmarkRequest {
+    #[serde(rename = "postId")]
+    pub post_id: Ulid,
+}
+
+/// Which page of bookmarks to return, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct BookmarkPageRequest {
+    pub next: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BookmarkResponse {
+    #[serde(rename = "postId")]
+    pub post_id: Ulid,
+    #[serde(rename = "bookmarkedAt")]
+    pub bookmarked_at: String,
+}
+
+impl From<Bookmark> for BookmarkResponse {
+    fn from(bookmark: Bookmark) -> Self {
+        Self {
+            post_id: bookmark.post_id,
+            bookmarked_at: bookmark.created_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BookmarkPageResponse {
+    pub bookmarks: Vec<BookmarkResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
@@ -0,0 +1,76 @@
+﻿use crate::errors;
+use chrono::{DateTime, NaiveDateTime};
+use serde::Serialize;
+use ulid::Ulid;
+
+#[derive(Debug, Clone)]
+pub struct Like {
+    pub user_id: Ulid,
+    pub entity_id: Ulid,
+    pub liked_at: NaiveDateTime,
+}
+
+/// Position after the last like of a page: when it was given, with the id on the other side of
+/// the like, the user for likes of a post and the post for likes of a user, breaking ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LikeCursor {
+    pub liked_at: NaiveDateTime,
+    pub id: Ulid,
+}
+
+impl LikeCursor {
+    pub fn encode(&self) -> String {
+        format!("{}.{}", self.liked_at.and_utc().timestamp_micros(), self.id)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, errors::ValidationError> {
+        let (liked_at, id) = cursor
+            .split_once('.')
+            .ok_or(errors::ValidationError::InvalidCursor)?;
+
+        let liked_at = liked_at
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or(errors::ValidationError::InvalidCursor)?;
+
+        Ok(Self {
+            liked_at: liked_at.naive_utc(),
+            id: Ulid::from_string(id).map_err(|_| errors::ValidationError::InvalidCursor)?,
+        })
+    }
+}
+
+/// Which page of likes to return, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct LikePageRequest {
+    pub next: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LikeResponse {
+    #[serde(rename = "userId")]
+    pub user_id: Ulid,
+    #[serde(rename = "postId")]
+    pub post_id: Ulid,
+    #[serde(rename = "likedAt")]
+    pub liked_at: String,
+}
+
+impl From<Like> for LikeResponse {
+    fn from(like: Like) -> Self {
+        Self {
+            user_id: like.user_id,
+            post_id: like.entity_id,
+            liked_at: like.liked_at.and_utc().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LikePageResponse {
+    pub likes: Vec<LikeResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
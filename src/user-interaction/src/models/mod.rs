@@ -2,8 +2,13 @@
 use crate::errors;
 
 pub mod reply;
+pub mod like;
+pub mod reaction;
+pub mod bookmark;
+pub mod analytics;
 pub mod app_state;
 pub mod amq_message;
+pub mod outbox;
 
 #[async_trait]
 pub trait Finalizer {
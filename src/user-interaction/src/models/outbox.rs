@@ -0,0 +1,27 @@
+use serde_json::Value;
+use ulid::Ulid;
+use zylo_common::outbox::OutboxEntry;
+
+/// Event stored in the `outbox` table until the relay publishes it to RabbitMQ.
+#[derive(Debug, Clone)]
+pub struct OutboxMessage {
+    pub id: Ulid,
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: Value,
+    pub attempts: u32,
+}
+
+impl OutboxEntry for OutboxMessage {
+    fn id(&self) -> Ulid {
+        self.id
+    }
+
+    fn routing_key(&self) -> &str {
+        &self.routing_key
+    }
+
+    fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
@@ -0,0 +1,6 @@
+﻿use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReactionRequest {
+    pub kind: String,
+}
@@ -1,17 +1,26 @@
-﻿use chrono::{NaiveDateTime, TimeZone, Utc};
+﻿use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use ulid::Ulid;
 use crate::errors;
+use crate::utils::constants::DELETED_REPLY_CONTENT;
 use crate::utils::helpers::Validate;
+use zylo_common::auth::{Moderator, Principal, RequireRole};
 
 #[derive(Debug, Clone)]
 pub struct Reply {
     pub id: Ulid,
     pub post_id: Ulid,
     pub reply_to_id: Ulid,
-    pub user_id: Ulid,
+    /// Cleared, along with the content, once the reply becomes a tombstone.
+    pub user_id: Option<Ulid>,
     pub content: String,
     pub created_at: NaiveDateTime,
+    /// Set when a reply that still had replies of its own was deleted. Such a reply is kept as a
+    /// tombstone so the thread under it stays in place, until compaction removes it.
+    pub deleted_at: Option<NaiveDateTime>,
+    /// Materialized path of hex ids from the post down to this reply, e.g. `/post/parent/reply/`.
+    pub path: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,7 +28,8 @@ pub struct ReplyResponse {
     pub id: Ulid,
     #[serde(skip_serializing)]
     pub post_id: Ulid,
-    pub user_id: Ulid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Ulid>,
     #[serde(rename="replyToId")]
     pub reply_to_id: Ulid,
     pub content: String,
@@ -31,25 +41,138 @@ pub struct ReplyResponse {
     pub views: u64,
     #[serde(rename="userInteracted", skip_serializing_if = "Option::is_none")]
     pub user_interacted: Option<bool>,
+    /// Reaction counts by kind, likes included.
+    #[serde(default)]
+    pub reactions: HashMap<String, u64>,
+    #[serde(rename="userReaction", skip_serializing_if = "Option::is_none")]
+    pub user_reaction: Option<String>,
+    #[serde(rename="hasMoreChildren", default)]
+    pub has_more_children: bool,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(skip)]
+    pub path: String,
 }
 
 impl From<Reply> for ReplyResponse {
     fn from(value: Reply) -> Self {
+        let deleted = value.deleted_at.is_some();
+        let content = if deleted {
+            String::from(DELETED_REPLY_CONTENT)
+        } else {
+            value.content
+        };
+
         Self {
             id: value.id,
             post_id: value.post_id,
             user_id: value.user_id,
             reply_to_id: value.reply_to_id,
-            content: value.content,
+            content,
             created_at: Utc.from_utc_datetime(&value.created_at).to_rfc3339(),
             nested_replies: Vec::<ReplyResponse>::new(),
             likes: 0,
             views: 0,
             user_interacted: None,
+            reactions: HashMap::new(),
+            user_reaction: None,
+            has_more_children: false,
+            deleted,
+            path: value.path,
+        }
+    }
+}
+
+/// A reply loaded as part of a truncated thread, with the number of direct replies it has.
+#[derive(Debug, Clone)]
+pub struct ThreadReply {
+    pub reply: Reply,
+    pub child_count: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplySort {
+    #[default]
+    Oldest,
+    Newest,
+    MostLiked,
+}
+
+impl ReplySort {
+    fn cursor_prefix(&self) -> &'static str {
+        match self {
+            ReplySort::Oldest => "o",
+            ReplySort::Newest => "n",
+            ReplySort::MostLiked => "l",
         }
     }
 }
 
+/// Position after the last reply of a page: its creation time in microseconds for the
+/// chronological sorts, its like count for `MostLiked`, with the id breaking ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplyCursor {
+    pub key: i64,
+    pub id: Ulid,
+}
+
+impl ReplyCursor {
+    pub fn from_created_at(created_at: &NaiveDateTime, id: Ulid) -> Self {
+        Self {
+            key: created_at.and_utc().timestamp_micros(),
+            id,
+        }
+    }
+
+    pub fn created_at(&self) -> Result<NaiveDateTime, errors::ValidationError> {
+        DateTime::from_timestamp_micros(self.key)
+            .map(|at| at.naive_utc())
+            .ok_or(errors::ValidationError::InvalidCursor)
+    }
+
+    pub fn encode(&self, sort: ReplySort) -> String {
+        format!("{}.{}.{}", sort.cursor_prefix(), self.key, self.id)
+    }
+
+    /// Parses a cursor, rejecting one that was issued for a different sort order.
+    pub fn decode(cursor: &str, sort: ReplySort) -> Result<Self, errors::ValidationError> {
+        let mut parts = cursor.splitn(3, '.');
+        let (Some(prefix), Some(key), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(errors::ValidationError::InvalidCursor);
+        };
+
+        if prefix != sort.cursor_prefix() {
+            return Err(errors::ValidationError::InvalidCursor);
+        }
+
+        Ok(Self {
+            key: key
+                .parse()
+                .map_err(|_| errors::ValidationError::InvalidCursor)?,
+            id: Ulid::from_string(id).map_err(|_| errors::ValidationError::InvalidCursor)?,
+        })
+    }
+}
+
+/// Which replies of a post or reply to return. Unset values fall back to the configured
+/// defaults and every value is capped by the configured maximum.
+#[derive(Debug, Clone, Default)]
+pub struct ReplyPageRequest {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub sort: ReplySort,
+    pub depth: Option<u32>,
+    pub children: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReplyPageResponse {
+    pub replies: Vec<ReplyResponse>,
+    #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct UpdateReplyRequest {
     pub content: String,
@@ -58,8 +181,6 @@ pub struct UpdateReplyRequest {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct CreateReplyRequest {
-    #[serde(rename="userId")]
-    pub user_id: Ulid,
     #[serde(rename="replyToId")]
     pub reply_to_id: Ulid,
     pub content: String,
@@ -75,15 +196,58 @@ impl Validate for CreateReplyRequest {
         if self.reply_to_id.is_nil() {
             return Err(errors::ValidationError::InvalidReplyToId)
         }   
-        
-        if self.user_id.is_nil() {
-            return Err(errors::ValidationError::InvalidUserId)
-        }
 
         Ok(())
     }
 }
 
+/// The caller changing a reply. Only the author may edit or delete a reply unless the caller
+/// is a moderator. Their roles are kept for the audit trail of moderator changes.
+#[derive(Debug, Clone)]
+pub struct ReplyActor {
+    pub user_id: Ulid,
+    pub roles: Vec<String>,
+    pub is_moderator: bool,
+}
+
+impl ReplyActor {
+    /// The caller, with moderator rights when they hold the moderator role.
+    pub fn new(
+        principal: &Principal,
+        moderator: Option<RequireRole<Moderator>>,
+    ) -> Result<Self, errors::ValidationError> {
+        let actor = Self::try_from(principal)?;
+        Ok(match moderator {
+            Some(_) => actor.moderator(),
+            None => actor,
+        })
+    }
+
+    /// Acts with moderator rights, for routes that have already required the moderator role.
+    pub fn moderator(self) -> Self {
+        Self {
+            is_moderator: true,
+            ..self
+        }
+    }
+}
+
+/// The caller acting as the author, without moderator rights.
+impl TryFrom<&Principal> for ReplyActor {
+    type Error = errors::ValidationError;
+
+    fn try_from(principal: &Principal) -> Result<Self, Self::Error> {
+        let user_id = Ulid::from_string(&principal.user_id)
+            .map_err(|_| errors::ValidationError::InvalidUserId)?;
+
+        Ok(Self {
+            user_id,
+            roles: principal.roles.clone(),
+            is_moderator: false,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PostInteractionResponse {
     #[serde(rename = "postId")]
@@ -93,4 +257,13 @@ pub struct PostInteractionResponse {
     pub views: u64,
     #[serde(rename = "userInteracted", skip_serializing_if = "Option::is_none")]
     pub user_interacted: Option<bool>,
+    #[serde(default)]
+    pub reactions: HashMap<String, u64>,
+    #[serde(rename = "userReaction", skip_serializing_if = "Option::is_none")]
+    pub user_reaction: Option<String>,
+    /// Whether the signed in user has bookmarked the post.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bookmarked: Option<bool>,
+    #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
\ No newline at end of file
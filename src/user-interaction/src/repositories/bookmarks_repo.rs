@@ -0,0 +1,159 @@
+﻿use crate::errors;
+use crate::models::bookmark::{Bookmark, BookmarkCursor};
+use crate::repositories::ulid_from_bytes;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use ulid::Ulid;
+
+#[async_trait]
+pub trait BookmarksRepository: Send + Sync {
+    /// Bookmarks a post, returning false if the user had already bookmarked it.
+    async fn create(&self, user_id: &Ulid, post_id: &Ulid) -> Result<bool, errors::DatabaseError>;
+    /// Removes a bookmark, returning false if there was none.
+    async fn delete(&self, user_id: &Ulid, post_id: &Ulid) -> Result<bool, errors::DatabaseError>;
+    /// Bookmarks of a user, most recent first.
+    async fn get_page(
+        &self,
+        user_id: &Ulid,
+        after: Option<BookmarkCursor>,
+        limit: i64,
+    ) -> Result<Vec<Bookmark>, errors::DatabaseError>;
+    /// Which of the given posts the user has bookmarked.
+    async fn get_bookmarked(
+        &self,
+        user_id: &Ulid,
+        post_ids: &[Ulid],
+    ) -> Result<HashSet<Ulid>, errors::DatabaseError>;
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct BookmarkRow {
+    post_id: Vec<u8>,
+    created_at: NaiveDateTime,
+}
+
+impl TryFrom<BookmarkRow> for Bookmark {
+    type Error = errors::DatabaseError;
+
+    fn try_from(row: BookmarkRow) -> Result<Self, Self::Error> {
+        Ok(Bookmark {
+            post_id: ulid_from_bytes(row.post_id)?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+/// Bookmarks reference `posts` and `users` with cascading deletes, so the rows go away when the
+/// post.deleted and user.deleted consumers remove the post or the user.
+pub struct PostgresBookmarksRepository {
+    pool: PgPool,
+}
+
+impl PostgresBookmarksRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BookmarksRepository for PostgresBookmarksRepository {
+    async fn create(&self, user_id: &Ulid, post_id: &Ulid) -> Result<bool, errors::DatabaseError> {
+        let (post_exists, created): (bool, bool) = sqlx::query_as(
+            r#"
+            WITH post AS (
+                SELECT id FROM posts WHERE id = $2
+            ), inserted AS (
+                INSERT INTO bookmarks (user_id, post_id)
+                SELECT $1, id FROM post
+                ON CONFLICT (user_id, post_id) DO NOTHING
+                RETURNING post_id
+            )
+            SELECT EXISTS (SELECT 1 FROM post), EXISTS (SELECT 1 FROM inserted)
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .bind(post_id.to_bytes())
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !post_exists {
+            return Err(errors::ValidationError::PostNotFound.into());
+        }
+
+        Ok(created)
+    }
+
+    async fn delete(&self, user_id: &Ulid, post_id: &Ulid) -> Result<bool, errors::DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM bookmarks
+            WHERE user_id = $1 AND post_id = $2
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .bind(post_id.to_bytes())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_page(
+        &self,
+        user_id: &Ulid,
+        after: Option<BookmarkCursor>,
+        limit: i64,
+    ) -> Result<Vec<Bookmark>, errors::DatabaseError> {
+        let rows: Vec<BookmarkRow> = sqlx::query_as(
+            r#"
+            SELECT post_id, created_at
+            FROM bookmarks
+            WHERE user_id = $1
+              AND ($2::TIMESTAMP IS NULL OR (created_at, post_id) < ($2, $3::BYTEA))
+            ORDER BY created_at DESC, post_id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .bind(after.map(|cursor| cursor.created_at))
+        .bind(after.map(|cursor| cursor.post_id.to_bytes()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Bookmark::try_from).collect()
+    }
+
+    async fn get_bookmarked(
+        &self,
+        user_id: &Ulid,
+        post_ids: &[Ulid],
+    ) -> Result<HashSet<Ulid>, errors::DatabaseError> {
+        if post_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let ids = post_ids
+            .iter()
+            .map(|id| id.to_bytes().to_vec())
+            .collect::<Vec<_>>();
+
+        let rows: Vec<(Vec<u8>,)> = sqlx::query_as(
+            r#"
+            SELECT post_id
+            FROM bookmarks
+            WHERE user_id = $1 AND post_id = ANY($2)
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(post_id,)| ulid_from_bytes(post_id))
+            .collect()
+    }
+}
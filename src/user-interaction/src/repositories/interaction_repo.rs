@@ -1,171 +1,937 @@
 ﻿use crate::errors;
+use crate::models::amq_message::{PostLikedMessage, PostUnlikedMessage, ReplyLikedMessage};
+use crate::models::analytics::{AnalyticsBucket, Granularity};
+use crate::models::like::{Like, LikeCursor};
+use crate::repositories::outbox_repo::enqueue_event;
+use crate::repositories::ulid_from_bytes;
 use crate::services::cache_service::CacheService;
+use crate::settings::Reactions;
+use crate::utils::constants::{
+    LIKE_REACTION, POST_EXCHANGE_NAME, VIEW_CHECKPOINT_BATCH_SIZE, VIEW_CHECKPOINT_INTERVAL_SECONDS,
+};
 use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
 use ulid::Ulid;
 
 #[async_trait]
 pub trait InteractionRepository: Send + Sync {
-    async fn is_liked(
+    async fn get_many_likes(
+        &self,
+        resource_key: &[String],
+    ) -> Result<HashMap<String, u64>, errors::AppError>;
+    /// Likes a post. Returns whether it was not liked by the user yet.
+    async fn like(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError>;
+    /// Removes the like of a user on a post. Returns whether the post was liked by the user.
+    async fn unlike(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError>;
+
+    /// Sets the reaction of a user on an entity, replacing a reaction of another kind. Returns
+    /// whether the reaction changed.
+    async fn react(
         &self,
         resource_key: &str,
         user_id: &Ulid,
-    ) -> Result<bool, errors::RedisError>;
-    async fn is_many_liked(
+        kind: &str,
+    ) -> Result<bool, errors::AppError>;
+    /// Removes the reaction of a user on an entity, whatever its kind.
+    async fn unreact(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError>;
+    /// Reaction counts of each entity by kind, with every allowed kind present.
+    async fn get_many_reactions(
+        &self,
+        resource_keys: &[String],
+    ) -> Result<HashMap<String, HashMap<String, u64>>, errors::AppError>;
+    /// The kind of reaction a user left on each entity. Entities without one are left out.
+    async fn get_many_user_reactions(
         &self,
         resource_keys: &[String],
         user_id: &Ulid,
-    ) -> Result<HashMap<String, bool>, errors::RedisError>;
-    async fn get_likes(&self, resource_key: &str) -> Result<u64, errors::RedisError>;
-    async fn get_many_likes(&self, resource_key: &[String]) -> Result<HashMap<String, u64>, errors::RedisError>;
-    async fn like(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::RedisError>;
-    async fn unlike(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::RedisError>;
-
-    async fn view(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::RedisError>;
-    async fn get_views(&self, resource_key: &str) -> Result<u64, errors::RedisError>;
-    async fn get_many_views(&self, resource_key: &[String]) -> Result<HashMap<String, u64>, errors::RedisError>;
-    async fn delete_interactions(&self, resource_key: &str) -> Result<(), errors::RedisError>;
+    ) -> Result<HashMap<String, String>, errors::AppError>;
+
+    /// Likes of an entity, newest first, starting after `after`.
+    async fn get_likers(
+        &self,
+        resource_key: &str,
+        after: Option<LikeCursor>,
+        limit: i64,
+    ) -> Result<Vec<Like>, errors::AppError>;
+    /// Posts liked by a user, newest like first, starting after `after`. Likes of replies are
+    /// left out.
+    async fn get_liked_posts(
+        &self,
+        user_id: &Ulid,
+        after: Option<LikeCursor>,
+        limit: i64,
+    ) -> Result<Vec<Like>, errors::AppError>;
+
+    async fn view(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError>;
+    async fn get_views(&self, resource_key: &str) -> Result<u64, errors::AppError>;
+    async fn get_many_views(
+        &self,
+        resource_key: &[String],
+    ) -> Result<HashMap<String, u64>, errors::AppError>;
+    /// Unique views and likes of an entity in each of the buckets starting at `buckets`.
+    async fn get_analytics(
+        &self,
+        resource_key: &str,
+        granularity: Granularity,
+        buckets: &[NaiveDateTime],
+    ) -> Result<Vec<AnalyticsBucket>, errors::AppError>;
+    async fn delete_interactions(&self, resource_key: &str) -> Result<(), errors::AppError>;
     async fn delete_many_interactions(
         &self,
         resource_keys: &[String],
-    ) -> Result<(), errors::RedisError>;
+    ) -> Result<(), errors::AppError>;
+    /// Takes back every reaction of a user who is being deleted.
+    async fn delete_user_reactions(&self, user_id: &Ulid) -> Result<(), errors::AppError>;
 }
 
-pub struct RedisInteractionRepository<C: CacheService + 'static> {
-    cache_service: Arc<C>,
+/// Entities viewed since their last checkpoint.
+const VIEWS_DIRTY_KEY: &str = "interactions:views:dirty";
+
+fn likes_key(resource_key: &str) -> String {
+    format!("entity:{}:likes", resource_key)
 }
 
-impl<C: CacheService + 'static> RedisInteractionRepository<C> {
-    pub fn new(cache_service: Arc<C>) -> Self {
-        Self { cache_service }
+/// Likes keep the set they had before reactions of other kinds were added.
+fn reaction_key(resource_key: &str, kind: &str) -> String {
+    if kind == LIKE_REACTION {
+        return likes_key(resource_key);
     }
+
+    format!("entity:{}:reactions:{}", resource_key, kind)
 }
 
-#[async_trait]
-impl<C: CacheService + 'static> InteractionRepository for RedisInteractionRepository<C> {
-    async fn is_liked(
+fn views_key(resource_key: &str) -> String {
+    format!("entity:{}:views", resource_key)
+}
+
+/// Unique views of an entity during one bucket. Buckets only live in Redis and expire once they
+/// are past the retention of their granularity.
+fn view_bucket_key(resource_key: &str, granularity: Granularity, start: NaiveDateTime) -> String {
+    format!(
+        "entity:{}:views:{}",
+        resource_key,
+        granularity.bucket_id(start)
+    )
+}
+
+/// Set once the reactions and views of an entity have been loaded from Postgres into Redis.
+fn cached_key(resource_key: &str) -> String {
+    format!("entity:{}:cached", resource_key)
+}
+
+fn entity_id(resource_key: &str) -> Result<Vec<u8>, errors::AppError> {
+    Ulid::from_string(resource_key)
+        .map(|id| id.to_bytes().to_vec())
+        .map_err(|_| {
+            errors::ValidationError::Failed(format!("Invalid entity id: {}", resource_key)).into()
+        })
+}
+
+/// Entity id, user id and kind of a reaction.
+type ReactionRow = (Vec<u8>, Vec<u8>, String);
+/// Entity id and view HyperLogLog of a checkpoint.
+type SketchRow = (Vec<u8>, Vec<u8>);
+
+#[derive(Debug, sqlx::FromRow)]
+struct LikeRow {
+    entity_id: Vec<u8>,
+    user_id: Vec<u8>,
+    created_at: NaiveDateTime,
+}
+
+impl TryFrom<LikeRow> for Like {
+    type Error = errors::DatabaseError;
+
+    fn try_from(row: LikeRow) -> Result<Self, Self::Error> {
+        Ok(Like {
+            user_id: ulid_from_bytes(row.user_id)?,
+            entity_id: ulid_from_bytes(row.entity_id)?,
+            liked_at: row.created_at,
+        })
+    }
+}
+
+/// Reactions, likes included, are written to Postgres first and mirrored into one Redis set per
+/// entity and kind, which serve all reads. Views only go to Redis HyperLogLogs and are
+/// checkpointed to Postgres periodically. An entity missing from Redis, after a flush or
+/// eviction, is loaded back from Postgres on first use.
+pub struct PostgresInteractionRepository<C: CacheService + 'static> {
+    pool: PgPool,
+    cache_service: Arc<C>,
+    kinds: Vec<String>,
+}
+
+impl<C: CacheService + 'static> PostgresInteractionRepository<C> {
+    pub fn new(pool: PgPool, cache_service: Arc<C>, reactions: &Reactions) -> Self {
+        Self {
+            pool,
+            cache_service,
+            kinds: reactions.allowed(),
+        }
+    }
+
+    fn check_kind(&self, kind: &str) -> Result<(), errors::ValidationError> {
+        if !self.kinds.iter().any(|allowed| allowed == kind) {
+            return Err(errors::ValidationError::InvalidReaction(kind.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Loads the entities among `resource_keys` that are not in Redis yet. Reactions are added to
+    /// their sets and the view checkpoint is merged into the HyperLogLog, so reactions and views
+    /// recorded while loading are kept. Likes only found in Redis, given before likes were stored
+    /// in Postgres, are saved first. Reactions of kinds no longer allowed are not loaded.
+    async fn warm(&self, resource_keys: &[String]) -> Result<(), errors::AppError> {
+        if resource_keys.is_empty() {
+            return Ok(());
+        }
+
+        let cached_keys: Vec<String> = resource_keys.iter().map(|key| cached_key(key)).collect();
+        let cached = self.cache_service.exists_many(&cached_keys).await?;
+        let missing: Vec<&String> = resource_keys
+            .iter()
+            .zip(&cached_keys)
+            .filter(|(_, cached_key)| !cached.get(*cached_key).copied().unwrap_or(false))
+            .map(|(resource_key, _)| resource_key)
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let ids = missing
+            .iter()
+            .map(|key| entity_id(key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (resource_key, id) in missing.iter().zip(&ids) {
+            let cached_likers = self
+                .cache_service
+                .smembers(&likes_key(resource_key))
+                .await?;
+            let user_ids: Vec<Vec<u8>> = cached_likers
+                .iter()
+                .filter_map(|user_id| Ulid::from_string(user_id).ok())
+                .map(|user_id| user_id.to_bytes().to_vec())
+                .collect();
+
+            if !user_ids.is_empty() {
+                self.insert_likes(id, &user_ids).await?;
+            }
+        }
+
+        let (reactions, sketches) = self.load(&ids).await?;
+
+        let mut reactors: HashMap<(Vec<u8>, String), Vec<String>> = HashMap::new();
+        for (id, user_id, kind) in reactions {
+            if let Ok(user_id) = <[u8; 16]>::try_from(user_id) {
+                reactors
+                    .entry((id, kind))
+                    .or_default()
+                    .push(Ulid::from_bytes(user_id).to_string());
+            }
+        }
+        let sketches: HashMap<Vec<u8>, Vec<u8>> = sketches.into_iter().collect();
+
+        for (resource_key, id) in missing.into_iter().zip(ids) {
+            if let Some(sketch) = sketches.get(&id) {
+                self.cache_service
+                    .pfmerge_raw(&views_key(resource_key), sketch)
+                    .await?;
+            }
+
+            let sets: Vec<(String, Vec<String>)> = self
+                .kinds
+                .iter()
+                .map(|kind| {
+                    let members = reactors
+                        .remove(&(id.clone(), kind.clone()))
+                        .unwrap_or_default();
+                    (reaction_key(resource_key, kind), members)
+                })
+                .collect();
+            self.cache_service
+                .fill_sets(&sets, &cached_key(resource_key))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the reactions and the last view checkpoint of the given entities.
+    async fn load(
         &self,
-        resource_key: &str,
+        ids: &[Vec<u8>],
+    ) -> Result<(Vec<ReactionRow>, Vec<SketchRow>), errors::DatabaseError> {
+        let reactions = sqlx::query_as(
+            "SELECT entity_id, user_id, kind FROM reactions WHERE entity_id = ANY($1)",
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sketches = sqlx::query_as(
+            "SELECT entity_id, sketch FROM view_checkpoints WHERE entity_id = ANY($1)",
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((reactions, sketches))
+    }
+
+    async fn upsert_reaction(
+        conn: &mut PgConnection,
+        id: &[u8],
         user_id: &Ulid,
-    ) -> Result<bool, errors::RedisError> {
-        let cache_key = format!("entity:{}:likes", resource_key);
-        self.cache_service
-            .sismember(&cache_key, &user_id.to_string())
-            .await
+        kind: &str,
+    ) -> Result<bool, errors::DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reactions (entity_id, user_id, kind)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (entity_id, user_id) DO UPDATE
+            SET kind = EXCLUDED.kind,
+                created_at = NOW()
+            WHERE reactions.kind <> EXCLUDED.kind
+            "#,
+        )
+        .bind(id)
+        .bind(user_id.to_bytes())
+        .bind(kind)
+        .execute(conn)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
     }
 
-    async fn is_many_liked(
+    async fn insert_likes(
         &self,
-        resource_keys: &[String],
+        id: &[u8],
+        user_ids: &[Vec<u8>],
+    ) -> Result<(), errors::DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO reactions (entity_id, user_id)
+            SELECT $1, user_id FROM UNNEST($2::BYTEA[]) AS user_id
+            WHERE EXISTS (SELECT 1 FROM users WHERE id = user_id)
+            ON CONFLICT (entity_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(user_ids)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the reaction of a user, only when it is of `kind` if one is given, and returns the
+    /// kind of the deleted reaction.
+    async fn delete_reaction(
+        conn: &mut PgConnection,
+        id: &[u8],
         user_id: &Ulid,
-    ) -> Result<HashMap<String, bool>, errors::RedisError> {
-        if resource_keys.is_empty() { 
-            return Ok(HashMap::new());
+        kind: Option<&str>,
+    ) -> Result<Option<String>, errors::DatabaseError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            DELETE FROM reactions
+            WHERE entity_id = $1 AND user_id = $2 AND ($3::VARCHAR IS NULL OR kind = $3)
+            RETURNING kind
+            "#,
+        )
+        .bind(id)
+        .bind(user_id.to_bytes())
+        .bind(kind)
+        .fetch_optional(conn)
+        .await?)
+    }
+
+    async fn select_reaction(
+        conn: &mut PgConnection,
+        id: &[u8],
+        user_id: &Ulid,
+    ) -> Result<Option<String>, errors::DatabaseError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT kind
+            FROM reactions
+            WHERE entity_id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .bind(user_id.to_bytes())
+        .fetch_optional(conn)
+        .await?)
+    }
+
+    async fn post_exists(&self, id: &[u8]) -> Result<bool, errors::DatabaseError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM posts WHERE id = $1)
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn entity_exists(&self, id: &[u8]) -> Result<bool, errors::DatabaseError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM posts WHERE id = $1)
+                OR EXISTS (SELECT 1 FROM replies WHERE id = $1)
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Reactions are stored by entity id alone, so the kind of entity is looked up to pick the
+    /// event of a like.
+    async fn select_entity_type(
+        conn: &mut PgConnection,
+        id: &[u8],
+    ) -> Result<Option<String>, errors::DatabaseError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT CASE
+                WHEN EXISTS (SELECT 1 FROM posts WHERE id = $1) THEN 'post'
+                WHEN EXISTS (SELECT 1 FROM replies WHERE id = $1) THEN 'reply'
+            END
+            "#,
+        )
+        .bind(id)
+        .fetch_one(conn)
+        .await?)
+    }
+
+    /// Adds the event of a like given or taken back to the outbox, in the transaction that
+    /// stored it. Only the likes of replies are published, not their removal.
+    async fn enqueue_like_event(
+        conn: &mut PgConnection,
+        id: &[u8],
+        user_id: &Ulid,
+        liked: bool,
+    ) -> Result<(), errors::DatabaseError> {
+        let entity_id = ulid_from_bytes(id.to_vec())?;
+
+        match (Self::select_entity_type(conn, id).await?.as_deref(), liked) {
+            (Some("post"), true) => {
+                enqueue_event(
+                    conn,
+                    POST_EXCHANGE_NAME,
+                    "post.liked",
+                    PostLikedMessage::new(entity_id, *user_id),
+                )
+                .await
+            }
+            (Some("post"), false) => {
+                enqueue_event(
+                    conn,
+                    POST_EXCHANGE_NAME,
+                    "post.unliked",
+                    PostUnlikedMessage::new(entity_id, *user_id),
+                )
+                .await
+            }
+            (Some("reply"), true) => {
+                enqueue_event(
+                    conn,
+                    POST_EXCHANGE_NAME,
+                    "reply.liked",
+                    ReplyLikedMessage::new(entity_id, *user_id),
+                )
+                .await
+            }
+            _ => Ok(()),
         }
-        
-        let cache_keys: Vec<String> = resource_keys
-            .iter()
-            .map(|key| format!("entity:{}:likes", key))
-            .collect();
-        
-        let results = self.cache_service
-            .sismember_many(&cache_keys, &user_id.to_string())
+    }
+
+    async fn select_likers(
+        &self,
+        id: &[u8],
+        after: Option<LikeCursor>,
+        limit: i64,
+    ) -> Result<Vec<Like>, errors::DatabaseError> {
+        let rows: Vec<LikeRow> = sqlx::query_as(
+            r#"
+            SELECT entity_id, user_id, created_at
+            FROM reactions
+            WHERE entity_id = $1
+              AND kind = $5
+              AND ($2::TIMESTAMP IS NULL OR (created_at, user_id) < ($2, $3::BYTEA))
+            ORDER BY created_at DESC, user_id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(id)
+        .bind(after.map(|cursor| cursor.liked_at))
+        .bind(after.map(|cursor| cursor.id.to_bytes()))
+        .bind(limit)
+        .bind(LIKE_REACTION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Like::try_from).collect()
+    }
+
+    async fn select_liked_posts(
+        &self,
+        user_id: &Ulid,
+        after: Option<LikeCursor>,
+        limit: i64,
+    ) -> Result<Vec<Like>, errors::DatabaseError> {
+        let rows: Vec<LikeRow> = sqlx::query_as(
+            r#"
+            SELECT r.entity_id, r.user_id, r.created_at
+            FROM reactions r
+            JOIN posts p ON p.id = r.entity_id
+            WHERE r.user_id = $1
+              AND r.kind = $5
+              AND ($2::TIMESTAMP IS NULL OR (r.created_at, r.entity_id) < ($2, $3::BYTEA))
+            ORDER BY r.created_at DESC, r.entity_id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .bind(after.map(|cursor| cursor.liked_at))
+        .bind(after.map(|cursor| cursor.id.to_bytes()))
+        .bind(limit)
+        .bind(LIKE_REACTION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Like::try_from).collect()
+    }
+
+    /// Counts the likes given in `[from, to)` that still stand, by bucket.
+    async fn select_like_buckets(
+        &self,
+        id: &[u8],
+        granularity: Granularity,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<HashMap<NaiveDateTime, i64>, errors::DatabaseError> {
+        let rows: Vec<(NaiveDateTime, i64)> = sqlx::query_as(
+            r#"
+            SELECT date_trunc($4, created_at) AS bucket, COUNT(*)
+            FROM reactions
+            WHERE entity_id = $1
+              AND kind = $5
+              AND created_at >= $2
+              AND created_at < $3
+            GROUP BY bucket
+            "#,
+        )
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .bind(granularity.date_trunc_field())
+        .bind(LIKE_REACTION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn delete_entities(&self, ids: &[Vec<u8>]) -> Result<(), errors::DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM reactions WHERE entity_id = ANY($1)")
+            .bind(ids)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM view_checkpoints WHERE entity_id = ANY($1)")
+            .bind(ids)
+            .execute(&mut *tx)
             .await?;
 
-        let mut response = HashMap::new();
-        for (cache_key, views) in results {
-            if let Some(resource_key) = cache_key
-                .strip_prefix("entity:")
-                .and_then(|s| s.strip_suffix(":likes"))
-            {
-                response.insert(resource_key.to_string(), views);
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes the reactions of a user and returns the entity and kind of each.
+    async fn delete_reactions_of(
+        &self,
+        user_id: &Ulid,
+    ) -> Result<Vec<(Vec<u8>, String)>, errors::DatabaseError> {
+        Ok(sqlx::query_as(
+            r#"
+            DELETE FROM reactions
+            WHERE user_id = $1
+            RETURNING entity_id, kind
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn upsert_checkpoints(
+        &self,
+        ids: &[Vec<u8>],
+        views: &[i64],
+        sketches: &[Vec<u8>],
+    ) -> Result<(), errors::DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO view_checkpoints (entity_id, views, sketch)
+            SELECT * FROM UNNEST($1::BYTEA[], $2::BIGINT[], $3::BYTEA[])
+            ON CONFLICT (entity_id) DO UPDATE
+            SET views = EXCLUDED.views,
+                sketch = EXCLUDED.sketch,
+                checkpointed_at = NOW()
+            "#,
+        )
+        .bind(ids)
+        .bind(views)
+        .bind(sketches)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_reaction(
+        &self,
+        resource_key: &str,
+        user_id: &Ulid,
+        kind: Option<&str>,
+    ) -> Result<bool, errors::AppError> {
+        self.warm(&[resource_key.to_string()]).await?;
+
+        let id = entity_id(resource_key)?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(errors::DatabaseError::from)?;
+        let Some(removed) = Self::delete_reaction(&mut tx, &id, user_id, kind).await? else {
+            return Ok(false);
+        };
+        if removed == LIKE_REACTION {
+            Self::enqueue_like_event(&mut tx, &id, user_id, false).await?;
+        }
+
+        self.cache_service
+            .srem(&reaction_key(resource_key, &removed), &user_id.to_string())
+            .await?;
+
+        tx.commit().await.map_err(errors::DatabaseError::from)?;
+        Ok(true)
+    }
+
+    /// Periodically copies the HyperLogLogs of recently viewed entities to Postgres.
+    pub fn spawn_view_checkpoints(self: &Arc<Self>) {
+        let repo = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(VIEW_CHECKPOINT_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                match repo.checkpoint_views().await {
+                    Ok(saved) if saved > 0 => info!("Checkpointed views of {} entities", saved),
+                    Ok(_) => {}
+                    Err(e) => error!("Failed to checkpoint views: {:?}", e),
+                }
+            }
+        });
+    }
+
+    async fn checkpoint_views(&self) -> Result<u64, errors::AppError> {
+        let mut saved = 0;
+
+        loop {
+            let resource_keys = self
+                .cache_service
+                .spop_many(VIEWS_DIRTY_KEY, VIEW_CHECKPOINT_BATCH_SIZE)
+                .await?;
+            if resource_keys.is_empty() {
+                return Ok(saved);
+            }
+
+            if let Err(e) = self.save_checkpoints(&resource_keys).await {
+                for resource_key in &resource_keys {
+                    self.cache_service
+                        .sadd(VIEWS_DIRTY_KEY, resource_key)
+                        .await?;
+                }
+                return Err(e);
+            }
+
+            saved += resource_keys.len() as u64;
+            if resource_keys.len() < VIEW_CHECKPOINT_BATCH_SIZE {
+                return Ok(saved);
             }
         }
-        
-        Ok(response)
     }
 
-    async fn get_likes(&self, resource_key: &str) -> Result<u64, errors::RedisError> {
-        let cache_key = format!("entity:{}:likes", resource_key);
-        self.cache_service.scard(&cache_key).await
+    async fn save_checkpoints(&self, resource_keys: &[String]) -> Result<(), errors::AppError> {
+        let view_keys: Vec<String> = resource_keys.iter().map(|key| views_key(key)).collect();
+        let counts = self.cache_service.pfcount_many(&view_keys).await?;
+
+        let mut ids = Vec::with_capacity(resource_keys.len());
+        let mut views = Vec::with_capacity(resource_keys.len());
+        let mut sketches = Vec::with_capacity(resource_keys.len());
+        for (resource_key, view_key) in resource_keys.iter().zip(&view_keys) {
+            let Some(sketch) = self.cache_service.get_raw(view_key).await? else {
+                continue;
+            };
+
+            ids.push(entity_id(resource_key)?);
+            views.push(counts.get(view_key).copied().unwrap_or(0) as i64);
+            sketches.push(sketch);
+        }
+
+        Ok(self.upsert_checkpoints(&ids, &views, &sketches).await?)
     }
+}
 
+#[async_trait]
+impl<C: CacheService + 'static> InteractionRepository for PostgresInteractionRepository<C> {
     async fn get_many_likes(
         &self,
         resource_keys: &[String],
-    ) -> Result<HashMap<String, u64>, errors::RedisError> {
+    ) -> Result<HashMap<String, u64>, errors::AppError> {
         if resource_keys.is_empty() {
             return Ok(HashMap::new());
         }
 
-        let cache_keys: Vec<String> = resource_keys
-            .iter()
-            .map(|key| format!("entity:{}:likes", key))
-            .collect();
-        
+        self.warm(resource_keys).await?;
+        let cache_keys: Vec<String> = resource_keys.iter().map(|key| likes_key(key)).collect();
         let many_likes = self.cache_service.scard_many(&cache_keys).await?;
-        let mut response = HashMap::new();
 
-        for (cache_key, views) in many_likes {
+        let mut response = HashMap::new();
+        for (cache_key, likes) in many_likes {
             if let Some(resource_key) = cache_key
                 .strip_prefix("entity:")
                 .and_then(|s| s.strip_suffix(":likes"))
             {
-                response.insert(resource_key.to_string(), views);
+                response.insert(resource_key.to_string(), likes);
             }
         }
-        
+
         Ok(response)
     }
 
-    async fn like(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::RedisError> {
-        let cache_key = format!("entity:{}:likes", resource_key);
-        self.cache_service
-            .sadd(&cache_key, &user_id.to_string())
-            .await
+    async fn like(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError> {
+        if !self.post_exists(&entity_id(resource_key)?).await? {
+            return Err(errors::ValidationError::PostNotFound.into());
+        }
+
+        self.react(resource_key, user_id, LIKE_REACTION).await
     }
 
-    async fn unlike(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::RedisError> {
-        let cache_key = format!("entity:{}:likes", resource_key);
-        self.cache_service
-            .srem(&cache_key, &user_id.to_string())
+    async fn unlike(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError> {
+        if !self.post_exists(&entity_id(resource_key)?).await? {
+            return Err(errors::ValidationError::PostNotFound.into());
+        }
+
+        self.remove_reaction(resource_key, user_id, Some(LIKE_REACTION))
             .await
     }
 
-    async fn view(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::RedisError> {
-        let cache_key = format!("entity:{}:views", resource_key);
-        self.cache_service
-            .pfadd(&cache_key, &user_id.to_string())
+    /// The Redis sets are updated while the row is locked, so concurrent reactions of the same
+    /// user reach Redis in the order they were stored. Replacing a like with another kind of
+    /// reaction publishes the like as taken back.
+    async fn react(
+        &self,
+        resource_key: &str,
+        user_id: &Ulid,
+        kind: &str,
+    ) -> Result<bool, errors::AppError> {
+        self.check_kind(kind)?;
+        let id = entity_id(resource_key)?;
+        if !self.entity_exists(&id).await? {
+            return Err(errors::AppError::NotFound(String::from(
+                "Post or reply could not be found",
+            )));
+        }
+
+        self.warm(&[resource_key.to_string()]).await?;
+        let mut tx = self
+            .pool
+            .begin()
             .await
-    }
+            .map_err(errors::DatabaseError::from)?;
+        let previous = Self::select_reaction(&mut tx, &id, user_id).await?;
+        let changed = Self::upsert_reaction(&mut tx, &id, user_id, kind).await?;
+        if changed && kind == LIKE_REACTION {
+            Self::enqueue_like_event(&mut tx, &id, user_id, true).await?;
+        } else if changed && previous.as_deref() == Some(LIKE_REACTION) {
+            Self::enqueue_like_event(&mut tx, &id, user_id, false).await?;
+        }
 
-    async fn get_views(&self, resource_key: &str) -> Result<u64, errors::RedisError> {
-        let cache_key = format!("entity:{}:views", resource_key);
-        let views = self
-            .cache_service
-            .pfcount(&cache_key)
+        let others: Vec<String> = self
+            .kinds
+            .iter()
+            .filter(|other| *other != kind)
+            .map(|other| reaction_key(resource_key, other))
+            .collect();
+        self.cache_service
+            .sadd_exclusive(
+                &reaction_key(resource_key, kind),
+                &others,
+                &user_id.to_string(),
+            )
             .await?;
 
-        Ok(views)
+        tx.commit().await.map_err(errors::DatabaseError::from)?;
+        Ok(changed)
     }
 
-    async fn get_many_views(
+    async fn unreact(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError> {
+        self.remove_reaction(resource_key, user_id, None).await
+    }
+
+    async fn get_many_reactions(
         &self,
         resource_keys: &[String],
-    ) -> Result<HashMap<String, u64>, errors::RedisError> {
+    ) -> Result<HashMap<String, HashMap<String, u64>>, errors::AppError> {
         if resource_keys.is_empty() {
             return Ok(HashMap::new());
         }
 
+        self.warm(resource_keys).await?;
         let cache_keys: Vec<String> = resource_keys
             .iter()
-            .map(|key| format!("entity:{}:views", key))
+            .flat_map(|key| self.kinds.iter().map(move |kind| reaction_key(key, kind)))
             .collect();
+        let counts = self.cache_service.scard_many(&cache_keys).await?;
+
+        Ok(resource_keys
+            .iter()
+            .map(|key| {
+                let reactions = self
+                    .kinds
+                    .iter()
+                    .map(|kind| {
+                        let count = counts.get(&reaction_key(key, kind)).copied();
+                        (kind.clone(), count.unwrap_or(0))
+                    })
+                    .collect();
+                (key.clone(), reactions)
+            })
+            .collect())
+    }
+
+    async fn get_many_user_reactions(
+        &self,
+        resource_keys: &[String],
+        user_id: &Ulid,
+    ) -> Result<HashMap<String, String>, errors::AppError> {
+        if resource_keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        self.warm(resource_keys).await?;
+        let cache_keys: Vec<String> = resource_keys
+            .iter()
+            .flat_map(|key| self.kinds.iter().map(move |kind| reaction_key(key, kind)))
+            .collect();
+        let members = self
+            .cache_service
+            .sismember_many(&cache_keys, &user_id.to_string())
+            .await?;
+
+        Ok(resource_keys
+            .iter()
+            .filter_map(|key| {
+                self.kinds
+                    .iter()
+                    .find(|kind| {
+                        members
+                            .get(&reaction_key(key, kind))
+                            .copied()
+                            .unwrap_or(false)
+                    })
+                    .map(|kind| (key.clone(), kind.clone()))
+            })
+            .collect())
+    }
+
+    async fn get_likers(
+        &self,
+        resource_key: &str,
+        after: Option<LikeCursor>,
+        limit: i64,
+    ) -> Result<Vec<Like>, errors::AppError> {
+        Ok(self
+            .select_likers(&entity_id(resource_key)?, after, limit)
+            .await?)
+    }
+
+    async fn get_liked_posts(
+        &self,
+        user_id: &Ulid,
+        after: Option<LikeCursor>,
+        limit: i64,
+    ) -> Result<Vec<Like>, errors::AppError> {
+        Ok(self.select_liked_posts(user_id, after, limit).await?)
+    }
+
+    async fn view(&self, resource_key: &str, user_id: &Ulid) -> Result<bool, errors::AppError> {
+        self.warm(&[resource_key.to_string()]).await?;
 
+        let counted = self
+            .cache_service
+            .pfadd(&views_key(resource_key), &user_id.to_string())
+            .await?;
+
+        if counted {
+            self.cache_service
+                .sadd(VIEWS_DIRTY_KEY, resource_key)
+                .await?;
+        }
+
+        let now = Utc::now().naive_utc();
+        let buckets: Vec<(String, i64)> = Granularity::ALL
+            .iter()
+            .map(|granularity| {
+                let start = granularity.bucket_start(now);
+                let expire_at = start + granularity.step() + granularity.retention();
+                (
+                    view_bucket_key(resource_key, *granularity, start),
+                    expire_at.and_utc().timestamp(),
+                )
+            })
+            .collect();
+        self.cache_service
+            .pfadd_expire_at(&buckets, &user_id.to_string())
+            .await?;
+
+        Ok(counted)
+    }
+
+    async fn get_views(&self, resource_key: &str) -> Result<u64, errors::AppError> {
+        self.warm(&[resource_key.to_string()]).await?;
+
+        Ok(self.cache_service.pfcount(&views_key(resource_key)).await?)
+    }
+
+    async fn get_many_views(
+        &self,
+        resource_keys: &[String],
+    ) -> Result<HashMap<String, u64>, errors::AppError> {
+        if resource_keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        self.warm(resource_keys).await?;
+        let cache_keys: Vec<String> = resource_keys.iter().map(|key| views_key(key)).collect();
         let many_views = self.cache_service.pfcount_many(&cache_keys).await?;
+
         let mut response = HashMap::new();
-        
         for (cache_key, views) in many_views {
             if let Some(resource_key) = cache_key
                 .strip_prefix("entity:")
@@ -173,37 +939,190 @@ impl<C: CacheService + 'static> InteractionRepository for RedisInteractionReposi
             {
                 response.insert(resource_key.to_string(), views);
             }
-            
         }
+
         Ok(response)
     }
 
-    async fn delete_interactions(&self, resource_key: &str) -> Result<(), errors::RedisError> {
-        let cache_key_views = format!("entity:{}:views", resource_key);
-        let cache_key_likes = format!("entity:{}:likes", resource_key);
+    async fn get_analytics(
+        &self,
+        resource_key: &str,
+        granularity: Granularity,
+        buckets: &[NaiveDateTime],
+    ) -> Result<Vec<AnalyticsBucket>, errors::AppError> {
+        let (Some(first), Some(last)) = (buckets.first(), buckets.last()) else {
+            return Ok(Vec::new());
+        };
 
-        self.cache_service
-            .del(&[cache_key_views, cache_key_likes])
+        let id = entity_id(resource_key)?;
+        let cache_keys: Vec<String> = buckets
+            .iter()
+            .map(|start| view_bucket_key(resource_key, granularity, *start))
+            .collect();
+        let views = self.cache_service.pfcount_many(&cache_keys).await?;
+        let likes = self
+            .select_like_buckets(&id, granularity, *first, *last + granularity.step())
+            .await?;
+
+        Ok(buckets
+            .iter()
+            .zip(&cache_keys)
+            .map(|(start, cache_key)| AnalyticsBucket {
+                start: *start,
+                views: views.get(cache_key).copied().unwrap_or(0),
+                likes: likes.get(start).copied().unwrap_or(0) as u64,
+            })
+            .collect())
+    }
+
+    async fn delete_interactions(&self, resource_key: &str) -> Result<(), errors::AppError> {
+        self.delete_many_interactions(&[resource_key.to_string()])
             .await
     }
 
     async fn delete_many_interactions(
         &self,
         resource_keys: &[String],
-    ) -> Result<(), errors::RedisError> {
-        if resource_keys.is_empty() { 
+    ) -> Result<(), errors::AppError> {
+        if resource_keys.is_empty() {
             return Ok(());
         }
-        
-        let mut cache_keys = Vec::new();
+
+        let ids = resource_keys
+            .iter()
+            .map(|key| entity_id(key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.delete_entities(&ids).await?;
+
+        let mut cache_keys = Vec::with_capacity(resource_keys.len() * (self.kinds.len() + 2));
         for resource_key in resource_keys {
-            let cache_key_views = format!("entity:{}:views", resource_key);
-            let cache_key_likes = format!("entity:{}:likes", resource_key);
+            for kind in &self.kinds {
+                cache_keys.push(reaction_key(resource_key, kind));
+            }
+            cache_keys.push(views_key(resource_key));
+            cache_keys.push(cached_key(resource_key));
+        }
+
+        Ok(self.cache_service.del(&cache_keys).await?)
+    }
+
+    async fn delete_user_reactions(&self, user_id: &Ulid) -> Result<(), errors::AppError> {
+        let deleted = self.delete_reactions_of(user_id).await?;
+
+        let member = user_id.to_string();
+        for (id, kind) in deleted {
+            let resource_key = ulid_from_bytes(id)?.to_string();
+            self.cache_service
+                .srem(&reaction_key(&resource_key, &kind), &member)
+                .await?;
+        }
 
-            cache_keys.push(cache_key_likes);
-            cache_keys.push(cache_key_views);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::reply_repo::{PostgresReplyRepository, ReplyRepository};
+    use crate::test_support::{interaction_repo, seed_post};
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    async fn enqueued(pool: &PgPool, routing_key: &str) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM outbox WHERE routing_key = $1")
+            .bind(routing_key)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn assert_not_found(result: Result<bool, errors::AppError>) {
+        match result {
+            Err(e) => assert_eq!(e.into_response().status(), StatusCode::NOT_FOUND),
+            Ok(changed) => panic!("expected 404, got {changed}"),
         }
+    }
+
+    #[sqlx::test]
+    async fn like_and_unlike_of_a_missing_post_are_not_found(pool: PgPool) {
+        let repo = interaction_repo(&pool);
+        let user_id = Ulid::new();
+        let post_id = Ulid::new().to_string();
+
+        assert_not_found(repo.like(&post_id, &user_id).await);
+        assert_not_found(repo.unlike(&post_id, &user_id).await);
+        assert_eq!(enqueued(&pool, "post.liked").await, 0);
+    }
+
+    #[sqlx::test]
+    async fn react_to_a_missing_entity_is_not_found(pool: PgPool) {
+        let repo = interaction_repo(&pool);
+
+        assert_not_found(
+            repo.react(&Ulid::new().to_string(), &Ulid::new(), LIKE_REACTION)
+                .await,
+        );
+    }
+
+    /// `like` answers 201 when it stores the like and 200 when the post was already liked, and
+    /// `unlike` answers 204 and then 200. Only the changes publish an event.
+    #[sqlx::test]
+    async fn like_and_unlike_are_idempotent(pool: PgPool) {
+        let repo = interaction_repo(&pool);
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await.to_string();
+
+        assert!(repo.like(&post_id, &user_id).await.unwrap());
+        assert!(!repo.like(&post_id, &user_id).await.unwrap());
+        assert_eq!(enqueued(&pool, "post.liked").await, 1);
+
+        assert!(repo.unlike(&post_id, &user_id).await.unwrap());
+        assert!(!repo.unlike(&post_id, &user_id).await.unwrap());
+        assert_eq!(enqueued(&pool, "post.unliked").await, 1);
+    }
+
+    #[sqlx::test]
+    async fn replacing_a_like_publishes_it_as_taken_back(pool: PgPool) {
+        let repo = interaction_repo(&pool);
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await.to_string();
+
+        assert!(repo.react(&post_id, &user_id, LIKE_REACTION).await.unwrap());
+        assert!(!repo.react(&post_id, &user_id, LIKE_REACTION).await.unwrap());
+        assert!(repo.react(&post_id, &user_id, "love").await.unwrap());
+        assert_eq!(enqueued(&pool, "post.liked").await, 1);
+        assert_eq!(enqueued(&pool, "post.unliked").await, 1);
+
+        assert!(repo.unreact(&post_id, &user_id).await.unwrap());
+        assert!(!repo.unreact(&post_id, &user_id).await.unwrap());
+        assert_eq!(enqueued(&pool, "post.unliked").await, 1);
+    }
+
+    #[sqlx::test]
+    async fn like_of_a_reply_publishes_reply_liked(pool: PgPool) {
+        let repo = interaction_repo(&pool);
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+        let reply = PostgresReplyRepository::new(pool.clone())
+            .create(post_id, post_id, "reply", user_id)
+            .await
+            .unwrap();
+        let reply_id = reply.id.to_string();
+
+        assert!(repo
+            .react(&reply_id, &user_id, LIKE_REACTION)
+            .await
+            .unwrap());
+        assert!(!repo
+            .react(&reply_id, &user_id, LIKE_REACTION)
+            .await
+            .unwrap());
+        assert!(repo.unreact(&reply_id, &user_id).await.unwrap());
 
-        self.cache_service.del(&cache_keys).await
+        assert_eq!(enqueued(&pool, "reply.liked").await, 1);
+        assert_eq!(enqueued(&pool, "post.liked").await, 0);
+        assert_eq!(enqueued(&pool, "post.unliked").await, 0);
     }
 }
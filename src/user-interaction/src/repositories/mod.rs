@@ -2,11 +2,15 @@
 use sqlx::postgres::PgPoolOptions;
 use crate::errors;
 use crate::settings::Database;
+use ulid::Ulid;
 
 pub mod reply_repo;
 pub mod interaction_repo;
 pub mod posts_repo;
 pub mod users_repo;
+pub mod outbox_repo;
+pub mod processed_messages_repo;
+pub mod bookmarks_repo;
 
 pub async fn init_db(config: &Database) -> Result<PgPool, errors::DatabaseError> {
     let pool = PgPoolOptions::new()
@@ -17,4 +21,14 @@ pub async fn init_db(config: &Database) -> Result<PgPool, errors::DatabaseError>
 
     migrate!().run(&pool).await?;
     Ok(pool)
+}
+
+/// Reads a ULID stored as `BYTEA`, failing instead of panicking when the column does not hold
+/// exactly 16 bytes.
+pub fn ulid_from_bytes(bytes: Vec<u8>) -> Result<Ulid, errors::DatabaseError> {
+    let bytes: [u8; 16] = bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| errors::DatabaseError::InvalidId(bytes.len()))?;
+
+    Ok(Ulid::from_bytes(bytes))
 }
\ No newline at end of file
@@ -0,0 +1,228 @@
+use crate::errors;
+use crate::models::outbox::OutboxMessage;
+use crate::repositories::ulid_from_bytes;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use std::time::Duration;
+use tracing::error;
+use ulid::Ulid;
+use zylo_common::events::{Event, EventEnvelope};
+use zylo_common::outbox::OutboxStore;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct OutboxRow {
+    pub id: Vec<u8>,
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: Value,
+    pub attempts: i32,
+}
+
+impl TryFrom<OutboxRow> for OutboxMessage {
+    type Error = errors::DatabaseError;
+
+    fn try_from(row: OutboxRow) -> Result<Self, Self::Error> {
+        Ok(OutboxMessage {
+            id: ulid_from_bytes(row.id)?,
+            exchange: row.exchange,
+            routing_key: row.routing_key,
+            payload: row.payload,
+            attempts: row.attempts.max(0) as u32,
+        })
+    }
+}
+
+/// Stores an event in the outbox using the caller's connection, so it commits or rolls
+/// back together with the change it describes.
+pub async fn enqueue_event<T: Event + Serialize + Send>(
+    conn: &mut PgConnection,
+    exchange: &str,
+    routing_key: &str,
+    event: T,
+) -> Result<(), errors::DatabaseError> {
+    let envelope = EventEnvelope::new(event);
+
+    sqlx::query(
+        r#"
+        INSERT INTO outbox (id, exchange, routing_key, payload)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(envelope.id.to_bytes())
+    .bind(exchange)
+    .bind(routing_key)
+    .bind(Json(&envelope))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub struct PostgresOutboxRepository {
+    pool: PgPool,
+}
+
+impl PostgresOutboxRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Keeps a row that can not be relayed out of every later batch, leaving it in the table
+    /// with the reason so it can be inspected.
+    async fn park(&self, id: &[u8], error: &str) -> Result<(), errors::DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE outbox
+            SET available_at = 'infinity', last_error = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutboxStore for PostgresOutboxRepository {
+    type Message = OutboxMessage;
+    type Error = errors::DatabaseError;
+
+    async fn claim_batch(
+        &self,
+        limit: usize,
+        lock_for: Duration,
+    ) -> Result<Vec<OutboxMessage>, errors::DatabaseError> {
+        let rows: Vec<OutboxRow> = sqlx::query_as(
+            r#"
+            UPDATE outbox
+            SET available_at = NOW() + make_interval(secs => $2),
+                attempts = attempts + 1
+            WHERE id IN (
+                SELECT id
+                FROM outbox
+                WHERE sent_at IS NULL
+                  AND available_at <= NOW()
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, exchange, routing_key, payload, attempts
+            "#,
+        )
+        .bind(limit as i64)
+        .bind(lock_for.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut messages = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id = row.id.clone();
+            match OutboxMessage::try_from(row) {
+                Ok(message) => messages.push(message),
+                Err(e) => {
+                    error!("Parking outbox message {:?}: {}", id, e);
+                    self.park(&id, &e.to_string()).await?;
+                }
+            }
+        }
+
+        messages.sort_by_key(|message| message.id);
+        Ok(messages)
+    }
+
+    async fn mark_sent(&self, id: &Ulid) -> Result<(), errors::DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE outbox
+            SET sent_at = NOW(), last_error = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(id.to_bytes())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: &Ulid,
+        error: &str,
+        retry_in: Duration,
+    ) -> Result<(), errors::DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE outbox
+            SET available_at = NOW() + make_interval(secs => $2), last_error = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(id.to_bytes())
+        .bind(retry_in.as_secs_f64())
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_sent_before(&self, older_than: Duration) -> Result<u64, errors::DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM outbox
+            WHERE sent_at IS NOT NULL
+              AND sent_at < NOW() - make_interval(secs => $1)
+            "#,
+        )
+        .bind(older_than.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn claim_batch_parks_rows_with_an_invalid_id(pool: PgPool) {
+        sqlx::query(
+            r#"
+            INSERT INTO outbox (id, exchange, routing_key, payload)
+            VALUES ($1, 'post_exchange', 'reply.deleted', '{}'), ($2, 'post_exchange', 'reply.deleted', '{}')
+            "#,
+        )
+        .bind(vec![1u8, 2, 3])
+        .bind(Ulid::new().to_bytes())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let repo = PostgresOutboxRepository::new(pool.clone());
+        let batch = repo.claim_batch(10, Duration::from_secs(30)).await.unwrap();
+        assert_eq!(batch.len(), 1);
+
+        let (last_error, parked): (Option<String>, bool) = sqlx::query_as(
+            "SELECT last_error, available_at = 'infinity' FROM outbox WHERE id = $1",
+        )
+        .bind(vec![1u8, 2, 3])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(parked);
+        assert_eq!(
+            last_error.as_deref(),
+            Some("Stored id has 3 bytes instead of 16")
+        );
+    }
+}
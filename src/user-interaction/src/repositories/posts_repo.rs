@@ -1,4 +1,5 @@
 ﻿use crate::errors;
+use crate::repositories::ulid_from_bytes;
 use async_trait::async_trait;
 use sqlx::{PgPool};
 use ulid::Ulid;
@@ -8,6 +9,8 @@ use ulid::Ulid;
 pub trait PostsRepository: Send + Sync {
     async fn create(&self, post_id: &Ulid, user_id: &Ulid) -> Result<(), errors::DatabaseError>;
     async fn delete(&self, post_id: &Ulid) -> Result<(),errors::DatabaseError>;
+    /// The user who created the post, if the post exists.
+    async fn get_author(&self, post_id: &Ulid) -> Result<Option<Ulid>, errors::DatabaseError>;
 }
 
 pub struct PostgresPostsRepository {
@@ -27,6 +30,7 @@ impl PostsRepository for PostgresPostsRepository {
             r#"
             INSERT INTO posts (id, user_id)
             VALUES ($1, $2)
+            ON CONFLICT (id) DO NOTHING
             "#,
         )
         .bind(post_id.to_bytes())
@@ -56,4 +60,19 @@ impl PostsRepository for PostgresPostsRepository {
       
         Ok(())
     }
+
+    async fn get_author(&self, post_id: &Ulid) -> Result<Option<Ulid>, errors::DatabaseError> {
+        let user_id: Option<Vec<u8>> = sqlx::query_scalar(
+            r#"
+            SELECT user_id
+            FROM posts
+            WHERE id = $1
+            "#,
+        )
+        .bind(post_id.to_bytes())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        user_id.map(ulid_from_bytes).transpose()
+    }
 }
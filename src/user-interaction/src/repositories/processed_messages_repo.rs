@@ -0,0 +1,109 @@
+use crate::errors;
+use crate::utils::constants::PROCESSED_MESSAGES_TTL_SECONDS;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Ledger of message ids each consumer has already handled, used to skip redeliveries.
+#[async_trait]
+pub trait ProcessedMessagesRepository: Send + Sync {
+    async fn is_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<bool, errors::DatabaseError>;
+    async fn mark_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<(), errors::DatabaseError>;
+}
+
+pub struct PostgresProcessedMessagesRepository {
+    pool: PgPool,
+}
+
+impl PostgresProcessedMessagesRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Periodically removes ledger entries older than the TTL, as Postgres does not expire rows.
+    pub fn spawn_cleanup(self: &Arc<Self>) {
+        let repo = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+            loop {
+                interval.tick().await;
+                match repo.delete_expired().await {
+                    Ok(deleted) if deleted > 0 => {
+                        info!("Removed {} expired processed message ids", deleted)
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Failed to remove expired processed message ids: {:?}", e),
+                }
+            }
+        });
+    }
+
+    async fn delete_expired(&self) -> Result<u64, errors::DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM processed_messages
+            WHERE processed_at < NOW() - make_interval(secs => $1)
+            "#,
+        )
+        .bind(PROCESSED_MESSAGES_TTL_SECONDS as f64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl ProcessedMessagesRepository for PostgresProcessedMessagesRepository {
+    async fn is_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<bool, errors::DatabaseError> {
+        let processed: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM processed_messages
+                WHERE consumer = $1 AND message_id = $2
+            )
+            "#,
+        )
+        .bind(consumer)
+        .bind(message_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(processed)
+    }
+
+    async fn mark_processed(
+        &self,
+        consumer: &str,
+        message_id: &str,
+    ) -> Result<(), errors::DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO processed_messages (consumer, message_id)
+            VALUES ($1, $2)
+            ON CONFLICT (consumer, message_id) DO NOTHING
+            "#,
+        )
+        .bind(consumer)
+        .bind(message_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
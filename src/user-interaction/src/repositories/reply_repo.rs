@@ -1,27 +1,55 @@
 ﻿use crate::errors;
-use crate::models::reply::Reply;
+use crate::models::amq_message::{
+    AuditEventMessage, ReplyCreatedMessage, ReplyDeletedMessage, ReplyUpdatedMessage,
+};
+use crate::models::reply::{Reply, ReplyActor, ReplyCursor, ReplyResponse, ThreadReply};
 use crate::models::Finalizer;
+use crate::repositories::outbox_repo::enqueue_event;
+use crate::repositories::ulid_from_bytes;
+use crate::utils::constants::{AUDIT_EXCHANGE_NAME, POST_EXCHANGE_NAME};
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
-use sqlx::{PgPool};
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashMap;
 use tracing::info;
 use ulid::Ulid;
 
 #[async_trait]
 pub trait ReplyRepository: Send + Sync + Finalizer {
-    async fn get_all_from_post(&self, post_id: &Ulid) -> Result<Vec<Reply>, errors::DatabaseError>;
+    /// Direct replies to `parent_id` in creation order, starting after `after`. Top level
+    /// replies are the ones whose parent is the post itself.
+    async fn get_children_page(
+        &self,
+        post_id: &Ulid,
+        parent_id: &Ulid,
+        newest_first: bool,
+        after: Option<ReplyCursor>,
+        limit: i64,
+    ) -> Result<Vec<Reply>, errors::DatabaseError>;
+
+    /// Positions of the direct replies to `parent_id` after `after`, the most liked first.
+    async fn get_most_liked_children(
+        &self,
+        post_id: &Ulid,
+        parent_id: &Ulid,
+        after: Option<ReplyCursor>,
+        limit: i64,
+    ) -> Result<Vec<ReplyCursor>, errors::DatabaseError>;
+
+    /// Loads `root_ids` and their replies down to `max_depth` levels, the roots being level 1,
+    /// keeping at most `max_children` of the oldest direct replies under each reply.
+    async fn get_threads(
+        &self,
+        root_ids: &[Ulid],
+        max_depth: u32,
+        max_children: u32,
+    ) -> Result<Vec<ThreadReply>, errors::DatabaseError>;
 
     async fn get_all_from_posts(
         &self,
         post_ids: &[Ulid],
     ) -> Result<HashMap<Ulid, Vec<Reply>>, errors::DatabaseError>;
 
-    async fn get_with_nested_by_path_prefix(
-        &self,
-        prefix: &str,
-    ) -> Result<Vec<Reply>, errors::DatabaseError>;
-
     async fn get_with_nested(&self, id: &Ulid) -> Result<Vec<Reply>, errors::DatabaseError>;
 
     async fn create(
@@ -32,9 +60,20 @@ pub trait ReplyRepository: Send + Sync + Finalizer {
         user_id: Ulid,
     ) -> Result<Reply, errors::DatabaseError>;
 
-    async fn update(&self, id: &Ulid, content: &str) -> Result<Reply, errors::DatabaseError>;
-    async fn delete(&self, id: &Ulid) -> Result<(), errors::DatabaseError>;
-    async fn delete_all_by_user_id(&self, id: &Ulid) -> Result<Vec<String>, errors::DatabaseError>;
+    /// Fails with `Forbidden` without changing anything unless `actor` wrote the reply or is a
+    /// moderator.
+    async fn update(
+        &self,
+        id: &Ulid,
+        content: &str,
+        actor: &ReplyActor,
+    ) -> Result<Reply, errors::DatabaseError>;
+    /// Removes a reply without replies of its own. A reply that has replies is turned into a
+    /// tombstone instead, with its author and content cleared, so its thread stays in place.
+    async fn delete(&self, id: &Ulid, actor: &ReplyActor) -> Result<(), errors::DatabaseError>;
+
+    /// Removes up to `limit` tombstones that no longer have any replies under them.
+    async fn compact_tombstones(&self, limit: i64) -> Result<u64, errors::DatabaseError>;
 }
 
 pub struct PostgresReplyRepository {
@@ -45,22 +84,46 @@ pub struct PostgresReplyRepository {
 struct ReplyRow {
     pub id: Vec<u8>,
     pub post_id: Vec<u8>,
-    pub user_id: Vec<u8>,
+    pub user_id: Option<Vec<u8>>,
     pub reply_to_id: Vec<u8>,
     pub content: String,
     pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ThreadRow {
+    #[sqlx(flatten)]
+    reply: ReplyRow,
+    child_count: i64,
 }
 
-impl From<ReplyRow> for Reply {
-    fn from(row: ReplyRow) -> Self {
-        Reply {
-            id: Ulid::from_bytes(row.id.try_into().unwrap()),
-            post_id: Ulid::from_bytes(row.post_id.try_into().unwrap()),
-            reply_to_id: Ulid::from_bytes(row.reply_to_id.try_into().unwrap()),
-            user_id: Ulid::from_bytes(row.user_id.try_into().unwrap()),
+impl TryFrom<ThreadRow> for ThreadReply {
+    type Error = errors::DatabaseError;
+
+    fn try_from(row: ThreadRow) -> Result<Self, Self::Error> {
+        Ok(ThreadReply {
+            reply: Reply::try_from(row.reply)?,
+            child_count: row.child_count as u64,
+        })
+    }
+}
+
+impl TryFrom<ReplyRow> for Reply {
+    type Error = errors::DatabaseError;
+
+    fn try_from(row: ReplyRow) -> Result<Self, Self::Error> {
+        Ok(Reply {
+            id: ulid_from_bytes(row.id)?,
+            post_id: ulid_from_bytes(row.post_id)?,
+            reply_to_id: ulid_from_bytes(row.reply_to_id)?,
+            user_id: row.user_id.map(ulid_from_bytes).transpose()?,
             content: row.content,
             created_at: row.created_at,
-        }
+            deleted_at: row.deleted_at,
+            path: row.path,
+        })
     }
 }
 
@@ -68,12 +131,134 @@ impl PostgresReplyRepository {
     pub fn new(pool: PgPool) -> PostgresReplyRepository {
         Self { pool }
     }
+
+    /// Explains why a reply could not be attached: either the post is missing or the parent is
+    /// neither the post nor one of its replies.
+    async fn parent_error(conn: &mut PgConnection, post_id: &Ulid) -> errors::DatabaseError {
+        let post_exists: Result<bool, sqlx::Error> =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM posts WHERE id = $1)")
+                .bind(post_id.to_bytes())
+                .fetch_one(conn)
+                .await;
+
+        match post_exists {
+            Ok(true) => errors::ValidationError::InvalidReplyToId.into(),
+            Ok(false) => errors::ValidationError::PostNotFound.into(),
+            Err(err) => err.into(),
+        }
+    }
+
+    /// Deletes the replies of a user the way `delete` does, within the transaction removing the
+    /// user: replies with replies under them become tombstones and the others are removed.
+    /// Returns the ids of every reply that was deleted.
+    pub async fn delete_all_by_user_id(
+        conn: &mut PgConnection,
+        user_id: &Ulid,
+    ) -> Result<Vec<Ulid>, errors::DatabaseError> {
+        // Locked like in `delete`, so no reply gets attached to one that is about to be removed.
+        sqlx::query("SELECT id FROM replies WHERE user_id = $1 FOR UPDATE")
+            .bind(user_id.to_bytes())
+            .execute(&mut *conn)
+            .await?;
+
+        let mut deleted: Vec<Vec<u8>> = sqlx::query_scalar(
+            r#"
+            UPDATE replies r
+            SET user_id = NULL, content = '', deleted_at = NOW()
+            WHERE r.user_id = $1
+              AND EXISTS (SELECT 1 FROM replies c WHERE c.reply_to_id = r.id)
+            RETURNING r.id
+            "#,
+        )
+        .bind(user_id.to_bytes())
+        .fetch_all(&mut *conn)
+        .await?;
+
+        deleted.extend(
+            sqlx::query_scalar::<_, Vec<u8>>("DELETE FROM replies WHERE user_id = $1 RETURNING id")
+                .bind(user_id.to_bytes())
+                .fetch_all(&mut *conn)
+                .await?,
+        );
+
+        let mut ids = Vec::with_capacity(deleted.len());
+        for id in deleted {
+            let id = ulid_from_bytes(id)?;
+            enqueue_event(
+                conn,
+                POST_EXCHANGE_NAME,
+                "reply.deleted",
+                ReplyDeletedMessage::from(id),
+            )
+            .await?;
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Adds a moderator's change to the reply of another user to the audit trail, in the
+    /// transaction making the change. Authors changing their own replies are not audited.
+    async fn audit_moderation(
+        conn: &mut PgConnection,
+        actor: &ReplyActor,
+        action: &str,
+        id: &Ulid,
+        owner_id: Ulid,
+    ) -> Result<(), errors::DatabaseError> {
+        if !actor.is_moderator || owner_id == actor.user_id {
+            return Ok(());
+        }
+
+        enqueue_event(
+            conn,
+            AUDIT_EXCHANGE_NAME,
+            "reply.moderated",
+            AuditEventMessage::new(actor, action, "reply", *id, owner_id),
+        )
+        .await
+    }
+
+    /// Tells apart a missing reply from one the actor is not allowed to change, after a write
+    /// guarded by ownership matched no rows.
+    async fn ownership_error(conn: &mut PgConnection, id: &Ulid) -> errors::DatabaseError {
+        let exists: Result<bool, sqlx::Error> = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM replies WHERE id = $1 AND deleted_at IS NULL)",
+        )
+        .bind(id.to_bytes())
+        .fetch_one(conn)
+        .await;
+
+        match exists {
+            Ok(true) => errors::DatabaseError::Forbidden(String::from(
+                "Only the author of a reply can change it",
+            )),
+            Ok(false) => errors::DatabaseError::NotFound(String::from(
+                "Reply with given id has not been found",
+            )),
+            Err(err) => err.into(),
+        }
+    }
 }
 
 #[async_trait]
 impl ReplyRepository for PostgresReplyRepository {
-    async fn get_all_from_post(&self, post_id: &Ulid) -> Result<Vec<Reply>, errors::DatabaseError> {
-        let rows: Vec<ReplyRow> = sqlx::query_as(
+    async fn get_children_page(
+        &self,
+        post_id: &Ulid,
+        parent_id: &Ulid,
+        newest_first: bool,
+        after: Option<ReplyCursor>,
+        limit: i64,
+    ) -> Result<Vec<Reply>, errors::DatabaseError> {
+        let (comparison, direction) = if newest_first {
+            ("<", "DESC")
+        } else {
+            (">", "ASC")
+        };
+        let after_created_at = after.map(|cursor| cursor.created_at()).transpose()?;
+
+        let rows: Vec<ReplyRow> = sqlx::query_as(&format!(
             r#"
             SELECT
               id,
@@ -82,18 +267,115 @@ impl ReplyRepository for PostgresReplyRepository {
               user_id,
               content,
               created_at,
+              deleted_at,
               path
             FROM replies
-            WHERE post_id = $1
-            ORDER BY created_at ASC
+            WHERE reply_to_id = $2
+              AND post_id = $1
+              AND ($3::TIMESTAMP IS NULL OR (created_at, id) {comparison} ($3, $4::BYTEA))
+            ORDER BY created_at {direction}, id {direction}
+            LIMIT $5
+            "#,
+        ))
+        .bind(post_id.to_bytes())
+        .bind(parent_id.to_bytes())
+        .bind(after_created_at)
+        .bind(after.map(|cursor| cursor.id.to_bytes()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Reply::try_from).collect()
+    }
+
+    async fn get_most_liked_children(
+        &self,
+        post_id: &Ulid,
+        parent_id: &Ulid,
+        after: Option<ReplyCursor>,
+        limit: i64,
+    ) -> Result<Vec<ReplyCursor>, errors::DatabaseError> {
+        let positions = sqlx::query_as(
+            r#"
+            SELECT id, like_count
+            FROM replies
+            WHERE reply_to_id = $2
+              AND post_id = $1
+              AND ($3::BIGINT IS NULL OR (like_count, id) < ($3, $4::BYTEA))
+            ORDER BY like_count DESC, id DESC
+            LIMIT $5
             "#,
         )
         .bind(post_id.to_bytes())
+        .bind(parent_id.to_bytes())
+        .bind(after.map(|cursor| cursor.key))
+        .bind(after.map(|cursor| cursor.id.to_bytes()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|(id, like_count): ([u8; 16], i64)| ReplyCursor {
+            key: like_count,
+            id: Ulid::from_bytes(id),
+        })
+        .collect();
+
+        Ok(positions)
+    }
+
+    async fn get_threads(
+        &self,
+        root_ids: &[Ulid],
+        max_depth: u32,
+        max_children: u32,
+    ) -> Result<Vec<ThreadReply>, errors::DatabaseError> {
+        if root_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let root_id_bytes: Vec<Vec<u8>> =
+            root_ids.iter().map(|id| id.to_bytes().to_vec()).collect();
+
+        let rows: Vec<ThreadRow> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE thread AS (
+                SELECT r.id, r.post_id, r.reply_to_id, r.user_id, r.content, r.created_at,
+                       r.deleted_at, r.path, 1 AS depth
+                FROM replies r
+                WHERE r.id = ANY($1)
+                UNION ALL
+                SELECT c.id, c.post_id, c.reply_to_id, c.user_id, c.content, c.created_at,
+                       c.deleted_at, c.path, t.depth + 1
+                FROM thread t
+                CROSS JOIN LATERAL (
+                    SELECT *
+                    FROM replies
+                    WHERE reply_to_id = t.id
+                    ORDER BY created_at, id
+                    LIMIT $3
+                ) c
+                WHERE t.depth < $2
+            )
+            SELECT
+              t.id,
+              t.post_id,
+              t.reply_to_id,
+              t.user_id,
+              t.content,
+              t.created_at,
+              t.deleted_at,
+              t.path,
+              (SELECT COUNT(*) FROM replies x WHERE x.reply_to_id = t.id) AS child_count
+            FROM thread t
+            "#,
+        )
+        .bind(&root_id_bytes)
+        .bind(max_depth as i32)
+        .bind(i64::from(max_children))
         .fetch_all(&self.pool)
         .await?;
 
-        let replies: Vec<Reply> = rows.into_iter().map(Reply::from).collect();
-        Ok(replies)
+        rows.into_iter().map(ThreadReply::try_from).collect()
     }
 
     async fn get_all_from_posts(
@@ -116,17 +398,21 @@ impl ReplyRepository for PostgresReplyRepository {
               user_id,
               content,
               created_at,
+              deleted_at,
               path
             FROM replies
             WHERE post_id = ANY($1)
-            ORDER BY created_at
+            ORDER BY created_at, id
             "#,
         )
         .bind(&post_id_bytes)
         .fetch_all(&self.pool)
         .await?;
 
-        let all_replies: Vec<Reply> = rows.into_iter().map(Reply::from).collect();
+        let all_replies = rows
+            .into_iter()
+            .map(Reply::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
         let mut grouped_map: HashMap<Ulid, Vec<Reply>> = HashMap::new();
         for reply in all_replies {
             grouped_map.entry(reply.post_id).or_default().push(reply);
@@ -135,48 +421,22 @@ impl ReplyRepository for PostgresReplyRepository {
         Ok(grouped_map)
     }
 
-    async fn get_with_nested_by_path_prefix(
-        &self,
-        prefix: &str,
-    ) -> Result<Vec<Reply>, errors::DatabaseError> {
-        let rows: Vec<ReplyRow> = sqlx::query_as(
-            r#"
-            SELECT
-              id,
-              post_id,
-              reply_to_id,
-              user_id,
-              content,
-              created_at,
-              path
-            FROM replies
-            WHERE path LIKE $1 || '%'
-            ORDER BY created_at
-            "#,
-        )
-        .bind(prefix)
-        .fetch_all(&self.pool)
-        .await?;
-
-        Ok(rows.into_iter().map(Reply::from).collect())
-    }
-
     async fn get_with_nested(&self, id: &Ulid) -> Result<Vec<Reply>, errors::DatabaseError> {
         let rows: Vec<ReplyRow> = sqlx::query_as(
             r#"
-            SELECT *
+            SELECT r1.*
             FROM replies AS r
             JOIN replies AS r1
               ON r1.path LIKE r.path || '%'
             WHERE r.id = $1
-            ORDER BY r1.created_at
+            ORDER BY r1.created_at, r1.id
         "#,
         )
         .bind(id.to_bytes())
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(Reply::from).collect())
+        rows.into_iter().map(Reply::try_from).collect()
     }
 
     async fn create(
@@ -187,14 +447,21 @@ impl ReplyRepository for PostgresReplyRepository {
         user_id: Ulid,
     ) -> Result<Reply, errors::DatabaseError> {
         let reply_id = Ulid::new();
-        let reply_row: ReplyRow = sqlx::query_as(
+        let mut tx = self.pool.begin().await?;
+        let reply_row: Option<ReplyRow> = sqlx::query_as(
             r#"
                 WITH parent_path AS (
-                    SELECT 
-                        CASE WHEN $2 = $1 
-                            THEN CONCAT('/', encode($1, 'hex'), '/')
-                            ELSE (SELECT path FROM replies WHERE id = $2)
-                        END AS path
+                    SELECT CONCAT('/', encode(p.id, 'hex'), '/') AS path
+                    FROM posts p
+                    WHERE p.id = $1 AND $2 = $1
+                    UNION ALL
+                    SELECT parent.path
+                    FROM (
+                        SELECT r.path
+                        FROM replies r
+                        WHERE r.id = $2 AND r.post_id = $1 AND r.deleted_at IS NULL
+                        FOR SHARE
+                    ) AS parent
                 )
                 INSERT INTO replies (id, post_id, user_id, reply_to_id, content, path)
                 SELECT 
@@ -209,68 +476,147 @@ impl ReplyRepository for PostgresReplyRepository {
         .bind(reply_id.to_bytes())
         .bind(user_id.to_bytes())
         .bind(content)
-        .fetch_one(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        Ok(Reply::from(reply_row))
+        let Some(reply_row) = reply_row else {
+            return Err(Self::parent_error(&mut tx, &post_id).await);
+        };
+
+        let reply = Reply::try_from(reply_row)?;
+        enqueue_event(
+            &mut tx,
+            POST_EXCHANGE_NAME,
+            "reply.created",
+            ReplyCreatedMessage::new(ReplyResponse::from(reply.clone()), user_id),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(reply)
     }
 
-    async fn update(&self, id: &Ulid, content: &str) -> Result<Reply, errors::DatabaseError> {
+    async fn update(
+        &self,
+        id: &Ulid,
+        content: &str,
+        actor: &ReplyActor,
+    ) -> Result<Reply, errors::DatabaseError> {
         let id_bytes = id.to_bytes();
-        let reply_row: ReplyRow = sqlx::query_as(
+        let mut tx = self.pool.begin().await?;
+        let reply_row: Option<ReplyRow> = sqlx::query_as(
             r#"
             UPDATE replies
             SET content = $2
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL AND (user_id = $3 OR $4)
             RETURNING *
             "#,
         )
         .bind(id_bytes)
         .bind(content)
-        .fetch_one(&self.pool)
+        .bind(actor.user_id.to_bytes())
+        .bind(actor.is_moderator)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        Ok(Reply::from(reply_row))
+        let Some(reply_row) = reply_row else {
+            return Err(Self::ownership_error(&mut tx, id).await);
+        };
+
+        let reply = Reply::try_from(reply_row)?;
+        enqueue_event(
+            &mut tx,
+            POST_EXCHANGE_NAME,
+            "reply.updated",
+            ReplyUpdatedMessage::new(*id, content.to_string(), Utc::now().naive_utc()),
+        )
+        .await?;
+        if let Some(owner_id) = reply.user_id {
+            Self::audit_moderation(&mut tx, actor, "reply.updated", id, owner_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(reply)
     }
 
-    async fn delete(&self, id: &Ulid) -> Result<(), errors::DatabaseError> {
-        let result = sqlx::query(
+    async fn delete(&self, id: &Ulid, actor: &ReplyActor) -> Result<(), errors::DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Replies being attached hold a share lock on their parent, so once this lock is taken
+        // they have either committed and are seen below or will no longer find the parent.
+        let locked: Option<Option<Vec<u8>>> = sqlx::query_scalar(
             r#"
-            DELETE FROM replies
-            WHERE id = $1
+            SELECT user_id
+            FROM replies
+            WHERE id = $1 AND deleted_at IS NULL AND (user_id = $2 OR $3)
+            FOR UPDATE
             "#,
         )
         .bind(id.to_bytes())
-        .execute(&self.pool)
+        .bind(actor.user_id.to_bytes())
+        .bind(actor.is_moderator)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        if result.rows_affected() > 0 {
-            return Ok(());
+        let Some(owner_id) = locked else {
+            return Err(Self::ownership_error(&mut tx, id).await);
+        };
+
+        let has_children: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM replies WHERE reply_to_id = $1)")
+                .bind(id.to_bytes())
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let query = if has_children {
+            r#"
+            UPDATE replies
+            SET user_id = NULL, content = '', deleted_at = NOW()
+            WHERE id = $1
+            "#
+        } else {
+            "DELETE FROM replies WHERE id = $1"
+        };
+
+        sqlx::query(query)
+            .bind(id.to_bytes())
+            .execute(&mut *tx)
+            .await?;
+
+        enqueue_event(
+            &mut tx,
+            POST_EXCHANGE_NAME,
+            "reply.deleted",
+            ReplyDeletedMessage::from(*id),
+        )
+        .await?;
+        if let Some(owner_id) = owner_id.map(ulid_from_bytes).transpose()? {
+            Self::audit_moderation(&mut tx, actor, "reply.deleted", id, owner_id).await?;
         }
 
-        Err(errors::DatabaseError::NotFound(String::from(
-            "Reply with given id has not been found",
-        )))
+        tx.commit().await?;
+        Ok(())
     }
 
-    async fn delete_all_by_user_id(&self, id: &Ulid) -> Result<Vec<String>, errors::DatabaseError> {
-        let bytes = id.to_bytes();
-
-        let deleted_replies_ids: Vec<String> = sqlx::query_scalar(
+    async fn compact_tombstones(&self, limit: i64) -> Result<u64, errors::DatabaseError> {
+        let result = sqlx::query(
             r#"
             DELETE FROM replies
-            WHERE user_id = $1
-            RETURNING id
+            WHERE id IN (
+                SELECT t.id
+                FROM replies t
+                WHERE t.deleted_at IS NOT NULL
+                  AND NOT EXISTS (SELECT 1 FROM replies c WHERE c.reply_to_id = t.id)
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
             "#,
         )
-        .bind(bytes)
-        .fetch_all(&self.pool)
-        .await?
-        .into_iter()
-        .map(|id: [u8; 16]| Ulid::from_bytes(id).to_string())
-        .collect();
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
 
-        Ok(deleted_replies_ids)
+        Ok(result.rows_affected())
     }
 }
 
@@ -284,3 +630,204 @@ impl Finalizer for PostgresReplyRepository {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::interaction_repo::InteractionRepository;
+    use crate::repositories::users_repo::{PostgresUsersRepository, UsersRepository};
+    use crate::test_support::{interaction_repo, seed_post};
+    use crate::utils::constants::LIKE_REACTION;
+
+    fn assert_invalid_reply_to_id(result: Result<Reply, errors::DatabaseError>) {
+        assert!(
+            matches!(
+                result,
+                Err(errors::DatabaseError::Validation(
+                    errors::ValidationError::InvalidReplyToId
+                ))
+            ),
+            "{:?}",
+            result
+        );
+    }
+
+    #[sqlx::test]
+    async fn create_nests_the_reply_under_its_parent(pool: PgPool) {
+        let repo = PostgresReplyRepository::new(pool.clone());
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+
+        let parent = repo
+            .create(post_id, post_id, "parent", user_id)
+            .await
+            .unwrap();
+        let child = repo
+            .create(post_id, parent.id, "child", user_id)
+            .await
+            .unwrap();
+
+        assert_eq!(child.reply_to_id, parent.id);
+        assert_eq!(child.path, format!("{}{}/", parent.path, hex_id(&child.id)));
+    }
+
+    #[sqlx::test]
+    async fn create_rejects_a_parent_reply_of_another_post(pool: PgPool) {
+        let repo = PostgresReplyRepository::new(pool.clone());
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+        let other_post_id = seed_post(&pool, &user_id).await;
+
+        let other_reply = repo
+            .create(other_post_id, other_post_id, "elsewhere", user_id)
+            .await
+            .unwrap();
+
+        assert_invalid_reply_to_id(repo.create(post_id, other_reply.id, "cross", user_id).await);
+    }
+
+    #[sqlx::test]
+    async fn create_rejects_another_post_as_parent(pool: PgPool) {
+        let repo = PostgresReplyRepository::new(pool.clone());
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+        let other_post_id = seed_post(&pool, &user_id).await;
+
+        assert_invalid_reply_to_id(repo.create(post_id, other_post_id, "cross", user_id).await);
+    }
+
+    #[sqlx::test]
+    async fn create_rejects_an_unknown_or_deleted_parent(pool: PgPool) {
+        let repo = PostgresReplyRepository::new(pool.clone());
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+
+        assert_invalid_reply_to_id(repo.create(post_id, Ulid::new(), "orphan", user_id).await);
+
+        let parent = repo
+            .create(post_id, post_id, "parent", user_id)
+            .await
+            .unwrap();
+        repo.create(post_id, parent.id, "child", user_id)
+            .await
+            .unwrap();
+        repo.delete(
+            &parent.id,
+            &ReplyActor {
+                user_id,
+                roles: Vec::new(),
+                is_moderator: false,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_invalid_reply_to_id(repo.create(post_id, parent.id, "late", user_id).await);
+    }
+
+    #[sqlx::test]
+    async fn create_on_a_missing_post_is_post_not_found(pool: PgPool) {
+        let repo = PostgresReplyRepository::new(pool.clone());
+        let post_id = Ulid::new();
+
+        let result = repo.create(post_id, post_id, "nowhere", Ulid::new()).await;
+
+        assert!(
+            matches!(
+                result,
+                Err(errors::DatabaseError::Validation(
+                    errors::ValidationError::PostNotFound
+                ))
+            ),
+            "{:?}",
+            result
+        );
+    }
+
+    #[sqlx::test]
+    async fn most_liked_children_follow_the_likes_and_page_by_cursor(pool: PgPool) {
+        let repo = PostgresReplyRepository::new(pool.clone());
+        let interactions = interaction_repo(&pool);
+        let users_repo = PostgresUsersRepository::new(pool.clone());
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+        let (first_liker, second_liker) = (Ulid::new(), Ulid::new());
+        users_repo.create(&first_liker).await.unwrap();
+        users_repo.create(&second_liker).await.unwrap();
+
+        let mut replies = Vec::new();
+        for content in ["none", "one", "two"] {
+            replies.push(
+                repo.create(post_id, post_id, content, user_id)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let (none, one, two) = (&replies[0], &replies[1], &replies[2]);
+        for (reply, liker) in [(one, first_liker), (two, first_liker), (two, second_liker)] {
+            interactions
+                .react(&reply.id.to_string(), &liker, LIKE_REACTION)
+                .await
+                .unwrap();
+        }
+
+        let first_page = repo
+            .get_most_liked_children(&post_id, &post_id, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            first_page,
+            vec![
+                ReplyCursor { key: 2, id: two.id },
+                ReplyCursor { key: 1, id: one.id },
+            ]
+        );
+        let second_page = repo
+            .get_most_liked_children(&post_id, &post_id, first_page.last().copied(), 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            second_page,
+            vec![ReplyCursor {
+                key: 0,
+                id: none.id
+            }]
+        );
+
+        interactions
+            .unreact(&two.id.to_string(), &first_liker)
+            .await
+            .unwrap();
+        interactions
+            .react(&two.id.to_string(), &second_liker, "love")
+            .await
+            .unwrap();
+        let positions = repo
+            .get_most_liked_children(&post_id, &post_id, None, 3)
+            .await
+            .unwrap();
+        let (unliked, last) = if two.id > none.id {
+            (two.id, none.id)
+        } else {
+            (none.id, two.id)
+        };
+        assert_eq!(
+            positions,
+            vec![
+                ReplyCursor { key: 1, id: one.id },
+                ReplyCursor {
+                    key: 0,
+                    id: unliked
+                },
+                ReplyCursor { key: 0, id: last },
+            ]
+        );
+    }
+
+    fn hex_id(id: &Ulid) -> String {
+        id.to_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
@@ -1,14 +1,16 @@
 ﻿use crate::errors;
+use crate::repositories::reply_repo::PostgresReplyRepository;
 use async_trait::async_trait;
 use sqlx::{PgPool, Row};
 use ulid::Ulid;
 
-pub type DeletedPostIds = Vec<String>;
+/// Posts and replies removed together with a user, whose interactions have to go as well.
+pub type DeletedEntityIds = Vec<String>;
 
 #[async_trait]
 pub trait UsersRepository: Send + Sync {
     async fn create(&self, user_id: &Ulid) -> Result<(), errors::DatabaseError>;
-    async fn delete(&self, user_id: &Ulid) -> Result<DeletedPostIds, errors::DatabaseError>;
+    async fn delete(&self, user_id: &Ulid) -> Result<DeletedEntityIds, errors::DatabaseError>;
 }
 
 pub struct PostgresUsersRepository {
@@ -28,6 +30,7 @@ impl UsersRepository for PostgresUsersRepository {
             r#"
             INSERT INTO users (id)
             VALUES ($1)
+            ON CONFLICT (id) DO NOTHING
             "#,
         )
         .bind(user_id.to_bytes())
@@ -37,7 +40,7 @@ impl UsersRepository for PostgresUsersRepository {
         Ok(())
     }
 
-    async fn delete(&self, user_id: &Ulid) -> Result<DeletedPostIds, errors::DatabaseError> {
+    async fn delete(&self, user_id: &Ulid) -> Result<DeletedEntityIds, errors::DatabaseError> {
         let mut transaction = self.pool.begin().await?;
         let rows = sqlx::query(
             r#"
@@ -50,6 +53,11 @@ impl UsersRepository for PostgresUsersRepository {
         .fetch_all(&mut *transaction)
         .await?;
 
+        // Replies are cleared before the user row goes, so that the ones other users replied to
+        // stay in their threads as tombstones instead of being removed by the cascade.
+        let deleted_replies =
+            PostgresReplyRepository::delete_all_by_user_id(&mut transaction, user_id).await?;
+
         let result = sqlx::query(
             r#"
                 DELETE FROM users
@@ -68,14 +76,93 @@ impl UsersRepository for PostgresUsersRepository {
             )));
         }
 
-        let deleted_posts: Vec<String> = rows
+        let mut deleted: Vec<String> = rows
             .into_iter()
             .map(|row| {
                 let id: Vec<u8> = row.get("id");
                 Ulid::from_bytes(id.try_into().unwrap()).to_string()
             })
             .collect();
+        deleted.extend(deleted_replies.iter().map(Ulid::to_string));
+
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::posts_repo::{PostgresPostsRepository, PostsRepository};
+    use crate::repositories::reply_repo::ReplyRepository;
+
+    #[sqlx::test]
+    async fn delete_keeps_replies_of_other_users_in_their_thread(pool: PgPool) {
+        let users_repo = PostgresUsersRepository::new(pool.clone());
+        let posts_repo = PostgresPostsRepository::new(pool.clone());
+        let reply_repo = PostgresReplyRepository::new(pool.clone());
+
+        let (deleted_user, other_user, post_id) = (Ulid::new(), Ulid::new(), Ulid::new());
+        users_repo.create(&deleted_user).await.unwrap();
+        users_repo.create(&other_user).await.unwrap();
+        posts_repo.create(&post_id, &other_user).await.unwrap();
+
+        let answered = reply_repo
+            .create(post_id, post_id, "answered", deleted_user)
+            .await
+            .unwrap();
+        let answer = reply_repo
+            .create(post_id, answered.id, "answer", other_user)
+            .await
+            .unwrap();
+        let leaf = reply_repo
+            .create(post_id, post_id, "leaf", deleted_user)
+            .await
+            .unwrap();
+
+        let mut deleted = users_repo.delete(&deleted_user).await.unwrap();
+        deleted.sort();
+        let mut expected = vec![answered.id.to_string(), leaf.id.to_string()];
+        expected.sort();
+        assert_eq!(deleted, expected);
+
+        let replies = reply_repo.get_with_nested(&answered.id).await.unwrap();
+        let tombstone = replies.iter().find(|r| r.id == answered.id).unwrap();
+        assert_eq!(tombstone.user_id, None);
+        assert_eq!(tombstone.content, "");
+        assert!(tombstone.deleted_at.is_some());
+
+        let kept = replies.iter().find(|r| r.id == answer.id).unwrap();
+        assert_eq!(kept.user_id, Some(other_user));
+        assert_eq!(kept.reply_to_id, answered.id);
+
+        let leaf_thread = reply_repo.get_with_nested(&leaf.id).await.unwrap();
+        assert!(leaf_thread.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn delete_removes_the_reactions_of_the_user(pool: PgPool) {
+        let users_repo = PostgresUsersRepository::new(pool.clone());
+        let posts_repo = PostgresPostsRepository::new(pool.clone());
+
+        let (deleted_user, other_user, post_id) = (Ulid::new(), Ulid::new(), Ulid::new());
+        users_repo.create(&deleted_user).await.unwrap();
+        users_repo.create(&other_user).await.unwrap();
+        posts_repo.create(&post_id, &other_user).await.unwrap();
+
+        sqlx::query("INSERT INTO reactions (entity_id, user_id) VALUES ($1, $2), ($1, $3)")
+            .bind(post_id.to_bytes())
+            .bind(deleted_user.to_bytes())
+            .bind(other_user.to_bytes())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        users_repo.delete(&deleted_user).await.unwrap();
 
-        Ok(deleted_posts)
+        let reactors: Vec<Vec<u8>> = sqlx::query_scalar("SELECT user_id FROM reactions")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(reactors, vec![other_user.to_bytes().to_vec()]);
     }
 }
@@ -0,0 +1,129 @@
+﻿use crate::errors::AppError;
+use crate::models::app_state::AppState;
+use crate::models::bookmark::{BookmarkPageRequest, BookmarkPageResponse, CreateBookmarkRequest};
+use crate::repositories::interaction_repo::InteractionRepository;
+use crate::services::amq_client::AmqClient;
+use crate::services::post_interactions_service::PostInteractionsService;
+use crate::services::reply_service::ReplyService;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde::Deserialize;
+use ulid::Ulid;
+use zylo_common::auth::Principal;
+
+pub fn create_router<A, I, RS, PS>(state: AppState<A, I, RS, PS>) -> Router
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    Router::new()
+        .route(
+            "/api/users/{userId}/bookmarks",
+            get(get_bookmarks).post(create_bookmark),
+        )
+        .route(
+            "/api/users/{userId}/bookmarks/{postId}",
+            delete(delete_bookmark),
+        )
+        .with_state(state)
+}
+
+/// Bookmarks are private, so only their owner may read or change them.
+fn require_owner(principal: &Principal, user_id: &Ulid) -> Result<(), AppError> {
+    if principal.user_id != user_id.to_string() {
+        return Err(AppError::Forbidden(String::from(
+            "Bookmarks of other users can not be accessed",
+        )));
+    }
+
+    Ok(())
+}
+
+async fn create_bookmark<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    Path(user_id): Path<Ulid>,
+    Json(request): Json<CreateBookmarkRequest>,
+) -> Result<StatusCode, AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    require_owner(&principal, &user_id)?;
+    let is_created = state
+        .post_interactions_service
+        .bookmark(user_id, request.post_id)
+        .await?;
+
+    match is_created {
+        true => Ok(StatusCode::CREATED),
+        false => Ok(StatusCode::OK),
+    }
+}
+
+async fn delete_bookmark<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    Path((user_id, post_id)): Path<(Ulid, Ulid)>,
+) -> Result<StatusCode, AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    require_owner(&principal, &user_id)?;
+    let is_removed = state
+        .post_interactions_service
+        .remove_bookmark(user_id, post_id)
+        .await?;
+
+    match is_removed {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(AppError::NotFound(String::from(
+            "Bookmark could not be found",
+        ))),
+    }
+}
+
+#[derive(Deserialize)]
+struct BookmarkPageParams {
+    next: Option<String>,
+    limit: Option<u32>,
+}
+
+impl From<BookmarkPageParams> for BookmarkPageRequest {
+    fn from(params: BookmarkPageParams) -> Self {
+        Self {
+            next: params.next,
+            limit: params.limit,
+        }
+    }
+}
+
+async fn get_bookmarks<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    Path(user_id): Path<Ulid>,
+    Query(params): Query<BookmarkPageParams>,
+) -> Result<(StatusCode, Json<BookmarkPageResponse>), AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    require_owner(&principal, &user_id)?;
+    let response = state
+        .post_interactions_service
+        .get_bookmarks(user_id, &params.into())
+        .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
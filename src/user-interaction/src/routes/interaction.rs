@@ -1,14 +1,20 @@
 ﻿use crate::errors;
+use crate::models::analytics::{AnalyticsRequest, Granularity, PostAnalyticsResponse};
 use crate::models::app_state::AppState;
+use crate::models::like::{LikePageRequest, LikePageResponse};
+use crate::models::reaction::ReactionRequest;
 use crate::repositories::interaction_repo::InteractionRepository;
 use crate::services::amq_client::AmqClient;
 use crate::services::post_interactions_service::PostInteractionsService;
 use crate::services::reply_service::ReplyService;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
-use axum::routing::{delete, post};
-use axum::Router;
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use ulid::Ulid;
+use zylo_common::auth::Principal;
 
 pub fn create_router<A, I, RS, PS>(state: AppState<A, I, RS, PS>) -> Router
 where
@@ -24,11 +30,23 @@ where
             delete(unlike_post),
         )
         .route("/api/users/{userId}/views/posts/{postId}", post(view_post))
+        .route(
+            "/api/users/{userId}/reactions/posts/{postId}",
+            put(react).delete(unreact),
+        )
+        .route(
+            "/api/users/{userId}/reactions/replies/{replyId}",
+            put(react).delete(unreact),
+        )
+        .route("/api/posts/{postId}/likes", get(get_post_likes))
+        .route("/api/users/{userId}/likes/posts", get(get_user_liked_posts))
+        .route("/api/posts/{postId}/analytics", get(get_post_analytics))
         .with_state(state)
 }
 
 async fn like_post<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
     Path((user_id, post_id)): Path<(Ulid, Ulid)>,
 ) -> Result<StatusCode, errors::AppError>
 where
@@ -37,6 +55,7 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
+    require_self(&principal, &user_id)?;
     let is_liked = state
         .interaction_repo
         .like(&post_id.to_string(), &user_id)
@@ -44,12 +63,13 @@ where
 
     match is_liked {
         true => Ok(StatusCode::CREATED),
-        false =>  Err(errors::AppError::NotFound(String::from("Post or user could not be found"))),
+        false => Ok(StatusCode::OK),
     }
 }
 
 async fn unlike_post<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
     Path((user_id, post_id)): Path<(Ulid, Ulid)>,
 ) -> Result<StatusCode, errors::AppError>
 where
@@ -58,6 +78,7 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
+    require_self(&principal, &user_id)?;
     let is_unliked = state
         .interaction_repo
         .unlike(&post_id.to_string(), &user_id)
@@ -65,12 +86,13 @@ where
 
     match is_unliked {
         true => Ok(StatusCode::NO_CONTENT),
-        false => Err(errors::AppError::NotFound(String::from("Post or user could not be found"))),
+        false => Ok(StatusCode::OK),
     }
 }
 
 async fn view_post<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
     Path((user_id, post_id)): Path<(Ulid, Ulid)>,
 ) -> Result<StatusCode, errors::AppError>
 where
@@ -79,6 +101,7 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
+    require_self(&principal, &user_id)?;
     let is_applied = state
         .interaction_repo
         .view(&post_id.to_string(), &user_id)
@@ -89,3 +112,271 @@ where
         false => Ok(StatusCode::OK),
     }
 }
+
+/// Likes, reactions and views are given in the name of the signed in user only.
+fn require_self(principal: &Principal, user_id: &Ulid) -> Result<(), errors::AppError> {
+    if principal.user_id != user_id.to_string() {
+        return Err(errors::AppError::Forbidden(String::from(
+            "Interactions of other users can not be changed",
+        )));
+    }
+
+    Ok(())
+}
+
+async fn react<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    Path((user_id, entity_id)): Path<(Ulid, Ulid)>,
+    Json(request): Json<ReactionRequest>,
+) -> Result<StatusCode, errors::AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    require_self(&principal, &user_id)?;
+    let is_changed = state
+        .interaction_repo
+        .react(&entity_id.to_string(), &user_id, &request.kind)
+        .await?;
+
+    match is_changed {
+        true => Ok(StatusCode::CREATED),
+        false => Ok(StatusCode::OK),
+    }
+}
+
+async fn unreact<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    Path((user_id, entity_id)): Path<(Ulid, Ulid)>,
+) -> Result<StatusCode, errors::AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    require_self(&principal, &user_id)?;
+    let is_removed = state
+        .interaction_repo
+        .unreact(&entity_id.to_string(), &user_id)
+        .await?;
+
+    match is_removed {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(errors::AppError::NotFound(String::from("Reaction could not be found"))),
+    }
+}
+
+#[derive(Deserialize)]
+struct LikePageParams {
+    next: Option<String>,
+    limit: Option<u32>,
+}
+
+impl From<LikePageParams> for LikePageRequest {
+    fn from(params: LikePageParams) -> Self {
+        Self {
+            next: params.next,
+            limit: params.limit,
+        }
+    }
+}
+
+async fn get_post_likes<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    Path(post_id): Path<Ulid>,
+    Query(params): Query<LikePageParams>,
+) -> Result<(StatusCode, Json<LikePageResponse>), errors::AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    let response = state
+        .post_interactions_service
+        .get_post_likes(post_id, &params.into())
+        .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn get_user_liked_posts<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    Path(user_id): Path<Ulid>,
+    Query(params): Query<LikePageParams>,
+) -> Result<(StatusCode, Json<LikePageResponse>), errors::AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    let response = state
+        .post_interactions_service
+        .get_user_liked_posts(user_id, &params.into())
+        .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[derive(Deserialize)]
+struct AnalyticsParams {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    granularity: Granularity,
+}
+
+impl From<AnalyticsParams> for AnalyticsRequest {
+    fn from(params: AnalyticsParams) -> Self {
+        Self {
+            from: params.from,
+            to: params.to,
+            granularity: params.granularity,
+        }
+    }
+}
+
+async fn get_post_analytics<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    Path(post_id): Path<Ulid>,
+    Query(params): Query<AnalyticsParams>,
+) -> Result<(StatusCode, Json<PostAnalyticsResponse>), errors::AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    let user_id = Ulid::from_string(&principal.user_id)
+        .map_err(|_| errors::ValidationError::InvalidUserId)?;
+    let response = state
+        .post_interactions_service
+        .get_post_analytics(post_id, user_id, &params.into())
+        .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app_state, principal, request, seed_post};
+    use axum::http::Method;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    fn like_uri(user_id: &Ulid, post_id: &Ulid) -> String {
+        format!("/api/users/{user_id}/likes/posts/{post_id}")
+    }
+
+    async fn reaction_of(pool: &PgPool, user_id: &Ulid) -> Option<String> {
+        sqlx::query_scalar("SELECT kind FROM reactions WHERE user_id = $1")
+            .bind(user_id.to_bytes())
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn like_and_unlike_answer_201_200_204_200_and_404(pool: PgPool) {
+        let app = create_router(app_state(&pool));
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+        let caller = principal(&user_id);
+        let uri = like_uri(&user_id, &post_id);
+
+        for (method, expected) in [
+            (Method::POST, StatusCode::CREATED),
+            (Method::POST, StatusCode::OK),
+            (Method::DELETE, StatusCode::NO_CONTENT),
+            (Method::DELETE, StatusCode::OK),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(request(method.clone(), &uri, Some(&caller), None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), expected, "{method} {uri}");
+        }
+
+        let missing = like_uri(&user_id, &Ulid::new());
+        for method in [Method::POST, Method::DELETE] {
+            let response = app
+                .clone()
+                .oneshot(request(method, &missing, Some(&caller), None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[sqlx::test]
+    async fn like_in_the_name_of_another_user_is_forbidden(pool: PgPool) {
+        let state = app_state(&pool);
+        let app = create_router(state.clone());
+        let victim = Ulid::new();
+        let post_id = seed_post(&pool, &victim).await;
+        state
+            .interaction_repo
+            .react(&post_id.to_string(), &victim, "love")
+            .await
+            .unwrap();
+
+        let attacker = principal(&Ulid::new());
+        let uri = like_uri(&victim, &post_id);
+        for method in [Method::POST, Method::DELETE] {
+            let response = app
+                .clone()
+                .oneshot(request(method, &uri, Some(&attacker), None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+
+        assert_eq!(reaction_of(&pool, &victim).await.as_deref(), Some("love"));
+        let events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM outbox")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(events, 0);
+    }
+
+    #[sqlx::test]
+    async fn views_count_once_per_signed_in_user(pool: PgPool) {
+        let state = app_state(&pool);
+        let app = create_router(state.clone());
+        let user_id = Ulid::new();
+        let post_id = seed_post(&pool, &user_id).await;
+        let caller = principal(&user_id);
+
+        let view = |viewer: Ulid| {
+            request(
+                Method::POST,
+                &format!("/api/users/{viewer}/views/posts/{post_id}"),
+                Some(&caller),
+                None,
+            )
+        };
+
+        let response = app.clone().oneshot(view(user_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = app.clone().oneshot(view(user_id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(view(Ulid::new())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let views = state
+            .interaction_repo
+            .get_views(&post_id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(views, 1);
+    }
+}
@@ -1,2 +1,4 @@
-﻿pub mod interaction;
+﻿pub mod bookmark;
+pub mod interaction;
+pub mod moderation;
 pub mod reply;
\ No newline at end of file
@@ -0,0 +1,122 @@
+use crate::errors::AppError;
+use crate::models::app_state::AppState;
+use crate::models::reply::ReplyActor;
+use crate::repositories::interaction_repo::InteractionRepository;
+use crate::services::amq_client::AmqClient;
+use crate::services::post_interactions_service::PostInteractionsService;
+use crate::services::reply_service::ReplyService;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::delete;
+use axum::Router;
+use tracing::info;
+use ulid::Ulid;
+use zylo_common::auth::{Moderator, RequireRole};
+
+pub fn create_router<A, I, RS, PS>(state: AppState<A, I, RS, PS>) -> Router
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    Router::new()
+        .route("/api/moderation/replies/{replyId}", delete(delete_reply))
+        .with_state(state)
+}
+
+/// The audit event is written to the outbox in the transaction deleting the reply.
+async fn delete_reply<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    moderator: RequireRole<Moderator>,
+    Path(reply_id): Path<Ulid>,
+) -> Result<StatusCode, AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    let actor = ReplyActor::try_from(&moderator.principal)?.moderator();
+    state.reply_service.delete(&reply_id, &actor).await?;
+
+    info!(
+        "Reply {} was deleted by moderator {}",
+        reply_id, actor.user_id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::reply_repo::{PostgresReplyRepository, ReplyRepository};
+    use crate::test_support::{app_state, moderator, principal, request, seed_post};
+    use axum::http::Method;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn audited_owners(pool: &PgPool) -> Vec<String> {
+        sqlx::query_scalar(
+            r#"
+            SELECT payload -> 'payload' ->> 'resourceOwnerId'
+            FROM outbox
+            WHERE exchange = 'audit-exchange' AND routing_key = 'reply.moderated'
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn delete_writes_the_audit_event_with_the_reply_removal(pool: PgPool) {
+        let app = create_router(app_state(&pool));
+        let owner_id = Ulid::new();
+        let post_id = seed_post(&pool, &owner_id).await;
+        let reply = PostgresReplyRepository::new(pool.clone())
+            .create(post_id, post_id, "reply", owner_id)
+            .await
+            .unwrap();
+        let uri = format!("/api/moderation/replies/{}", reply.id);
+        let moderator = moderator(&Ulid::new());
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, &uri, Some(&moderator), None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(audited_owners(&pool).await, vec![owner_id.to_string()]);
+
+        let response = app
+            .oneshot(request(Method::DELETE, &uri, Some(&moderator), None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(audited_owners(&pool).await.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn delete_without_the_moderator_role_is_forbidden(pool: PgPool) {
+        let app = create_router(app_state(&pool));
+        let owner_id = Ulid::new();
+        let post_id = seed_post(&pool, &owner_id).await;
+        let reply = PostgresReplyRepository::new(pool.clone())
+            .create(post_id, post_id, "reply", owner_id)
+            .await
+            .unwrap();
+
+        let response = app
+            .oneshot(request(
+                Method::DELETE,
+                &format!("/api/moderation/replies/{}", reply.id),
+                Some(&principal(&Ulid::new())),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(audited_owners(&pool).await.is_empty());
+    }
+}
@@ -1,22 +1,21 @@
 ﻿use crate::errors::AppError;
-use crate::models::amq_message::{ReplyCreatedMessage, ReplyDeletedMessage, ReplyUpdatedMessage};
 use crate::models::app_state::AppState;
 use crate::models::reply::{
-    CreateReplyRequest, PostInteractionResponse, ReplyResponse, UpdateReplyRequest,
+    CreateReplyRequest, PostInteractionResponse, ReplyActor, ReplyPageRequest, ReplyPageResponse,
+    ReplyResponse, ReplySort, UpdateReplyRequest,
 };
 use crate::repositories::interaction_repo::InteractionRepository;
 use crate::services::amq_client::AmqClient;
 use crate::services::post_interactions_service::PostInteractionsService;
 use crate::services::reply_service::ReplyService;
-use crate::utils::constants::POST_EXCHANGE_NAME;
 use crate::utils::helpers::Validate;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::routing::{get, put};
 use axum::{Json, Router};
-use chrono::Utc;
 use serde::Deserialize;
 use ulid::Ulid;
+use zylo_common::auth::{Moderator, Principal, RequireRole};
 
 pub fn create_router<A, I, RS, PS>(state: AppState<A, I, RS, PS>) -> Router
 where
@@ -34,6 +33,10 @@ where
             "/api/posts/{postId}/replies/{replyId}",
             put(update_reply).delete(delete_reply).get(get_reply),
         )
+        .route(
+            "/api/posts/{postId}/replies/{replyId}/children",
+            get(get_children),
+        )
         .with_state(state)
 }
 
@@ -43,10 +46,35 @@ struct GetAllFromPostParams {
     user_id: Option<Ulid>,
 }
 
+#[derive(Deserialize)]
+struct ReplyPageParams {
+    #[serde(rename = "userId")]
+    user_id: Option<Ulid>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    #[serde(default)]
+    sort: ReplySort,
+    depth: Option<u32>,
+    children: Option<u32>,
+}
+
+impl ReplyPageParams {
+    fn page(&self) -> ReplyPageRequest {
+        ReplyPageRequest {
+            cursor: self.cursor.clone(),
+            limit: self.limit,
+            sort: self.sort,
+            depth: self.depth,
+            children: self.children,
+        }
+    }
+}
+
 async fn get_all_from_post<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
     Path(post_id): Path<Ulid>,
-    Query(params): Query<GetAllFromPostParams>,
+    Query(params): Query<ReplyPageParams>,
 ) -> Result<(StatusCode, Json<PostInteractionResponse>), AppError>
 where
     A: AmqClient + 'static,
@@ -54,9 +82,29 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
+    let viewer_id = ReplyActor::try_from(&principal)?.user_id;
     let response = state
         .post_interactions_service
-        .get_post_interactions(post_id, params.user_id)
+        .get_post_interactions(post_id, &params.page(), params.user_id, Some(viewer_id))
+        .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn get_children<A, I, RS, PS>(
+    State(state): State<AppState<A, I, RS, PS>>,
+    Path((post_id, reply_id)): Path<(Ulid, Ulid)>,
+    Query(params): Query<ReplyPageParams>,
+) -> Result<(StatusCode, Json<ReplyPageResponse>), AppError>
+where
+    A: AmqClient + 'static,
+    I: InteractionRepository + 'static,
+    RS: ReplyService + 'static,
+    PS: PostInteractionsService + 'static,
+{
+    let response = state
+        .reply_service
+        .get_reply_page(&post_id, &reply_id, &params.page(), params.user_id)
         .await?;
 
     Ok((StatusCode::OK, Json(response)))
@@ -79,6 +127,7 @@ where
 
 async fn create_reply<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
     Path(post_id): Path<Ulid>,
     Json(request): Json<CreateReplyRequest>,
 ) -> Result<(StatusCode, Json<ReplyResponse>), AppError>
@@ -89,22 +138,14 @@ where
     PS: PostInteractionsService + 'static,
 {
     request.validate()?;
+    let actor = ReplyActor::try_from(&principal)?;
     let reply_response: ReplyResponse = state
         .reply_service
         .create(
             post_id,
             request.reply_to_id,
             &request.content,
-            request.user_id,
-        )
-        .await?;
-
-    state
-        .amq_client
-        .publish_event(
-            POST_EXCHANGE_NAME,
-            "reply.created",
-            &ReplyCreatedMessage::from(reply_response.clone()),
+            actor.user_id,
         )
         .await?;
 
@@ -113,6 +154,8 @@ where
 
 async fn update_reply<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    moderator: Option<RequireRole<Moderator>>,
     Path((_, reply_id)): Path<(Ulid, Ulid)>,
     Json(request): Json<UpdateReplyRequest>,
 ) -> Result<(StatusCode, Json<ReplyResponse>), AppError>
@@ -122,18 +165,10 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
+    let actor = ReplyActor::new(&principal, moderator)?;
     let updated_reply = state
         .reply_service
-        .update(&reply_id, &request.content)
-        .await?;
-
-    state
-        .amq_client
-        .publish_event(
-            POST_EXCHANGE_NAME,
-            "reply.updated",
-            &ReplyUpdatedMessage::new(reply_id, request.content, Utc::now().naive_utc()),
-        )
+        .update(&reply_id, &request.content, &actor)
         .await?;
 
     Ok((StatusCode::OK, Json(updated_reply)))
@@ -141,6 +176,8 @@ where
 
 async fn delete_reply<A, I, RS, PS>(
     State(state): State<AppState<A, I, RS, PS>>,
+    principal: Principal,
+    moderator: Option<RequireRole<Moderator>>,
     Path((_, reply_id)): Path<(Ulid, Ulid)>,
 ) -> Result<StatusCode, AppError>
 where
@@ -149,15 +186,125 @@ where
     RS: ReplyService + 'static,
     PS: PostInteractionsService + 'static,
 {
-    state.reply_service.delete(&reply_id).await?;
-
-    state
-        .amq_client
-        .publish_event(
-            POST_EXCHANGE_NAME,
-            "reply.deleted",
-            &ReplyDeletedMessage::from(reply_id),
-        )
-        .await?;
+    let actor = ReplyActor::new(&principal, moderator)?;
+    state.reply_service.delete(&reply_id, &actor).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::reply_repo::{PostgresReplyRepository, ReplyRepository};
+    use crate::test_support::{app_state, moderator, principal, request, seed_post};
+    use axum::http::Method;
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn audited_actions(pool: &PgPool) -> Vec<String> {
+        sqlx::query_scalar(
+            r#"
+            SELECT payload -> 'payload' ->> 'action'
+            FROM outbox
+            WHERE routing_key = 'reply.moderated'
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn moderator_changes_to_replies_of_others_are_audited(pool: PgPool) {
+        let app = create_router(app_state(&pool));
+        let owner_id = Ulid::new();
+        let post_id = seed_post(&pool, &owner_id).await;
+        let reply = PostgresReplyRepository::new(pool.clone())
+            .create(post_id, post_id, "reply", owner_id)
+            .await
+            .unwrap();
+        let uri = format!("/api/posts/{post_id}/replies/{}", reply.id);
+        let moderator = moderator(&Ulid::new());
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::PUT,
+                &uri,
+                Some(&moderator),
+                Some(json!({ "content": "edited" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(request(Method::DELETE, &uri, Some(&moderator), None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        assert_eq!(
+            audited_actions(&pool).await,
+            vec!["reply.updated", "reply.deleted"]
+        );
+    }
+
+    #[sqlx::test]
+    async fn authors_changing_their_own_replies_are_not_audited(pool: PgPool) {
+        let app = create_router(app_state(&pool));
+        let owner_id = Ulid::new();
+        let post_id = seed_post(&pool, &owner_id).await;
+        let reply = PostgresReplyRepository::new(pool.clone())
+            .create(post_id, post_id, "reply", owner_id)
+            .await
+            .unwrap();
+        let uri = format!("/api/posts/{post_id}/replies/{}", reply.id);
+        let owner = moderator(&owner_id);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::PUT,
+                &uri,
+                Some(&owner),
+                Some(json!({ "content": "edited" })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(request(Method::DELETE, &uri, Some(&owner), None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        assert!(audited_actions(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn others_without_the_moderator_role_are_forbidden(pool: PgPool) {
+        let app = create_router(app_state(&pool));
+        let owner_id = Ulid::new();
+        let post_id = seed_post(&pool, &owner_id).await;
+        let reply = PostgresReplyRepository::new(pool.clone())
+            .create(post_id, post_id, "reply", owner_id)
+            .await
+            .unwrap();
+        let uri = format!("/api/posts/{post_id}/replies/{}", reply.id);
+
+        let response = app
+            .oneshot(request(
+                Method::DELETE,
+                &uri,
+                Some(&principal(&Ulid::new())),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(audited_actions(&pool).await.is_empty());
+    }
+}
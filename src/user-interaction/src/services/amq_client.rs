@@ -1,40 +1,54 @@
-﻿use crate::errors;
+use crate::errors;
 use crate::errors::ProblemResponse;
 use crate::models::amq_message::{
     PostCreatedMessage, PostDeletedMessage, UserCreatedMessage, UserDeletedMessage,
 };
+use crate::models::outbox::OutboxMessage;
 use crate::models::Finalizer;
 use crate::repositories::interaction_repo::InteractionRepository;
 use crate::repositories::posts_repo::PostsRepository;
+use crate::repositories::processed_messages_repo::ProcessedMessagesRepository;
 use crate::repositories::users_repo::UsersRepository;
+use crate::services::amq_topology::{
+    POST_CREATED_QUEUE, POST_DELETED_QUEUE, TOPOLOGY, USER_CREATED_QUEUE, USER_DELETED_QUEUE,
+};
 use crate::settings::RabbitMq;
-use crate::utils::constants::{POST_EXCHANGE_NAME, USER_EXCHANGE_NAME};
+use crate::utils::constants::{AMQ_HEALTH_CHECK_INTERVAL_SECONDS, OTEL_SERVICE_NAME};
 use async_trait::async_trait;
 use futures_util::StreamExt;
-use lapin::options::{
-    BasicAckOptions, BasicConsumeOptions, BasicNackOptions, BasicPublishOptions,
-    ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions,
-};
-use lapin::types::FieldTable;
-use lapin::{BasicProperties, Channel, Connection, ConnectionProperties};
+use lapin::message::Delivery;
+use lapin::options::{BasicAckOptions, BasicPublishOptions, ConfirmSelectOptions};
+use lapin::{BasicProperties, Channel, Consumer};
 use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify, RwLock};
+use tokio::time::Instant;
 use tracing::log::warn;
-use tracing::{error, info};
+use tracing::{error, info, Instrument, Span};
+use zylo_common::amq::{
+    consumer_span, reconnect_delay, retry_count, retry_or_park, trace_headers, trace_headers_from,
+    ActiveConsumer, AmqLink, ConsumerFactory, ConsumerMetrics, ConsumerStart, QueueBinding,
+    RetryPolicy,
+};
+use zylo_common::config::AmqConsumers;
+use zylo_common::events::{decode, trace_context_of, Event, EventEnvelope};
+use zylo_common::outbox::OutboxPublisher;
 
 #[async_trait]
 pub trait AmqClient: Send + Sync + Finalizer {
     async fn declare_exchanges(&self) -> Result<(), errors::AmqError>;
     async fn declare_queues(&self) -> Result<(), errors::AmqError>;
 
-    async fn publish_event<T: Serialize + Send + Sync>(
+    async fn publish_event<T: Event + Serialize + Send + Sync>(
         &self,
         exchange_name: &str,
         routing_key: &str,
-        event: &T,
+        event: T,
     ) -> Result<(), errors::AmqError>;
 
     async fn setup_listeners<
@@ -81,99 +95,422 @@ pub trait AmqConsumer: Send + Sync {
 }
 
 pub struct RabbitMqClient {
-    connection: Connection,
+    uri: String,
+    link: RwLock<Arc<AmqLink>>,
+    connection_lost: Arc<Notify>,
+    closing: Arc<AtomicBool>,
+    /// Set when the broker cancels a consumer while its channel stays open, e.g. because the
+    /// queue was deleted. The channel then looks healthy, so it is tracked separately.
+    consumer_cancelled: Arc<AtomicBool>,
     consumer_channels: Arc<Mutex<Vec<Channel>>>,
-    publish_channel: Arc<Channel>,
+    consumer_factories: Mutex<Vec<(&'static str, ConsumerFactory)>>,
+    retry_policy: RetryPolicy,
+    processed_messages: Arc<dyn ProcessedMessagesRepository>,
+    consumer_settings: AmqConsumers,
+    consumer_metrics: ConsumerMetrics,
+    active_consumers: Arc<Mutex<Vec<ActiveConsumer>>>,
 }
 
 impl RabbitMqClient {
-    pub async fn new(config: &RabbitMq) -> Result<Self, errors::AmqError> {
-        let connection = Connection::connect(&config.uri, ConnectionProperties::default()).await?;
-        let publish_channel = connection.create_channel().await?;
+    pub async fn new(
+        config: &RabbitMq,
+        processed_messages: Arc<dyn ProcessedMessagesRepository>,
+    ) -> Result<Self, errors::AmqError> {
+        let connection_lost = Arc::new(Notify::new());
+        let link = AmqLink::connect(&config.uri, connection_lost.clone()).await?;
 
         Ok(Self {
-            connection,
+            uri: config.uri.clone(),
+            link: RwLock::new(Arc::new(link)),
+            connection_lost,
+            closing: Arc::new(AtomicBool::new(false)),
+            consumer_cancelled: Arc::new(AtomicBool::new(false)),
             consumer_channels: Arc::new(Mutex::new(Vec::new())),
-            publish_channel: Arc::new(publish_channel),
+            consumer_factories: Mutex::new(Vec::new()),
+            retry_policy: RetryPolicy::default(),
+            processed_messages,
+            consumer_settings: config.consumers.clone(),
+            consumer_metrics: ConsumerMetrics::new(OTEL_SERVICE_NAME),
+            active_consumers: Arc::new(Mutex::new(Vec::new())),
         })
     }
 
+    /// Channel for a consumer, in confirm mode so that failed messages are only acknowledged once
+    /// their retry is with the broker.
     pub async fn new_channel(&self) -> Result<Channel, errors::AmqError> {
-        let channel = self.connection.create_channel().await?;
+        let channel = self.link.read().await.connection().create_channel().await?;
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
         self.consumer_channels.lock().await.push(channel.clone());
 
         Ok(channel)
     }
 
+    /// Channel for plain publishes. Fails fast while the broker is unreachable instead of
+    /// waiting for the supervisor to reconnect.
+    async fn publish_channel(&self) -> Result<Channel, errors::AmqError> {
+        let link = self.link.read().await;
+        if !link.is_healthy() {
+            return Err(errors::AmqError::Disconnected);
+        }
+
+        Ok(link.publish_channel().clone())
+    }
+
+    async fn confirm_channel(&self) -> Result<Channel, errors::AmqError> {
+        let link = self.link.read().await;
+        if !link.is_healthy() {
+            return Err(errors::AmqError::Disconnected);
+        }
+
+        Ok(link.confirm_channel().clone())
+    }
+
+    /// Fails when a declared queue has no consumer or a consumer reads an undeclared queue.
+    async fn verify_topology(&self) -> Result<(), errors::AmqError> {
+        let consumed: Vec<&str> = self
+            .consumer_factories
+            .lock()
+            .await
+            .iter()
+            .map(|(queue, _)| *queue)
+            .collect();
+
+        TOPOLOGY
+            .verify(&consumed)
+            .map_err(errors::AmqError::Topology)
+    }
+
+    async fn is_healthy(&self) -> bool {
+        if !self.link.read().await.is_healthy() {
+            return false;
+        }
+
+        self.consumer_channels
+            .lock()
+            .await
+            .iter()
+            .all(|channel| channel.status().connected())
+    }
+
+    /// Watches the connection and, once it or any channel is lost or a consumer is cancelled,
+    /// reconnects with backoff, redeclares the topology and restarts every registered consumer.
+    pub fn spawn_supervisor(self: &Arc<Self>) {
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(AMQ_HEALTH_CHECK_INTERVAL_SECONDS));
+            loop {
+                tokio::select! {
+                    _ = client.connection_lost.notified() => {}
+                    _ = interval.tick() => {}
+                }
+
+                if client.closing.load(Ordering::Relaxed) {
+                    break;
+                }
+                if client.consumer_cancelled.swap(false, Ordering::Relaxed) {
+                    warn!("RabbitMQ consumer cancelled, reconnecting...");
+                } else if client.is_healthy().await {
+                    continue;
+                } else {
+                    warn!("RabbitMQ connection lost, reconnecting...");
+                }
+
+                let mut attempt = 0;
+                while !client.closing.load(Ordering::Relaxed) {
+                    match client.reconnect().await {
+                        Ok(()) => {
+                            info!("Reconnected to RabbitMQ after {} attempts", attempt + 1);
+                            break;
+                        }
+                        Err(err) => {
+                            let delay = reconnect_delay(attempt);
+                            error!(
+                                "Failed to reconnect to RabbitMQ, retrying in {:?}: {}",
+                                delay, err
+                            );
+                            attempt += 1;
+                            tokio::time::sleep(delay).await;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    async fn reconnect(&self) -> Result<(), errors::AmqError> {
+        let link = Arc::new(AmqLink::connect(&self.uri, self.connection_lost.clone()).await?);
+        let previous = std::mem::replace(&mut *self.link.write().await, link.clone());
+        previous.close().await;
+
+        self.consumer_channels.lock().await.clear();
+        self.active_consumers.lock().await.clear();
+
+        if let Err(err) = self.restore_topology().await {
+            link.close().await;
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    async fn restore_topology(&self) -> Result<(), errors::AmqError> {
+        self.declare_exchanges().await?;
+        self.declare_queues().await?;
+
+        let factories = self.consumer_factories.lock().await.clone();
+        for (_, factory) in factories {
+            let channel = self.new_channel().await?;
+            factory(channel).await?;
+        }
+
+        Ok(())
+    }
+
     async fn consume_event<T, F, Fut>(
         &self,
-        queue_name: String,
+        binding: QueueBinding,
         handler: F,
     ) -> Result<(), errors::AppError>
     where
-        T: for<'de> serde::Deserialize<'de> + Send + 'static,
-        F: Fn(T) -> Fut + Send + 'static + Clone,
+        T: Event + DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static + Clone,
         Fut: Future<Output = Result<(), errors::AppError>> + Send + 'static,
     {
+        let queue_name = binding.queue.to_string();
+        let settings = self.consumer_settings.for_queue(&queue_name);
+        let metrics = self.consumer_metrics.clone();
+        let active_consumers = self.active_consumers.clone();
+        let retry_policy = self.retry_policy;
+        let processed_messages = self.processed_messages.clone();
+        let connection_lost = self.connection_lost.clone();
+        let closing = self.closing.clone();
+        let consumer_cancelled = self.consumer_cancelled.clone();
+
+        let factory: ConsumerFactory = Arc::new(move |channel: Channel| -> ConsumerStart {
+            let queue_name = queue_name.clone();
+            let metrics = metrics.clone();
+            let active_consumers = active_consumers.clone();
+            let processed_messages = processed_messages.clone();
+            let connection_lost = connection_lost.clone();
+            let closing = closing.clone();
+            let consumer_cancelled = consumer_cancelled.clone();
+            let handler = handler.clone();
+
+            Box::pin(async move {
+                let (active_consumer, consumer) =
+                    ActiveConsumer::start(channel.clone(), &queue_name, settings, &metrics).await?;
+                active_consumers.lock().await.push(active_consumer.clone());
+
+                tokio::spawn(async move {
+                    Self::run_consumer(
+                        consumer,
+                        active_consumer,
+                        channel.clone(),
+                        queue_name.clone(),
+                        retry_policy,
+                        processed_messages,
+                        handler,
+                    )
+                    .await;
+
+                    if !closing.load(Ordering::Relaxed) {
+                        warn!("Consumer of {} stopped unexpectedly", queue_name);
+                        // A closed channel is caught by the health check, an open one means
+                        // the broker cancelled the consumer.
+                        if channel.status().connected() {
+                            consumer_cancelled.store(true, Ordering::Relaxed);
+                        }
+                        connection_lost.notify_one();
+                    }
+                });
+
+                Ok(())
+            })
+        });
+
         let channel = self.new_channel().await?;
-        let mut consumer = channel
-            .basic_consume(
-                &queue_name,
-                &format!("{}-consumer", queue_name),
-                BasicConsumeOptions::default(),
-                FieldTable::default(),
-            )
+        factory(channel)
             .await
             .map_err(errors::AmqError::ConnectionError)?;
+        self.consumer_factories
+            .lock()
+            .await
+            .push((binding.queue, factory));
 
-        tokio::spawn(async move {
-            while let Some(delivery_result) = consumer.next().await {
-                match delivery_result {
-                    Ok(delivery) => {
-                        let event: T = match serde_json::from_slice(&delivery.data) {
-                            Ok(event) => event,
-                            Err(err) => {
-                                error!(
-                                    "Failed to deserialize message from {}: {}",
-                                    queue_name, err
-                                );
-
-                                let options = BasicNackOptions {
-                                    requeue: false,
-                                    ..Default::default()
-                                };
-                                if let Err(err) = delivery.nack(options).await {
-                                    error!("Failed to nack message from {}: {}", queue_name, err);
-                                }
-
-                                continue;
-                            }
-                        };
-
-                        if let Err(err) = handler(event).await {
-                            match err.status_code() {
-                                StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND => {
-                                    warn!("{}", err.to_string())
-                                }
-                                _ => error!("{}", err.to_string()),
-                            }
-                        }
+        Ok(())
+    }
 
-                        if let Err(err) = delivery.ack(BasicAckOptions::default()).await {
-                            error!(
-                                "Failed to acknowledge message from {}: {:?}",
-                                queue_name, err
-                            );
-                        }
+    async fn run_consumer<T, F, Fut>(
+        mut consumer: Consumer,
+        active_consumer: ActiveConsumer,
+        channel: Channel,
+        queue_name: String,
+        retry_policy: RetryPolicy,
+        processed_messages: Arc<dyn ProcessedMessagesRepository>,
+        handler: F,
+    ) where
+        T: Event + DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static + Clone,
+        Fut: Future<Output = Result<(), errors::AppError>> + Send + 'static,
+    {
+        while let Some(delivery_result) = consumer.next().await {
+            match delivery_result {
+                Ok(delivery) => {
+                    let in_flight = active_consumer.begin().await;
+                    let channel = channel.clone();
+                    let queue_name = queue_name.clone();
+                    let processed_messages = processed_messages.clone();
+                    let handler = handler.clone();
+
+                    tokio::spawn(async move {
+                        let span = consumer_span(&queue_name, &delivery);
+                        let outcome = Self::process_delivery(
+                            &delivery,
+                            &queue_name,
+                            &channel,
+                            &retry_policy,
+                            processed_messages.as_ref(),
+                            handler,
+                        )
+                        .instrument(span.clone())
+                        .await;
+
+                        span.record("messaging.outcome", outcome);
+                        drop(in_flight);
+                    });
+                }
+                Err(err) => {
+                    error!("Failed to consume message from {}: {:?}", queue_name, err);
+                }
+            }
+        }
+    }
+
+    /// Handles and settles a single delivery, returning the outcome recorded on its span.
+    ///
+    /// The ledger is written after the handler and outside of its work, which can span Postgres
+    /// and Redis, so a message can still be handled twice. Every handler is idempotent for that
+    /// reason; the ledger only saves repeating the work.
+    async fn process_delivery<T, F, Fut>(
+        delivery: &Delivery,
+        queue_name: &str,
+        channel: &Channel,
+        retry_policy: &RetryPolicy,
+        processed_messages: &dyn ProcessedMessagesRepository,
+        handler: F,
+    ) -> &'static str
+    where
+        T: Event + DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + 'static + Clone,
+        Fut: Future<Output = Result<(), errors::AppError>> + Send + 'static,
+    {
+        let message_id = delivery
+            .properties
+            .message_id()
+            .as_ref()
+            .map(|id| id.as_str().to_string());
+
+        if let Some(message_id) = &message_id {
+            match processed_messages
+                .is_processed(queue_name, message_id)
+                .await
+            {
+                Ok(true) => {
+                    info!(
+                        "Skipping already processed message {} from {}",
+                        message_id, queue_name
+                    );
+                    if let Err(err) = delivery.ack(BasicAckOptions::default()).await {
+                        error!(
+                            "Failed to acknowledge message from {}: {:?}",
+                            queue_name, err
+                        );
                     }
+                    return "skipped";
+                }
+                Ok(false) => {}
+                Err(err) => warn!(
+                    "Failed to look up message {} from {}: {}",
+                    message_id, queue_name, err
+                ),
+            }
+        }
+
+        let event: T = match decode::<T>(&delivery.data) {
+            Ok(envelope) => envelope.payload,
+            Err(err) => {
+                error!("Failed to decode message from {}: {}", queue_name, err);
+                Span::current().record("error.type", "decode_error");
+
+                return Self::settle_failure(delivery, queue_name, channel, retry_policy, true)
+                    .await;
+            }
+        };
+
+        match handler(event).await {
+            Ok(()) => {
+                if let Some(message_id) = &message_id {
+                    if let Err(err) = processed_messages
+                        .mark_processed(queue_name, message_id)
+                        .await
+                    {
+                        warn!(
+                            "Failed to record message {} from {}: {}",
+                            message_id, queue_name, err
+                        );
+                    }
+                }
+
+                match delivery.ack(BasicAckOptions::default()).await {
+                    Ok(()) => "acked",
                     Err(err) => {
-                        error!("Failed to consume message from {}: {:?}", queue_name, err);
+                        error!(
+                            "Failed to acknowledge message from {}: {:?}",
+                            queue_name, err
+                        );
+                        "ack_failed"
                     }
                 }
             }
-        });
+            Err(err) => {
+                let permanent = matches!(
+                    err.status_code(),
+                    StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND
+                );
+                if permanent {
+                    warn!("Parking message from {}: {}", queue_name, err);
+                } else {
+                    error!(
+                        "Failed to handle message from {} (retry {}): {}",
+                        queue_name,
+                        retry_count(delivery),
+                        err
+                    );
+                }
 
-        Ok(())
+                Span::current().record("error.type", err.title());
+                Self::settle_failure(delivery, queue_name, channel, retry_policy, permanent).await
+            }
+        }
+    }
+
+    async fn settle_failure(
+        delivery: &Delivery,
+        queue_name: &str,
+        channel: &Channel,
+        retry_policy: &RetryPolicy,
+        permanent: bool,
+    ) -> &'static str {
+        match retry_or_park(channel, queue_name, delivery, retry_policy, permanent).await {
+            Ok(disposition) => disposition.as_str(),
+            Err(err) => {
+                error!("Failed to settle message from {}: {:?}", queue_name, err);
+                "settle_failed"
+            }
+        }
     }
 
     async fn handle_post_deleted<P, I>(
@@ -187,7 +524,12 @@ impl RabbitMqClient {
     {
         let post_id = event.id.to_string();
 
-        posts_repo.delete(&event.id).await?;
+        // A redelivery finds the post already gone and still clears its interactions, in case
+        // the earlier delivery failed in between.
+        match posts_repo.delete(&event.id).await {
+            Ok(()) | Err(errors::DatabaseError::NotFound(_)) => {}
+            Err(err) => return Err(err.into()),
+        }
         interactions_repo.delete_interactions(&post_id).await?;
 
         Ok(())
@@ -218,10 +560,17 @@ impl RabbitMqClient {
         users_repo: Arc<U>,
         interaction_repo: Arc<I>,
     ) -> Result<(), errors::AppError> {
-        let deleted_posts_ids = users_repo.delete(&event.id).await?;
+        interaction_repo.delete_user_reactions(&event.id).await?;
+        // Already deleted by an earlier delivery. Its posts and replies went with the user, so
+        // at most their cached interactions are left behind, and those are never read again.
+        let deleted_ids = match users_repo.delete(&event.id).await {
+            Ok(deleted_ids) => deleted_ids,
+            Err(errors::DatabaseError::NotFound(_)) => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
 
         interaction_repo
-            .delete_many_interactions(&deleted_posts_ids)
+            .delete_many_interactions(&deleted_ids)
             .await?;
         Ok(())
     }
@@ -230,93 +579,44 @@ impl RabbitMqClient {
 #[async_trait]
 impl AmqClient for RabbitMqClient {
     async fn declare_exchanges(&self) -> Result<(), errors::AmqError> {
-        let exchange_options = ExchangeDeclareOptions {
-            durable: true,
-            ..Default::default()
-        };
-
-        self.publish_channel
-            .exchange_declare(
-                POST_EXCHANGE_NAME,
-                lapin::ExchangeKind::Direct,
-                exchange_options,
-                FieldTable::default(),
-            )
-            .await?;
-
-        self.publish_channel
-            .exchange_declare(
-                USER_EXCHANGE_NAME,
-                lapin::ExchangeKind::Direct,
-                exchange_options,
-                FieldTable::default(),
-            )
-            .await?;
+        let channel = self.publish_channel().await?;
+        TOPOLOGY.declare_exchanges(&channel).await?;
 
         Ok(())
     }
 
     async fn declare_queues(&self) -> Result<(), errors::AmqError> {
-        let queue_options = QueueDeclareOptions {
-            durable: true,
-            ..Default::default()
-        };
-        let queue_map = vec![
-            (
-                "post-deleted-user-interaction-queue",
-                POST_EXCHANGE_NAME,
-                "post.deleted",
-            ),
-            (
-                "post-created-user-interaction-queue",
-                POST_EXCHANGE_NAME,
-                "post.created",
-            ),
-            (
-                "user-created-user-interaction-queue",
-                USER_EXCHANGE_NAME,
-                "user.created",
-            ),
-            (
-                "user-deleted-user-interaction-queue",
-                USER_EXCHANGE_NAME,
-                "user.deleted",
-            ),
-        ];
-
-        for (queue_name, exchange_name, routing_key) in queue_map {
-            self.publish_channel
-                .queue_declare(queue_name, queue_options, FieldTable::default())
-                .await?;
-
-            self.publish_channel
-                .queue_bind(
-                    queue_name,
-                    exchange_name,
-                    routing_key,
-                    QueueBindOptions::default(),
-                    FieldTable::default(),
-                )
-                .await?;
-        }
+        let channel = self.publish_channel().await?;
+        TOPOLOGY
+            .declare_queues(&channel, &self.retry_policy)
+            .await?;
+        TOPOLOGY
+            .migrate_legacy_queues(self.link.read().await.connection())
+            .await?;
 
         Ok(())
     }
 
-    async fn publish_event<T: Serialize + Sync + Send>(
+    async fn publish_event<T: Event + Serialize + Sync + Send>(
         &self,
         exchange_name: &str,
         routing_key: &str,
-        event: &T,
+        event: T,
     ) -> Result<(), errors::AmqError> {
-        let message = serde_json::to_string(event).map_err(errors::AmqError::DeserializeError)?;
-        self.publish_channel
+        let envelope = EventEnvelope::new(event);
+        let message =
+            serde_json::to_string(&envelope).map_err(errors::AmqError::DeserializeError)?;
+        self.publish_channel()
+            .await?
             .basic_publish(
                 exchange_name,
                 routing_key,
                 BasicPublishOptions::default(),
                 message.as_bytes(),
-                BasicProperties::default(),
+                BasicProperties::default()
+                    .with_message_id(envelope.id.to_string().into())
+                    .with_content_type("application/json".into())
+                    .with_headers(trace_headers()),
             )
             .await?;
 
@@ -343,7 +643,44 @@ impl AmqClient for RabbitMqClient {
 
         self.consume_user_created(users_repo.clone()).await?;
         self.consume_user_deleted(users_repo, interaction_repo)
-            .await
+            .await?;
+
+        self.verify_topology().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutboxPublisher<OutboxMessage> for RabbitMqClient {
+    type Error = errors::AmqError;
+
+    async fn publish_confirmed(&self, message: &OutboxMessage) -> Result<(), errors::AmqError> {
+        let payload =
+            serde_json::to_vec(&message.payload).map_err(errors::AmqError::DeserializeError)?;
+        let properties = BasicProperties::default()
+            .with_message_id(message.id.to_string().into())
+            .with_content_type("application/json".into())
+            .with_delivery_mode(2)
+            .with_headers(trace_headers_from(&trace_context_of(&payload)));
+
+        let confirmation = self
+            .confirm_channel()
+            .await?
+            .basic_publish(
+                &message.exchange,
+                &message.routing_key,
+                BasicPublishOptions::default(),
+                &payload,
+                properties,
+            )
+            .await?
+            .await?;
+
+        if confirmation.is_nack() {
+            return Err(errors::AmqError::NotConfirmed(message.id.to_string()));
+        }
+
+        Ok(())
     }
 }
 
@@ -358,19 +695,15 @@ impl AmqConsumer for RabbitMqClient {
         P: PostsRepository + 'static,
         I: InteractionRepository + 'static,
     {
-        self.consume_event(
-            "post-deleted-user-interaction-queue".to_string(),
-            move |event: PostDeletedMessage| {
-                Box::pin({
-                    let posts_repo = posts_repo.clone();
-                    let interaction_repo = interaction_repo.clone();
-                    async move {
-                        RabbitMqClient::handle_post_deleted(event, posts_repo, interaction_repo)
-                            .await
-                    }
-                })
-            },
-        )
+        self.consume_event(POST_DELETED_QUEUE, move |event: PostDeletedMessage| {
+            Box::pin({
+                let posts_repo = posts_repo.clone();
+                let interaction_repo = interaction_repo.clone();
+                async move {
+                    RabbitMqClient::handle_post_deleted(event, posts_repo, interaction_repo).await
+                }
+            })
+        })
         .await
     }
 
@@ -378,15 +711,12 @@ impl AmqConsumer for RabbitMqClient {
         &self,
         posts_repo: Arc<P>,
     ) -> Result<(), errors::AppError> {
-        self.consume_event(
-            "post-created-user-interaction-queue".to_string(),
-            move |event: PostCreatedMessage| {
-                Box::pin({
-                    let posts_repo = posts_repo.clone();
-                    async move { RabbitMqClient::handle_post_created(event, posts_repo).await }
-                })
-            },
-        )
+        self.consume_event(POST_CREATED_QUEUE, move |event: PostCreatedMessage| {
+            Box::pin({
+                let posts_repo = posts_repo.clone();
+                async move { RabbitMqClient::handle_post_created(event, posts_repo).await }
+            })
+        })
         .await
     }
 
@@ -394,15 +724,12 @@ impl AmqConsumer for RabbitMqClient {
     where
         U: UsersRepository + 'static,
     {
-        self.consume_event(
-            "user-created-user-interaction-queue".to_string(),
-            move |event: UserCreatedMessage| {
-                Box::pin({
-                    let users_repo = users_repo.clone();
-                    async move { RabbitMqClient::handle_user_created(event, users_repo).await }
-                })
-            },
-        )
+        self.consume_event(USER_CREATED_QUEUE, move |event: UserCreatedMessage| {
+            Box::pin({
+                let users_repo = users_repo.clone();
+                async move { RabbitMqClient::handle_user_created(event, users_repo).await }
+            })
+        })
         .await
     }
 
@@ -415,19 +742,15 @@ impl AmqConsumer for RabbitMqClient {
         U: UsersRepository + 'static,
         I: InteractionRepository + 'static,
     {
-        self.consume_event(
-            "user-deleted-user-interaction-queue".to_string(),
-            move |event: UserDeletedMessage| {
-                Box::pin({
-                    let users_repo = users_repo.clone();
-                    let interaction_repo = interaction_repo.clone();
-                    async move {
-                        RabbitMqClient::handle_user_deleted(event, users_repo, interaction_repo)
-                            .await
-                    }
-                })
-            },
-        )
+        self.consume_event(USER_DELETED_QUEUE, move |event: UserDeletedMessage| {
+            Box::pin({
+                let users_repo = users_repo.clone();
+                let interaction_repo = interaction_repo.clone();
+                async move {
+                    RabbitMqClient::handle_user_deleted(event, users_repo, interaction_repo).await
+                }
+            })
+        })
         .await
     }
 }
@@ -435,6 +758,31 @@ impl AmqConsumer for RabbitMqClient {
 #[async_trait]
 impl Finalizer for RabbitMqClient {
     async fn finalize(&self) -> Result<(), errors::AppError> {
+        self.closing.store(true, Ordering::Relaxed);
+
+        let consumers = self.active_consumers.lock().await;
+        info!("Draining {} RabbitMQ consumers...", consumers.len());
+        for consumer in consumers.iter() {
+            if let Err(e) = consumer.cancel().await {
+                error!(
+                    "Failed to cancel consumer of {}: {:?}",
+                    consumer.queue_name(),
+                    e
+                );
+            }
+        }
+
+        let deadline =
+            Instant::now() + Duration::from_secs(self.consumer_settings.drain_timeout_seconds);
+        for consumer in consumers.iter() {
+            if !consumer.wait_idle(deadline).await {
+                warn!(
+                    "Handlers of {} were still running at the drain deadline",
+                    consumer.queue_name()
+                );
+            }
+        }
+
         info!("Closing RabbitMQ client connection...");
 
         let mut channels = self.consumer_channels.lock().await;
@@ -444,15 +792,182 @@ impl Finalizer for RabbitMqClient {
             }
         }
 
-        if let Err(e) = self.publish_channel.close(200, "Shutting down").await {
-            error!("Failed to close publish channel: {:?}", e);
+        self.link.read().await.close().await;
+
+        info!("RabbitMQ client connection closed");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::interaction_repo::PostgresInteractionRepository;
+    use crate::repositories::posts_repo::PostgresPostsRepository;
+    use crate::repositories::processed_messages_repo::PostgresProcessedMessagesRepository;
+    use crate::repositories::users_repo::PostgresUsersRepository;
+    use crate::services::cache_service::RedisCacheService;
+    use crate::settings::{Reactions, Redis};
+    use crate::test_support::interaction_repo;
+    use crate::utils::constants::USER_EXCHANGE_NAME;
+    use lapin::options::{BasicGetOptions, QueueBindOptions, QueueDeclareOptions};
+    use lapin::types::FieldTable;
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    fn env_or(key: &str, default: &str) -> String {
+        std::env::var(key).unwrap_or_else(|_| default.to_string())
+    }
+
+    async fn user_exists(pool: &PgPool, user_id: &Ulid) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id.to_bytes())
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn every_handler_can_handle_the_same_message_twice(pool: PgPool) {
+        let posts_repo = Arc::new(PostgresPostsRepository::new(pool.clone()));
+        let users_repo = Arc::new(PostgresUsersRepository::new(pool.clone()));
+        let interaction_repo = Arc::new(interaction_repo(&pool));
+        let (user_id, post_id) = (Ulid::new(), Ulid::new());
+
+        for _ in 0..2 {
+            RabbitMqClient::handle_user_created(
+                UserCreatedMessage { id: user_id },
+                users_repo.clone(),
+            )
+            .await
+            .unwrap();
+            RabbitMqClient::handle_post_created(
+                PostCreatedMessage {
+                    id: post_id,
+                    user_id,
+                },
+                posts_repo.clone(),
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            posts_repo.get_author(&post_id).await.unwrap(),
+            Some(user_id)
+        );
+
+        for _ in 0..2 {
+            RabbitMqClient::handle_post_deleted(
+                PostDeletedMessage { id: post_id },
+                posts_repo.clone(),
+                interaction_repo.clone(),
+            )
+            .await
+            .unwrap();
         }
+        assert_eq!(posts_repo.get_author(&post_id).await.unwrap(), None);
 
-        if let Err(e) = self.connection.close(200, "Shutting down").await {
-            error!("Failed to close connection: {:?}", e);
+        for _ in 0..2 {
+            RabbitMqClient::handle_user_deleted(
+                UserDeletedMessage { id: user_id },
+                users_repo.clone(),
+                interaction_repo.clone(),
+            )
+            .await
+            .unwrap();
         }
+        assert!(!user_exists(&pool, &user_id).await);
+    }
 
-        info!("RabbitMQ client connection closed");
-        Ok(())
+    #[sqlx::test]
+    #[ignore = "requires RabbitMQ and Redis"]
+    async fn deleted_user_is_removed_here_and_delivered_to_every_other_service(pool: PgPool) {
+        let posts_repo = Arc::new(PostgresPostsRepository::new(pool.clone()));
+        let users_repo = Arc::new(PostgresUsersRepository::new(pool.clone()));
+        let cache_service = Arc::new(
+            RedisCacheService::new(Redis {
+                uri: env_or("REDIS_URI", "redis://localhost:6379"),
+            })
+            .unwrap(),
+        );
+        let interaction_repo = Arc::new(PostgresInteractionRepository::new(
+            pool.clone(),
+            cache_service,
+            &Reactions::default(),
+        ));
+        let processed_messages = Arc::new(PostgresProcessedMessagesRepository::new(pool.clone()));
+
+        let config = RabbitMq {
+            uri: env_or("AMQP_URI", "amqp://localhost:5672"),
+            consumers: AmqConsumers::default(),
+        };
+        let client = RabbitMqClient::new(&config, processed_messages)
+            .await
+            .unwrap();
+        client
+            .setup_listeners(posts_repo.clone(), users_repo.clone(), interaction_repo)
+            .await
+            .unwrap();
+
+        // Stands in for media-service: it has to get its own copy of the event instead of
+        // competing with this service for it. Only the fan-out is checked here; that the real
+        // media-service consumer removes its users and posts is the test of the same name in
+        // media-service/src/services/amq.rs. Running both consumers in one test is out of scope,
+        // media-service being a binary crate that this one can not depend on.
+        let channel = client.publish_channel().await.unwrap();
+        let other_service_queue = format!("user-deleted-test-{}", Ulid::new());
+        channel
+            .queue_declare(
+                &other_service_queue,
+                QueueDeclareOptions {
+                    exclusive: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await
+            .unwrap();
+        channel
+            .queue_bind(
+                &other_service_queue,
+                USER_EXCHANGE_NAME,
+                USER_DELETED_QUEUE.routing_key,
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .unwrap();
+
+        let (user_id, post_id) = (Ulid::new(), Ulid::new());
+        users_repo.create(&user_id).await.unwrap();
+        posts_repo.create(&post_id, &user_id).await.unwrap();
+
+        // Published without an envelope, the way user-management does.
+        let body = serde_json::to_vec(&serde_json::json!({ "id": user_id })).unwrap();
+        channel
+            .basic_publish(
+                USER_EXCHANGE_NAME,
+                USER_DELETED_QUEUE.routing_key,
+                BasicPublishOptions::default(),
+                &body,
+                BasicProperties::default(),
+            )
+            .await
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while user_exists(&pool, &user_id).await {
+            assert!(Instant::now() < deadline, "user was not removed");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let copy = channel
+            .basic_get(&other_service_queue, BasicGetOptions::default())
+            .await
+            .unwrap()
+            .expect("the other service did not receive the event");
+        assert_eq!(copy.delivery.data, body);
+
+        client.finalize().await.unwrap();
     }
 }
@@ -0,0 +1,43 @@
+use crate::utils::constants::{AUDIT_EXCHANGE_NAME, POST_EXCHANGE_NAME, USER_EXCHANGE_NAME};
+use zylo_common::amq::{QueueBinding, Topology};
+
+pub const POST_CREATED_QUEUE: QueueBinding = QueueBinding {
+    queue: "post-created-user-interaction-queue-v2",
+    exchange: POST_EXCHANGE_NAME,
+    routing_key: "post.created",
+    legacy_queue: Some("post-created-user-interaction-queue"),
+};
+
+pub const POST_DELETED_QUEUE: QueueBinding = QueueBinding {
+    queue: "post-deleted-user-interaction-queue-v2",
+    exchange: POST_EXCHANGE_NAME,
+    routing_key: "post.deleted",
+    legacy_queue: Some("post-deleted-user-interaction-queue"),
+};
+
+pub const USER_CREATED_QUEUE: QueueBinding = QueueBinding {
+    queue: "user-created-user-interaction-queue-v2",
+    exchange: USER_EXCHANGE_NAME,
+    routing_key: "user.created",
+    legacy_queue: Some("user-created-user-interaction-queue"),
+};
+
+pub const USER_DELETED_QUEUE: QueueBinding = QueueBinding {
+    queue: "user-deleted-user-interaction-queue-v2",
+    exchange: USER_EXCHANGE_NAME,
+    routing_key: "user.deleted",
+    legacy_queue: Some("user-deleted-user-interaction-queue"),
+};
+
+/// Everything user-interaction declares on RabbitMQ. Every queue must have a consumer,
+/// which `AmqClient::setup_listeners` verifies at startup. The `-v2` queues replace the ones
+/// declared before dead-lettering was added, see `zylo_common::amq::migrate_legacy_queue`.
+pub const TOPOLOGY: Topology = Topology {
+    exchanges: &[POST_EXCHANGE_NAME, USER_EXCHANGE_NAME, AUDIT_EXCHANGE_NAME],
+    queues: &[
+        POST_CREATED_QUEUE,
+        POST_DELETED_QUEUE,
+        USER_CREATED_QUEUE,
+        USER_DELETED_QUEUE,
+    ],
+};
@@ -1,40 +1,31 @@
 ﻿use crate::{errors, settings};
 use async_trait::async_trait;
 use redis::aio::MultiplexedConnection;
-use redis::{cmd, pipe, AsyncCommands, AsyncIter, Client, ExpireOption};
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use redis::{cmd, pipe, AsyncCommands, Client};
 use std::collections::HashMap;
+use ulid::Ulid;
 
 #[async_trait]
 pub trait CacheService: Send + Sync {
     async fn get_conn(&self) -> Result<MultiplexedConnection, errors::RedisError>;
 
-    async fn hfind<T: DeserializeOwned>(
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, errors::RedisError>;
+    async fn exists_many(
         &self,
-        key: &str,
-        pattern: &str,
-    ) -> Result<Option<T>, errors::RedisError>;
-
-    async fn hfind_keys(&self, key: &str, pattern: &str)
-        -> Result<Vec<String>, errors::RedisError>;
-
-    async fn hget<T: DeserializeOwned>(
-        &self,
-        key: &str,
-        field: &str,
-    ) -> Result<Option<T>, errors::RedisError>;
+        keys: &[String],
+    ) -> Result<HashMap<String, bool>, errors::RedisError>;
 
-    async fn hset<T: Serialize + Sync + Send>(
+    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError>;
+    /// Adds `element` to every HyperLogLog in `keys`, each set to expire at the paired unix
+    /// timestamp, atomically.
+    async fn pfadd_expire_at(
         &self,
-        key: &str,
-        field: &str,
-        value: &T,
+        keys: &[(String, i64)],
+        element: &str,
     ) -> Result<(), errors::RedisError>;
-
-    async fn hdel(&self, key: &str, fields: &[String]) -> Result<(), errors::RedisError>;
-
-    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError>;
+    /// Merges a HyperLogLog previously read with `get_raw` into `key`. Merging is a union, so
+    /// elements added to `key` in the meantime are kept.
+    async fn pfmerge_raw(&self, key: &str, dump: &[u8]) -> Result<(), errors::RedisError>;
     async fn pfcount(&self, key: &str) -> Result<u64, errors::RedisError>;
     async fn pfcount_many(
         &self,
@@ -43,10 +34,24 @@ pub trait CacheService: Send + Sync {
 
     async fn sadd(&self, key: &str, member: &str) -> Result<bool, errors::RedisError>;
     async fn srem(&self, key: &str, member: &str) -> Result<bool, errors::RedisError>;
-    async fn scard(&self, key: &str) -> Result<u64, errors::RedisError>;
+    /// Adds `member` to the set at `key` and removes it from the sets at `others`, atomically.
+    /// Returns whether `member` was added to `key`.
+    async fn sadd_exclusive(
+        &self,
+        key: &str,
+        others: &[String],
+        member: &str,
+    ) -> Result<bool, errors::RedisError>;
+    /// Adds the members of every set in `sets` and sets `marker`, atomically.
+    async fn fill_sets(
+        &self,
+        sets: &[(String, Vec<String>)],
+        marker: &str,
+    ) -> Result<(), errors::RedisError>;
+    async fn spop_many(&self, key: &str, count: usize) -> Result<Vec<String>, errors::RedisError>;
+    async fn smembers(&self, key: &str) -> Result<Vec<String>, errors::RedisError>;
     async fn scard_many(&self, keys: &[String])
         -> Result<HashMap<String, u64>, errors::RedisError>;
-    async fn sismember(&self, key: &str, member: &str) -> Result<bool, errors::RedisError>;
     async fn sismember_many(
         &self,
         keys: &[String],
@@ -58,7 +63,6 @@ pub trait CacheService: Send + Sync {
 
 pub struct RedisCacheService {
     redis: Client,
-    config: settings::Redis,
 }
 
 impl RedisCacheService {
@@ -66,7 +70,7 @@ impl RedisCacheService {
         let redis = Client::open(config.uri.to_string())
             .map_err(|e| errors::redis_op_error("CONNECTION", "N/A", e))?;
 
-        Ok(Self { redis, config })
+        Ok(Self { redis })
     }
 }
 
@@ -79,96 +83,75 @@ impl CacheService for RedisCacheService {
             .map_err(|e| errors::redis_op_error("CONNECT", "N/A", e))
     }
 
-    async fn hfind<T: DeserializeOwned>(
-        &self,
-        key: &str,
-        pattern: &str,
-    ) -> Result<Option<T>, errors::RedisError> {
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, errors::RedisError> {
         let mut conn = self.get_conn().await?;
-        let mut iter: AsyncIter<String> = conn
-            .hscan_match(key, pattern)
-            .await
-            .map_err(|e| errors::redis_op_error("SCAN", key, e))?;
-
-        while let Some(value) = iter.next_item().await {
-            if let Ok(result) = serde_json::from_str(&value) {
-                return Ok(Some(result));
-            }
-        }
 
-        Ok(None)
+        conn.get(key)
+            .await
+            .map_err(|e| errors::redis_op_error("GET", key, e))
     }
 
-    async fn hfind_keys(
+    async fn exists_many(
         &self,
-        key: &str,
-        pattern: &str,
-    ) -> Result<Vec<String>, errors::RedisError> {
+        keys: &[String],
+    ) -> Result<HashMap<String, bool>, errors::RedisError> {
         let mut conn = self.get_conn().await?;
-        let keys: Vec<String> = cmd("HSCAN")
-            .arg(key)
-            .arg(pattern)
-            .arg("NOVALUES")
+        let mut pipe = pipe();
+
+        for key in keys {
+            pipe.exists(key);
+        }
+
+        let results: Vec<bool> = pipe
             .query_async(&mut conn)
             .await
-            .map_err(|e| errors::redis_op_error("HSCAN", key, e))?;
+            .map_err(|e| errors::redis_op_error("PIPE EXISTS", "multiple", e))?;
 
-        Ok(keys)
+        Ok(keys.iter().cloned().zip(results).collect())
     }
 
-    async fn hget<T: DeserializeOwned>(
-        &self,
-        key: &str,
-        field: &str,
-    ) -> Result<Option<T>, errors::RedisError> {
+    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError> {
         let mut conn = self.get_conn().await?;
 
-        let cached_result: Option<String> = conn
-            .hget(key, field)
+        conn.pfadd(key, element)
             .await
-            .map_err(|e| errors::redis_op_error("HGET", key, e))?;
-
-        let cached_result = match cached_result {
-            None => return Ok(None),
-            Some(cache) => cache,
-        };
-
-        Ok(serde_json::from_str(&cached_result)?)
+            .map_err(|e| errors::redis_op_error("PFADD", key, e))
     }
 
-    async fn hset<T: Serialize + Sync + Send>(
+    async fn pfadd_expire_at(
         &self,
-        key: &str,
-        field: &str,
-        value: &T,
+        keys: &[(String, i64)],
+        element: &str,
     ) -> Result<(), errors::RedisError> {
         let mut conn = self.get_conn().await?;
+        let mut pipe = pipe();
 
-        conn.hset::<_, _, _, ()>(key, field, serde_json::to_string(value)?)
-            .await
-            .map_err(|e| errors::redis_op_error("HSET", key, e))?;
-
-        conn.hexpire::<_, _, ()>(key, self.config.expire_time as i64, ExpireOption::GT, field)
-            .await
-            .map_err(|e| errors::redis_op_error("HEXPIRE", key, e))?;
-
-        Ok(())
-    }
-
-    async fn hdel(&self, key: &str, fields: &[String]) -> Result<(), errors::RedisError> {
-        let mut conn = self.get_conn().await?;
+        pipe.atomic();
+        for (key, expire_at) in keys {
+            pipe.pfadd(key, element).ignore();
+            pipe.expire_at(key, *expire_at).ignore();
+        }
 
-        conn.hdel::<_, _, ()>(key, fields)
+        pipe.query_async::<()>(&mut conn)
             .await
-            .map_err(|e| errors::redis_op_error("HDEL", key, e))
+            .map_err(|e| errors::redis_op_error("PIPE PFADD", "multiple", e))
     }
 
-    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError> {
+    async fn pfmerge_raw(&self, key: &str, dump: &[u8]) -> Result<(), errors::RedisError> {
         let mut conn = self.get_conn().await?;
+        let restore_key = format!("{}:restore:{}", key, Ulid::new());
 
-        conn.pfadd(key, element)
+        pipe()
+            .atomic()
+            .set(&restore_key, dump)
+            .ignore()
+            .pfmerge(key, &restore_key)
+            .ignore()
+            .del(&restore_key)
+            .ignore()
+            .query_async::<()>(&mut conn)
             .await
-            .map_err(|e| errors::redis_op_error("PFADD", key, e))
+            .map_err(|e| errors::redis_op_error("PFMERGE", key, e))
     }
 
     async fn pfcount(&self, key: &str) -> Result<u64, errors::RedisError> {
@@ -217,12 +200,67 @@ impl CacheService for RedisCacheService {
             .map_err(|e| errors::redis_op_error("SREM", key, e))
     }
 
-    async fn scard(&self, key: &str) -> Result<u64, errors::RedisError> {
+    async fn sadd_exclusive(
+        &self,
+        key: &str,
+        others: &[String],
+        member: &str,
+    ) -> Result<bool, errors::RedisError> {
+        let mut conn = self.get_conn().await?;
+        let mut pipe = pipe();
+
+        pipe.atomic();
+        for other in others {
+            pipe.srem(other, member).ignore();
+        }
+        pipe.sadd(key, member);
+
+        let (added,): (bool,) = pipe
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| errors::redis_op_error("PIPE SADD", key, e))?;
+
+        Ok(added)
+    }
+
+    async fn fill_sets(
+        &self,
+        sets: &[(String, Vec<String>)],
+        marker: &str,
+    ) -> Result<(), errors::RedisError> {
         let mut conn = self.get_conn().await?;
+        let mut pipe = pipe();
+
+        pipe.atomic();
+        for (key, members) in sets {
+            if !members.is_empty() {
+                pipe.sadd(key, members).ignore();
+            }
+        }
+        pipe.set(marker, 1).ignore();
 
-        conn.scard(key)
+        pipe.query_async::<()>(&mut conn)
             .await
-            .map_err(|e| errors::redis_op_error("SCARD", key, e))
+            .map_err(|e| errors::redis_op_error("PIPE SADD", marker, e))
+    }
+
+    async fn spop_many(&self, key: &str, count: usize) -> Result<Vec<String>, errors::RedisError> {
+        let mut conn = self.get_conn().await?;
+
+        cmd("SPOP")
+            .arg(key)
+            .arg(count)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| errors::redis_op_error("SPOP", key, e))
+    }
+
+    async fn smembers(&self, key: &str) -> Result<Vec<String>, errors::RedisError> {
+        let mut conn = self.get_conn().await?;
+
+        conn.smembers(key)
+            .await
+            .map_err(|e| errors::redis_op_error("SMEMBERS", key, e))
     }
 
     async fn scard_many(
@@ -250,13 +288,6 @@ impl CacheService for RedisCacheService {
         Ok(map)
     }
 
-    async fn sismember(&self, key: &str, member: &str) -> Result<bool, errors::RedisError> {
-        let mut conn = self.get_conn().await?;
-        conn.sismember(key, member)
-            .await
-            .map_err(|e| errors::redis_op_error("SISMEMBER", key, e))
-    }
-
     async fn sismember_many(
         &self,
         keys: &[String],
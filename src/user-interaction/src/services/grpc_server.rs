@@ -1,42 +1,28 @@
-use crate::models::reply::{PostInteractionResponse, ReplyResponse};
+use crate::models::like::{LikePageRequest, LikePageResponse, LikeResponse};
+use crate::models::reply::{PostInteractionResponse, ReplyPageRequest, ReplyResponse, ReplySort};
 use crate::services::grpc_server::reply_server::reply_service_server::ReplyService as GrpcReplyService;
-use crate::services::grpc_server::reply_server::{BatchOfPostInteractionsResponse, GetBatchOfPostInteractionsRequest, GetPostInteractionsRequest, GetReplyByIdRequest, PostInteractionsResponse as GrpcPostInteractionsResponse, ReplyResponse as GrpcReplyResponse};
+use crate::services::grpc_server::reply_server::{BatchOfPostInteractionsResponse, GetBatchOfPostInteractionsRequest, GetLikesRequest, GetPostInteractionsRequest, GetReplyByIdRequest, LikeResponse as GrpcLikeResponse, LikesResponse, PostInteractionsResponse as GrpcPostInteractionsResponse, ReplyResponse as GrpcReplyResponse, ReplySort as GrpcReplySort};
 use crate::services::post_interactions_service::PostInteractionsService;
 use crate::services::reply_service::ReplyService;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::str::FromStr;
 use std::sync::Arc;
-use opentelemetry::propagation::Extractor;
 use tonic::{Request, Response, Status};
 use ulid::Ulid;
 
 pub mod reply_server {
     tonic::include_proto!("reply_server");
 }
-pub struct MetadataMap<'a>(pub &'a tonic::metadata::MetadataMap);
-
-impl Extractor for MetadataMap<'_> {
-    fn get(&self, key: &str) -> Option<&str> {
-        self.0.get(key).and_then(|metadata| metadata.to_str().ok())
-    }
-
-    fn keys(&self) -> Vec<&str> {
-        self.0
-            .keys()
-            .map(|key| match key {
-                tonic::metadata::KeyRef::Ascii(v) => v.as_str(),
-                tonic::metadata::KeyRef::Binary(v) => v.as_str(),
-            })
-            .collect::<Vec<_>>()
-    }
-}
 
 impl From<ReplyResponse> for GrpcReplyResponse {
     fn from(reply_response: ReplyResponse) -> Self {
         Self {
             id: reply_response.id.to_string(),
             content: reply_response.content,
-            user_id: reply_response.user_id.to_string(),
+            user_id: reply_response
+                .user_id
+                .map(|user_id| user_id.to_string())
+                .unwrap_or_default(),
             reply_to_id: reply_response.reply_to_id.to_string(),
             created_at: reply_response
                 .created_at
@@ -50,6 +36,10 @@ impl From<ReplyResponse> for GrpcReplyResponse {
             likes: reply_response.likes,
             views: reply_response.views,
             user_interacted: reply_response.user_interacted.unwrap_or(false),
+            has_more_children: reply_response.has_more_children,
+            deleted: reply_response.deleted,
+            reactions: reply_response.reactions,
+            user_reaction: reply_response.user_reaction.unwrap_or_default(),
         }
     }
 }
@@ -62,6 +52,64 @@ impl From<PostInteractionResponse> for GrpcPostInteractionsResponse {
             likes: value.likes,
             views: value.views,
             user_interacted: value.user_interacted.unwrap_or_default(),
+            next_cursor: value.next_cursor.unwrap_or_default(),
+            reactions: value.reactions,
+            user_reaction: value.user_reaction.unwrap_or_default(),
+            bookmarked: value.bookmarked.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<GrpcReplySort> for ReplySort {
+    fn from(sort: GrpcReplySort) -> Self {
+        match sort {
+            GrpcReplySort::Oldest => ReplySort::Oldest,
+            GrpcReplySort::Newest => ReplySort::Newest,
+            GrpcReplySort::MostLiked => ReplySort::MostLiked,
+        }
+    }
+}
+
+impl From<&GetPostInteractionsRequest> for ReplyPageRequest {
+    fn from(request: &GetPostInteractionsRequest) -> Self {
+        let non_zero = |value: u32| (value > 0).then_some(value);
+
+        Self {
+            cursor: (!request.cursor.is_empty()).then(|| request.cursor.clone()),
+            limit: non_zero(request.limit),
+            sort: request.sort().into(),
+            depth: non_zero(request.max_depth),
+            children: non_zero(request.max_children),
+        }
+    }
+}
+
+impl From<LikeResponse> for GrpcLikeResponse {
+    fn from(like: LikeResponse) -> Self {
+        Self {
+            user_id: like.user_id.to_string(),
+            post_id: like.post_id.to_string(),
+            liked_at: DateTime::parse_from_rfc3339(&like.liked_at)
+                .map(|liked_at| liked_at.timestamp())
+                .unwrap_or(Utc::now().timestamp()),
+        }
+    }
+}
+
+impl From<LikePageResponse> for LikesResponse {
+    fn from(page: LikePageResponse) -> Self {
+        Self {
+            likes: page.likes.into_iter().map(GrpcLikeResponse::from).collect(),
+            next: page.next.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&GetLikesRequest> for LikePageRequest {
+    fn from(request: &GetLikesRequest) -> Self {
+        Self {
+            next: (!request.next.is_empty()).then(|| request.next.clone()),
+            limit: (request.limit > 0).then_some(request.limit),
         }
     }
 }
@@ -132,10 +180,12 @@ where
 
         let interaction_user_id: Option<Ulid> =
             Ulid::from_str(&inner_request.interaction_user_id).ok();
-        
+        let viewer_id: Option<Ulid> = Ulid::from_str(&inner_request.viewer_id).ok();
+        let page = ReplyPageRequest::from(&inner_request);
+
         let response = self
             .post_interactions_service
-            .get_post_interactions(post_id, interaction_user_id)
+            .get_post_interactions(post_id, &page, interaction_user_id, viewer_id)
             .await?;
 
         Ok(Response::new(GrpcPostInteractionsResponse::from(response)))
@@ -149,20 +199,55 @@ where
         let posts_ids: Vec<Ulid> = inner_request
             .posts_ids
             .iter()
-            .map(|id| Ulid::from_str(id).map_err(|e| Status::invalid_argument(e.to_string())))
+            .map(|id| Ulid::from_str(id))
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| Status::internal(e.to_string()))?;
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
 
         let interaction_user_id: Option<Ulid> =
             Ulid::from_str(&inner_request.interaction_user_id).ok();
+        let viewer_id: Option<Ulid> = Ulid::from_str(&inner_request.viewer_id).ok();
 
         let replies = self
             .post_interactions_service
-            .get_posts_interactions(&posts_ids, interaction_user_id)
+            .get_posts_interactions(&posts_ids, interaction_user_id, viewer_id)
             .await?;
 
             Ok(Response::new(BatchOfPostInteractionsResponse {
                 posts_interactions: replies.into_iter().map(GrpcPostInteractionsResponse::from).collect(),
             }))
         }
+
+    async fn get_post_likes(
+        &self,
+        request: Request<GetLikesRequest>,
+    ) -> Result<Response<LikesResponse>, Status> {
+        let inner_request = request.into_inner();
+
+        let post_id = Ulid::from_str(inner_request.id.as_str())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let response = self
+            .post_interactions_service
+            .get_post_likes(post_id, &LikePageRequest::from(&inner_request))
+            .await?;
+
+        Ok(Response::new(LikesResponse::from(response)))
+    }
+
+    async fn get_user_liked_posts(
+        &self,
+        request: Request<GetLikesRequest>,
+    ) -> Result<Response<LikesResponse>, Status> {
+        let inner_request = request.into_inner();
+
+        let user_id = Ulid::from_str(inner_request.id.as_str())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let response = self
+            .post_interactions_service
+            .get_user_liked_posts(user_id, &LikePageRequest::from(&inner_request))
+            .await?;
+
+        Ok(Response::new(LikesResponse::from(response)))
+    }
     }
\ No newline at end of file
@@ -1,5 +1,5 @@
 ﻿pub mod amq_client;
-pub mod key_vault;
+pub mod amq_topology;
 pub mod grpc_server;
 pub mod cache_service;
 pub mod post_interactions_service;
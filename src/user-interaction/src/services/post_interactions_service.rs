@@ -1,85 +1,196 @@
 ﻿use crate::errors;
-use crate::models::reply::PostInteractionResponse;
+use crate::models::analytics::{AnalyticsRequest, PostAnalyticsResponse};
+use crate::models::bookmark::{BookmarkCursor, BookmarkPageRequest, BookmarkPageResponse};
+use crate::models::like::{Like, LikeCursor, LikePageRequest, LikePageResponse};
+use crate::models::reply::{PostInteractionResponse, ReplyPageRequest};
+use crate::repositories::bookmarks_repo::BookmarksRepository;
 use crate::repositories::interaction_repo::InteractionRepository;
+use crate::repositories::posts_repo::PostsRepository;
 use crate::services::reply_service::ReplyService;
+use crate::utils::constants::{
+    BOOKMARKS_MAX_PAGE_SIZE, BOOKMARKS_PAGE_SIZE, LIKES_MAX_PAGE_SIZE, LIKES_PAGE_SIZE,
+    LIKE_REACTION,
+};
 use crate::utils::helpers::PostInteractionResponseBuilder;
 use async_trait::async_trait;
+use chrono::Utc;
 use std::sync::Arc;
 use ulid::Ulid;
 
 #[async_trait]
 pub trait PostInteractionsService: Send + Sync {
+    /// Reactions are reported for `user_id` while bookmarks, being private, are only reported
+    /// for `viewer_id`, the signed in user.
     async fn get_post_interactions(
         &self,
         post_id: Ulid,
+        page: &ReplyPageRequest,
         user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<PostInteractionResponse, errors::AppError>;
     async fn get_posts_interactions(
         &self,
         posts_ids: &[Ulid],
         user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<Vec<PostInteractionResponse>, errors::AppError>;
+    /// Users who liked a post, most recent like first.
+    async fn get_post_likes(
+        &self,
+        post_id: Ulid,
+        page: &LikePageRequest,
+    ) -> Result<LikePageResponse, errors::AppError>;
+    /// Posts a user liked, most recent like first.
+    async fn get_user_liked_posts(
+        &self,
+        user_id: Ulid,
+        page: &LikePageRequest,
+    ) -> Result<LikePageResponse, errors::AppError>;
+    /// Bookmarks a post for a user, returning false if it was already bookmarked.
+    async fn bookmark(&self, user_id: Ulid, post_id: Ulid) -> Result<bool, errors::AppError>;
+    /// Removes a bookmark, returning false if the post was not bookmarked.
+    async fn remove_bookmark(&self, user_id: Ulid, post_id: Ulid)
+        -> Result<bool, errors::AppError>;
+    /// Posts a user bookmarked, most recent bookmark first.
+    async fn get_bookmarks(
+        &self,
+        user_id: Ulid,
+        page: &BookmarkPageRequest,
+    ) -> Result<BookmarkPageResponse, errors::AppError>;
+    /// Unique views and likes of a post over time. Only the author of the post may see them.
+    async fn get_post_analytics(
+        &self,
+        post_id: Ulid,
+        user_id: Ulid,
+        request: &AnalyticsRequest,
+    ) -> Result<PostAnalyticsResponse, errors::AppError>;
 }
 
-pub struct PostInteractionsServiceImpl<RS, I>
+pub struct PostInteractionsServiceImpl<RS, I, B, P>
 where
     RS: ReplyService + 'static,
     I: InteractionRepository + 'static,
+    B: BookmarksRepository + 'static,
+    P: PostsRepository + 'static,
 {
     reply_service: Arc<RS>,
     interaction_repo: Arc<I>,
+    bookmarks_repo: Arc<B>,
+    posts_repo: Arc<P>,
 }
 
-impl<RS, I> PostInteractionsServiceImpl<RS, I>
+impl<RS, I, B, P> PostInteractionsServiceImpl<RS, I, B, P>
 where
 RS: ReplyService + 'static,
 I: InteractionRepository + 'static,
+B: BookmarksRepository + 'static,
+P: PostsRepository + 'static,
 {
-    pub fn new(reply_service: Arc<RS>, interaction_repo: Arc<I>) -> Self {
-        Self { reply_service, interaction_repo }
+    pub fn new(
+        reply_service: Arc<RS>,
+        interaction_repo: Arc<I>,
+        bookmarks_repo: Arc<B>,
+        posts_repo: Arc<P>,
+    ) -> Self {
+        Self {
+            reply_service,
+            interaction_repo,
+            bookmarks_repo,
+            posts_repo,
+        }
+    }
+}
+
+/// Decodes the page cursor and clamps the page size. Callers fetch one like more than the page
+/// size so they can tell whether another page follows.
+fn like_page_bounds(page: &LikePageRequest) -> Result<(Option<LikeCursor>, u32), errors::AppError> {
+    let after = page.next.as_deref().map(LikeCursor::decode).transpose()?;
+    let limit = page
+        .limit
+        .unwrap_or(LIKES_PAGE_SIZE)
+        .clamp(1, LIKES_MAX_PAGE_SIZE);
+
+    Ok((after, limit))
+}
+
+/// Trims the extra like fetched past the page size and builds the cursor for the next page
+/// from the last like kept. `cursor_id` picks the side of the like the query orders by.
+fn like_page(mut likes: Vec<Like>, limit: u32, cursor_id: fn(&Like) -> Ulid) -> LikePageResponse {
+    let next = if likes.len() > limit as usize {
+        likes.truncate(limit as usize);
+        likes.last().map(|like| {
+            LikeCursor {
+                liked_at: like.liked_at,
+                id: cursor_id(like),
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    LikePageResponse {
+        likes: likes.into_iter().map(Into::into).collect(),
+        next,
     }
 }
 
 #[async_trait]
-impl<RS, I> PostInteractionsService for PostInteractionsServiceImpl<RS, I>
+impl<RS, I, B, P> PostInteractionsService for PostInteractionsServiceImpl<RS, I, B, P>
 where
     RS: ReplyService + 'static,
     I: InteractionRepository + 'static,
+    B: BookmarksRepository + 'static,
+    P: PostsRepository + 'static,
 {
     async fn get_post_interactions(
         &self,
         post_id: Ulid,
+        page: &ReplyPageRequest,
         user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<PostInteractionResponse, errors::AppError> {
         let replies = self
             .reply_service
-            .get_replies_from_post(&post_id, user_id)
+            .get_reply_page(&post_id, &post_id, page, user_id)
             .await?;
 
         let post_id_string = post_id.to_string();
-        let user_interacted_on_post = match user_id {
-            Some(uid) => {
-                let is_liked = self
-                    .interaction_repo
-                    .is_liked(&post_id_string, &uid)
-                    .await?;
-
-                Ok::<Option<bool>, errors::RedisError>(Some(is_liked))
-            }
-            None => Ok(None),
-        }?;
+        let post_keys = [post_id_string.clone()];
+        let user_reaction = match user_id {
+            Some(uid) => self
+                .interaction_repo
+                .get_many_user_reactions(&post_keys, &uid)
+                .await?
+                .remove(&post_id_string),
+            None => None,
+        };
+        let bookmarked = match viewer_id {
+            Some(viewer_id) => Some(
+                self.bookmarks_repo
+                    .get_bookmarked(&viewer_id, &[post_id])
+                    .await?
+                    .contains(&post_id),
+            ),
+            None => None,
+        };
 
-        let (post_likes, post_views) = tokio::try_join!(
-            self.interaction_repo.get_likes(&post_id_string),
+        let (mut post_reactions, post_views) = tokio::try_join!(
+            self.interaction_repo.get_many_reactions(&post_keys),
             self.interaction_repo.get_views(&post_id_string)
         )?;
+        let post_reactions = post_reactions.remove(&post_id_string).unwrap_or_default();
 
         let response = PostInteractionResponseBuilder::new()
             .post_id(post_id)
-            .replies(replies)
-            .likes(post_likes)
+            .replies(replies.replies)
+            .next_cursor(replies.next_cursor)
+            .likes(post_reactions.get(LIKE_REACTION).copied().unwrap_or(0))
             .views(post_views)
-            .user_interacted(user_interacted_on_post)
+            .user_interacted(user_id.map(|_| user_reaction.as_deref() == Some(LIKE_REACTION)))
+            .reactions(post_reactions)
+            .user_reaction(user_reaction)
+            .bookmarked(bookmarked)
             .build();
 
         Ok(response)
@@ -89,6 +200,7 @@ where
         &self,
         posts_ids: &[Ulid],
         user_id: Option<Ulid>,
+        viewer_id: Option<Ulid>,
     ) -> Result<Vec<PostInteractionResponse>, errors::AppError> {
         let replies_map = self
             .reply_service
@@ -100,13 +212,21 @@ where
             .map(|id| id.to_string())
             .collect::<Vec<_>>();
 
-        let (posts_likes, posts_views) = tokio::try_join!(
-            self.interaction_repo.get_many_likes(&posts_ids_string),
+        let (mut posts_reactions, posts_views) = tokio::try_join!(
+            self.interaction_repo.get_many_reactions(&posts_ids_string),
             self.interaction_repo.get_many_views(&posts_ids_string)
         )?;
 
-        let is_liked_map = match user_id {
-            Some(uid) => Some(self.interaction_repo.is_many_liked(&posts_ids_string, &uid).await?),
+        let user_reactions_map = match user_id {
+            Some(uid) => Some(self.interaction_repo.get_many_user_reactions(&posts_ids_string, &uid).await?),
+            None => None,
+        };
+        let bookmarked_posts = match viewer_id {
+            Some(viewer_id) => Some(
+                self.bookmarks_repo
+                    .get_bookmarked(&viewer_id, posts_ids)
+                    .await?,
+            ),
             None => None,
         };
 
@@ -114,18 +234,28 @@ where
         for post_id in posts_ids {
             let post_id_string = post_id.to_string();
             let replies = replies_map.get(post_id).cloned();
-            let user_interacted = is_liked_map
+            let user_reaction = user_reactions_map
                 .as_ref()
-                .and_then(|map| map.get(&post_id_string).copied());
+                .and_then(|map| map.get(&post_id_string).cloned());
+            let user_interacted = user_reactions_map
+                .as_ref()
+                .map(|_| user_reaction.as_deref() == Some(LIKE_REACTION));
+            let bookmarked = bookmarked_posts
+                .as_ref()
+                .map(|bookmarked| bookmarked.contains(post_id));
             
-            let likes = posts_likes.get(&post_id_string).copied().unwrap_or_default();
+            let reactions = posts_reactions.remove(&post_id_string).unwrap_or_default();
+            let likes = reactions.get(LIKE_REACTION).copied().unwrap_or_default();
             let views = posts_views.get(&post_id_string).copied().unwrap_or_default();
 
             let mut builder = PostInteractionResponseBuilder::new()
                 .post_id(*post_id)
                 .likes(likes)
                 .views(views)
-                .user_interacted(user_interacted);
+                .user_interacted(user_interacted)
+                .reactions(reactions)
+                .user_reaction(user_reaction)
+                .bookmarked(bookmarked);
 
             if let Some(replies) = replies {
                 builder = builder.replies(replies);
@@ -137,4 +267,114 @@ where
 
         Ok(responses)
     }
+
+    async fn get_post_likes(
+        &self,
+        post_id: Ulid,
+        page: &LikePageRequest,
+    ) -> Result<LikePageResponse, errors::AppError> {
+        let (after, limit) = like_page_bounds(page)?;
+        let likes = self
+            .interaction_repo
+            .get_likers(&post_id.to_string(), after, i64::from(limit) + 1)
+            .await?;
+
+        Ok(like_page(likes, limit, |like| like.user_id))
+    }
+
+    async fn get_user_liked_posts(
+        &self,
+        user_id: Ulid,
+        page: &LikePageRequest,
+    ) -> Result<LikePageResponse, errors::AppError> {
+        let (after, limit) = like_page_bounds(page)?;
+        let likes = self
+            .interaction_repo
+            .get_liked_posts(&user_id, after, i64::from(limit) + 1)
+            .await?;
+
+        Ok(like_page(likes, limit, |like| like.entity_id))
+    }
+
+    async fn bookmark(&self, user_id: Ulid, post_id: Ulid) -> Result<bool, errors::AppError> {
+        Ok(self.bookmarks_repo.create(&user_id, &post_id).await?)
+    }
+
+    async fn remove_bookmark(
+        &self,
+        user_id: Ulid,
+        post_id: Ulid,
+    ) -> Result<bool, errors::AppError> {
+        Ok(self.bookmarks_repo.delete(&user_id, &post_id).await?)
+    }
+
+    async fn get_bookmarks(
+        &self,
+        user_id: Ulid,
+        page: &BookmarkPageRequest,
+    ) -> Result<BookmarkPageResponse, errors::AppError> {
+        let after = page
+            .next
+            .as_deref()
+            .map(BookmarkCursor::decode)
+            .transpose()?;
+        let limit = page
+            .limit
+            .unwrap_or(BOOKMARKS_PAGE_SIZE)
+            .clamp(1, BOOKMARKS_MAX_PAGE_SIZE);
+
+        let mut bookmarks = self
+            .bookmarks_repo
+            .get_page(&user_id, after, i64::from(limit) + 1)
+            .await?;
+
+        let next = if bookmarks.len() > limit as usize {
+            bookmarks.truncate(limit as usize);
+            bookmarks.last().map(|bookmark| {
+                BookmarkCursor {
+                    created_at: bookmark.created_at,
+                    post_id: bookmark.post_id,
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+
+        Ok(BookmarkPageResponse {
+            bookmarks: bookmarks.into_iter().map(Into::into).collect(),
+            next,
+        })
+    }
+
+    async fn get_post_analytics(
+        &self,
+        post_id: Ulid,
+        user_id: Ulid,
+        request: &AnalyticsRequest,
+    ) -> Result<PostAnalyticsResponse, errors::AppError> {
+        let author = self
+            .posts_repo
+            .get_author(&post_id)
+            .await?
+            .ok_or(errors::ValidationError::PostNotFound)?;
+
+        if author != user_id {
+            return Err(errors::AppError::Forbidden(String::from(
+                "Only the author of a post can see its analytics",
+            )));
+        }
+
+        let buckets = request.buckets(Utc::now().naive_utc())?;
+        let analytics = self
+            .interaction_repo
+            .get_analytics(&post_id.to_string(), request.granularity, &buckets)
+            .await?;
+
+        Ok(PostAnalyticsResponse {
+            post_id,
+            granularity: request.granularity,
+            buckets: analytics.into_iter().map(Into::into).collect(),
+        })
+    }
 }
@@ -1,21 +1,33 @@
 ﻿use crate::errors;
-use crate::models::reply::ReplyResponse;
+use crate::models::reply::{
+    ReplyActor, ReplyCursor, ReplyPageRequest, ReplyPageResponse, ReplyResponse, ReplySort,
+};
 use crate::models::Finalizer;
 use crate::repositories::interaction_repo::InteractionRepository;
 use crate::repositories::reply_repo::ReplyRepository;
-use crate::utils::helpers::map_nested;
+use crate::settings::ReplyThreads;
+use crate::utils::constants::{
+    LIKE_REACTION, REPLY_COMPACTION_BATCH_SIZE, REPLY_COMPACTION_INTERVAL_SECONDS,
+};
+use crate::utils::helpers::{build_threads, map_nested};
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
 use ulid::Ulid;
 
 #[async_trait]
 pub trait ReplyService: Send + Sync + Finalizer {
-    async fn get_replies_from_post(
+    /// Returns one page of the direct replies to `parent_id`, which is either the post itself or
+    /// one of its replies, each with a truncated thread of its own replies.
+    async fn get_reply_page(
         &self,
         post_id: &Ulid,
+        parent_id: &Ulid,
+        page: &ReplyPageRequest,
         user_id: Option<Ulid>,
-    ) -> Result<Vec<ReplyResponse>, errors::AppError>;
+    ) -> Result<ReplyPageResponse, errors::AppError>;
     async fn get_replies_from_posts(
         &self,
         posts_ids: &[Ulid],
@@ -37,23 +49,59 @@ pub trait ReplyService: Send + Sync + Finalizer {
         &self,
         reply_id: &Ulid,
         content: &str,
+        actor: &ReplyActor,
     ) -> Result<ReplyResponse, errors::AppError>;
-    async fn delete(&self, reply_id: &Ulid) -> Result<(), errors::AppError>;
+    async fn delete(&self, reply_id: &Ulid, actor: &ReplyActor) -> Result<(), errors::AppError>;
 }
 
 pub struct ReplyServiceImpl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> {
     reply_repo: Arc<R>,
     interaction_repo: Arc<I>,
+    threads: ReplyThreads,
 }
 
 impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> ReplyServiceImpl<R, I> {
-    pub fn new(reply_repo: Arc<R>, interaction_repo: Arc<I>) -> Self {
+    pub fn new(reply_repo: Arc<R>, interaction_repo: Arc<I>, threads: ReplyThreads) -> Self {
         Self {
             reply_repo,
             interaction_repo,
+            threads,
         }
     }
 
+    /// Periodically removes tombstones whose threads have been emptied. Removing one can leave
+    /// its tombstoned parent without replies, so each run repeats until nothing is left to remove.
+    pub fn spawn_compaction(self: &Arc<Self>) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(REPLY_COMPACTION_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+
+                let mut removed = 0;
+                loop {
+                    match service
+                        .reply_repo
+                        .compact_tombstones(REPLY_COMPACTION_BATCH_SIZE)
+                        .await
+                    {
+                        Ok(0) => break,
+                        Ok(deleted) => removed += deleted,
+                        Err(e) => {
+                            error!("Failed to compact reply tombstones: {:?}", e);
+                            break;
+                        }
+                    }
+                }
+
+                if removed > 0 {
+                    info!("Removed {} reply tombstones", removed);
+                }
+            }
+        });
+    }
+
     async fn populate_interactions(
         &self,
         replies: &mut [ReplyResponse],
@@ -61,14 +109,14 @@ impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> ReplyServ
     ) -> Result<(), errors::AppError> {
         let reply_ids: Vec<String> = replies.iter().map(|r| r.id.to_string()).collect();
 
-        let (likes, views) = tokio::try_join!(
-            self.interaction_repo.get_many_likes(&reply_ids),
+        let (mut reactions, views) = tokio::try_join!(
+            self.interaction_repo.get_many_reactions(&reply_ids),
             self.interaction_repo.get_many_views(&reply_ids)
         )?;
 
-        let user_interactions = if let Some(uid) = user_id {
+        let user_reactions = if let Some(uid) = user_id {
             self.interaction_repo
-                .is_many_liked(&reply_ids, &uid)
+                .get_many_user_reactions(&reply_ids, &uid)
                 .await?
         } else {
             HashMap::default()
@@ -77,11 +125,13 @@ impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> ReplyServ
         for reply in replies.iter_mut() {
             let reply_id = reply.id.to_string();
 
-            reply.likes = likes.get(&reply_id).copied().unwrap_or(0);
+            reply.reactions = reactions.remove(&reply_id).unwrap_or_default();
+            reply.likes = reply.reactions.get(LIKE_REACTION).copied().unwrap_or(0);
             reply.views = views.get(&reply_id).copied().unwrap_or(0);
 
-            if !user_interactions.is_empty() {
-                reply.user_interacted = user_interactions.get(&reply.id.to_string()).copied();
+            if user_id.is_some() {
+                reply.user_reaction = user_reactions.get(&reply_id).cloned();
+                reply.user_interacted = Some(reply.user_reaction.as_deref() == Some(LIKE_REACTION));
             }
         }
 
@@ -102,24 +152,81 @@ impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> Finalizer
 impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> ReplyService
     for ReplyServiceImpl<R, I>
 {
-    async fn get_replies_from_post(
+    async fn get_reply_page(
         &self,
         post_id: &Ulid,
+        parent_id: &Ulid,
+        page: &ReplyPageRequest,
         user_id: Option<Ulid>,
-    ) -> Result<Vec<ReplyResponse>, errors::AppError> {
-        let mut replies: Vec<ReplyResponse> = self
+    ) -> Result<ReplyPageResponse, errors::AppError> {
+        let limit = page
+            .limit
+            .unwrap_or(self.threads.page_size)
+            .clamp(1, self.threads.max_page_size.max(1)) as usize;
+        let depth = page
+            .depth
+            .unwrap_or(self.threads.max_depth)
+            .clamp(1, self.threads.max_depth.max(1));
+        let children = page
+            .children
+            .unwrap_or(self.threads.max_children)
+            .min(self.threads.max_children);
+
+        let after = page
+            .cursor
+            .as_deref()
+            .map(|cursor| ReplyCursor::decode(cursor, page.sort))
+            .transpose()?;
+
+        let mut positions = match page.sort {
+            ReplySort::Oldest | ReplySort::Newest => {
+                let newest_first = page.sort == ReplySort::Newest;
+
+                self.reply_repo
+                    .get_children_page(post_id, parent_id, newest_first, after, limit as i64 + 1)
+                    .await?
+                    .iter()
+                    .map(|reply| ReplyCursor::from_created_at(&reply.created_at, reply.id))
+                    .collect::<Vec<_>>()
+            }
+            ReplySort::MostLiked => {
+                self.reply_repo
+                    .get_most_liked_children(post_id, parent_id, after, limit as i64 + 1)
+                    .await?
+            }
+        };
+
+        let next_cursor = if positions.len() > limit {
+            positions.truncate(limit);
+            positions.last().map(|last| last.encode(page.sort))
+        } else {
+            None
+        };
+
+        let root_ids: Vec<Ulid> = positions.iter().map(|position| position.id).collect();
+        let mut thread = self
             .reply_repo
-            .get_all_from_post(post_id)
-            .await?
+            .get_threads(&root_ids, depth, children)
+            .await?;
+        thread.sort_unstable_by(|a, b| {
+            (a.reply.created_at, a.reply.id).cmp(&(b.reply.created_at, b.reply.id))
+        });
+
+        let child_counts: HashMap<Ulid, u64> = thread
+            .iter()
+            .map(|thread_reply| (thread_reply.reply.id, thread_reply.child_count))
+            .collect();
+        let mut replies: Vec<ReplyResponse> = thread
             .into_iter()
-            .map(ReplyResponse::from)
+            .map(|thread_reply| ReplyResponse::from(thread_reply.reply))
             .collect();
 
         self.populate_interactions(&mut replies, user_id).await?;
-        Ok(map_nested(replies)
-               .into_values()
-               .flatten()
-               .collect::<Vec<_>>())
+
+        Ok(ReplyPageResponse {
+            replies: build_threads(&root_ids, replies, &child_counts, children as usize),
+            next_cursor,
+        })
     }
 
     async fn get_replies_from_posts(
@@ -129,8 +236,8 @@ impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> ReplyServ
     ) -> Result<HashMap<Ulid, Vec<ReplyResponse>>, errors::AppError> {
         let grouped_replies_map = self.reply_repo.get_all_from_posts(posts_ids).await?;
         let mut replies_with_post: Vec<ReplyResponse> = grouped_replies_map
-            .iter()
-            .flat_map(|(_, replies)| {
+            .values()
+            .flat_map(|replies| {
                 replies
                     .clone()
                     .into_iter()
@@ -187,17 +294,17 @@ impl<R: ReplyRepository + 'static, I: InteractionRepository + 'static> ReplyServ
         &self,
         reply_id: &Ulid,
         content: &str,
+        actor: &ReplyActor,
     ) -> Result<ReplyResponse, errors::AppError> {
-        let updated = self.reply_repo.update(reply_id, content).await?;
+        let updated = self.reply_repo.update(reply_id, content, actor).await?;
         Ok(updated.into())
     }
 
-    async fn delete(&self, reply_id: &Ulid) -> Result<(), errors::AppError> {
-        self.reply_repo.delete(reply_id).await?;
+    async fn delete(&self, reply_id: &Ulid, actor: &ReplyActor) -> Result<(), errors::AppError> {
+        self.reply_repo.delete(reply_id, actor).await?;
 
         self.interaction_repo
             .delete_interactions(&reply_id.to_string())
             .await
-            .map_err(Into::into)
     }
 }
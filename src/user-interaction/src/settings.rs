@@ -1,7 +1,8 @@
-use crate::services::key_vault::KeyVault;
-use crate::utils::constants::{GRPC_SERVER_ADDR, JWT_AUDIENCE, JWT_ISSUER, JWT_SECRET, OTEL_COLLECTOR_ADDR, POSTGRES_CONNECTION_STRING, RABBITMQ_URL_SECRET, REDIS_CONNECTION_STRING, REDIS_EXPIRE};
+use crate::utils::constants::{GRPC_SERVER_ADDR, LIKE_REACTION, OTEL_COLLECTOR_ADDR, POSTGRES_CONNECTION_STRING, RABBITMQ_URL_SECRET, RATE_LIMIT_ROUTES, REACTION_KINDS, REDIS_CONNECTION_STRING, REPLIES_MAX_CHILDREN, REPLIES_MAX_DEPTH, REPLIES_MAX_PAGE_SIZE, REPLIES_PAGE_SIZE};
 use serde::Deserialize;
 use std::fs;
+use zylo_common::config::{AmqConsumers, Auth, RateLimits};
+use zylo_common::key_vault::KeyVault;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Global {
@@ -21,34 +22,15 @@ impl Database {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct Auth {
-    pub secret: String,
-    pub issuer: String,
-    pub audience: String,
-}
-
-impl Auth {
-    async fn from_key_vault(key_vault: &KeyVault) -> Self {
-        Self{
-            secret: key_vault.get_secret(JWT_SECRET).await.unwrap(),
-            issuer: key_vault.get_secret(JWT_ISSUER).await.unwrap(),
-            audience: key_vault.get_secret(JWT_AUDIENCE).await.unwrap(),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Deserialize)]
 pub struct Redis {
     pub uri: String,
-    pub expire_time: u32,
 }
 
 impl Redis {
     async fn from_key_vault(key_vault: &KeyVault) -> Self {
         Self{
             uri: key_vault.get_secret(REDIS_CONNECTION_STRING).await.unwrap(),
-            expire_time: key_vault.get_secret(REDIS_EXPIRE).await.unwrap().parse().unwrap(),
         }
     }
 }
@@ -56,12 +38,15 @@ impl Redis {
 #[derive(Debug, Clone, Deserialize)]
 pub struct RabbitMq {
     pub uri: String,
+    #[serde(default)]
+    pub consumers: AmqConsumers,
 }
 
 impl RabbitMq {
     async fn from_key_vault(key_vault: &KeyVault) -> Self {
         Self{
             uri: key_vault.get_secret(RABBITMQ_URL_SECRET).await.unwrap(),
+            consumers: AmqConsumers::from_key_vault(key_vault).await,
         }
     }
 }
@@ -91,6 +76,95 @@ impl OtelCollector {
    } 
 }
 
+/// Limits applied when reply threads are returned page by page.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ReplyThreads {
+    /// Top level replies per page when the caller does not ask for a size.
+    pub page_size: u32,
+    pub max_page_size: u32,
+    /// Deepest level of nested replies returned, top level replies being level 1.
+    pub max_depth: u32,
+    /// Most direct replies returned under a single reply.
+    pub max_children: u32,
+}
+
+impl Default for ReplyThreads {
+    fn default() -> Self {
+        Self {
+            page_size: 20,
+            max_page_size: 100,
+            max_depth: 3,
+            max_children: 5,
+        }
+    }
+}
+
+impl ReplyThreads {
+    async fn from_key_vault(key_vault: &KeyVault) -> Self {
+        let defaults = Self::default();
+        let secret = |name: &'static str| async move {
+            key_vault
+                .get_secret(name)
+                .await
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+        };
+
+        Self {
+            page_size: secret(REPLIES_PAGE_SIZE).await.unwrap_or(defaults.page_size),
+            max_page_size: secret(REPLIES_MAX_PAGE_SIZE).await.unwrap_or(defaults.max_page_size),
+            max_depth: secret(REPLIES_MAX_DEPTH).await.unwrap_or(defaults.max_depth),
+            max_children: secret(REPLIES_MAX_CHILDREN).await.unwrap_or(defaults.max_children),
+        }
+    }
+}
+
+/// Reaction kinds users can leave on posts and replies. A user has at most one reaction per
+/// entity.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Reactions {
+    pub kinds: Vec<String>,
+}
+
+impl Default for Reactions {
+    fn default() -> Self {
+        Self {
+            kinds: ["like", "love", "laugh", "sad", "angry"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+impl Reactions {
+    async fn from_key_vault(key_vault: &KeyVault) -> Self {
+        match key_vault.get_secret(REACTION_KINDS).await {
+            Ok(kinds) => Self {
+                kinds: kinds
+                    .split(',')
+                    .map(|kind| kind.trim().to_lowercase())
+                    .filter(|kind| !kind.is_empty())
+                    .collect(),
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// The kinds users may react with: the configured ones without duplicates, `like` first.
+    /// Likes are reactions of that kind, so it is allowed even when it is not configured.
+    pub fn allowed(&self) -> Vec<String> {
+        let mut kinds = vec![String::from(LIKE_REACTION)];
+        for kind in &self.kinds {
+            if !kinds.contains(kind) {
+                kinds.push(kind.clone());
+            }
+        }
+
+        kinds
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct AppConfig {
     pub global: Global,
@@ -99,7 +173,13 @@ pub struct AppConfig {
     pub auth: Auth,
     pub amq: RabbitMq,
     pub grpc_server: GrpcServer,
-    pub otel_collector: OtelCollector
+    pub otel_collector: OtelCollector,
+    #[serde(default)]
+    pub replies: ReplyThreads,
+    #[serde(default)]
+    pub reactions: Reactions,
+    #[serde(default)]
+    pub rate_limits: RateLimits,
 }
 
 impl AppConfig {
@@ -131,6 +211,9 @@ impl AppConfig {
             amq: RabbitMq::from_key_vault(key_vault).await,
             grpc_server: GrpcServer::from_key_vault(key_vault).await,
             otel_collector: OtelCollector::from_key_vault(key_vault).await,
+            replies: ReplyThreads::from_key_vault(key_vault).await,
+            reactions: Reactions::from_key_vault(key_vault).await,
+            rate_limits: RateLimits::from_key_vault(key_vault, RATE_LIMIT_ROUTES).await,
         }
     }
 
@@ -0,0 +1,403 @@
+//! Fixtures shared by the tests of the repositories, services and routes.
+
+use crate::errors;
+use crate::models::app_state::AppState;
+use crate::models::Finalizer;
+use crate::repositories::bookmarks_repo::PostgresBookmarksRepository;
+use crate::repositories::interaction_repo::{InteractionRepository, PostgresInteractionRepository};
+use crate::repositories::posts_repo::{PostgresPostsRepository, PostsRepository};
+use crate::repositories::reply_repo::PostgresReplyRepository;
+use crate::repositories::users_repo::{PostgresUsersRepository, UsersRepository};
+use crate::services::amq_client::AmqClient;
+use crate::services::cache_service::CacheService;
+use crate::services::post_interactions_service::PostInteractionsServiceImpl;
+use crate::services::reply_service::ReplyServiceImpl;
+use crate::settings::{AppConfig, Reactions};
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{header, Method, Request};
+use redis::aio::MultiplexedConnection;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use ulid::Ulid;
+use zylo_common::auth::Principal;
+use zylo_common::events::Event;
+
+pub type TestInteractionRepository = PostgresInteractionRepository<InMemoryCacheService>;
+pub type TestReplyService = ReplyServiceImpl<PostgresReplyRepository, TestInteractionRepository>;
+pub type TestState = AppState<
+    NoBroker,
+    TestInteractionRepository,
+    TestReplyService,
+    PostInteractionsServiceImpl<
+        TestReplyService,
+        TestInteractionRepository,
+        PostgresBookmarksRepository,
+        PostgresPostsRepository,
+    >,
+>;
+
+/// Creates `user_id` and a post of theirs.
+pub async fn seed_post(pool: &PgPool, user_id: &Ulid) -> Ulid {
+    let post_id = Ulid::new();
+    PostgresUsersRepository::new(pool.clone())
+        .create(user_id)
+        .await
+        .unwrap();
+    PostgresPostsRepository::new(pool.clone())
+        .create(&post_id, user_id)
+        .await
+        .unwrap();
+
+    post_id
+}
+
+pub fn interaction_repo(pool: &PgPool) -> TestInteractionRepository {
+    PostgresInteractionRepository::new(
+        pool.clone(),
+        Arc::new(InMemoryCacheService::default()),
+        &Reactions::default(),
+    )
+}
+
+/// The state of the service on `pool`, with the development configuration and no broker.
+pub fn app_state(pool: &PgPool) -> TestState {
+    let config: AppConfig = serde_json::from_str(include_str!("../config/development.json"))
+        .expect("Invalid JSON configuration");
+    let interaction_repo = Arc::new(interaction_repo(pool));
+    let reply_service = Arc::new(ReplyServiceImpl::new(
+        Arc::new(PostgresReplyRepository::new(pool.clone())),
+        interaction_repo.clone(),
+        config.replies,
+    ));
+    let post_interactions_service = Arc::new(PostInteractionsServiceImpl::new(
+        reply_service.clone(),
+        interaction_repo.clone(),
+        Arc::new(PostgresBookmarksRepository::new(pool.clone())),
+        Arc::new(PostgresPostsRepository::new(pool.clone())),
+    ));
+
+    AppState::new(
+        Arc::new(NoBroker),
+        interaction_repo,
+        reply_service,
+        post_interactions_service,
+        config,
+    )
+}
+
+pub fn principal(user_id: &Ulid) -> Principal {
+    Principal {
+        user_id: user_id.to_string(),
+        roles: Vec::new(),
+        scopes: Vec::new(),
+    }
+}
+
+pub fn moderator(user_id: &Ulid) -> Principal {
+    Principal {
+        roles: vec![String::from("moderator")],
+        ..principal(user_id)
+    }
+}
+
+/// A request made by `principal`, as the authentication middleware would pass it on.
+pub fn request(
+    method: Method,
+    uri: &str,
+    principal: Option<&Principal>,
+    body: Option<serde_json::Value>,
+) -> Request<Body> {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(principal) = principal {
+        request = request.extension(principal.clone());
+    }
+
+    match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => request.body(Body::empty()).unwrap(),
+    }
+}
+
+/// Stands in for RabbitMQ where nothing is expected to be published directly.
+pub struct NoBroker;
+
+#[async_trait]
+impl Finalizer for NoBroker {
+    async fn finalize(&self) -> Result<(), errors::AppError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AmqClient for NoBroker {
+    async fn declare_exchanges(&self) -> Result<(), errors::AmqError> {
+        Ok(())
+    }
+
+    async fn declare_queues(&self) -> Result<(), errors::AmqError> {
+        Ok(())
+    }
+
+    async fn publish_event<T: Event + Serialize + Send + Sync>(
+        &self,
+        _exchange_name: &str,
+        routing_key: &str,
+        _event: T,
+    ) -> Result<(), errors::AmqError> {
+        panic!("{routing_key} was published directly instead of through the outbox")
+    }
+
+    async fn setup_listeners<
+        P: PostsRepository + 'static,
+        U: UsersRepository + 'static,
+        I: InteractionRepository + 'static,
+    >(
+        &self,
+        _posts_repo: Arc<P>,
+        _users_repo: Arc<U>,
+        _interactions_repo: Arc<I>,
+    ) -> Result<(), errors::AppError> {
+        Ok(())
+    }
+}
+
+/// Stands in for Redis. HyperLogLogs are kept as exact sets, so counts are exact, and their raw
+/// form is the members separated by newlines.
+#[derive(Default)]
+pub struct InMemoryCacheService {
+    sets: Mutex<HashMap<String, HashSet<String>>>,
+    logs: Mutex<HashMap<String, HashSet<String>>>,
+    values: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryCacheService {
+    fn pfadd_one(&self, key: &str, element: &str) -> bool {
+        self.logs
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .insert(element.to_string())
+    }
+
+    fn scard(&self, key: &str) -> u64 {
+        self.sets
+            .lock()
+            .unwrap()
+            .get(key)
+            .map_or(0, |set| set.len() as u64)
+    }
+
+    fn pfcount_one(&self, key: &str) -> u64 {
+        self.logs
+            .lock()
+            .unwrap()
+            .get(key)
+            .map_or(0, |log| log.len() as u64)
+    }
+}
+
+#[async_trait]
+impl CacheService for InMemoryCacheService {
+    async fn get_conn(&self) -> Result<MultiplexedConnection, errors::RedisError> {
+        Err(errors::redis_op_error(
+            "CONNECT",
+            "N/A",
+            redis::RedisError::from((redis::ErrorKind::ClientError, "in memory cache")),
+        ))
+    }
+
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, errors::RedisError> {
+        Ok(self.logs.lock().unwrap().get(key).map(|log| {
+            log.iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n")
+                .into_bytes()
+        }))
+    }
+
+    async fn exists_many(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, bool>, errors::RedisError> {
+        let sets = self.sets.lock().unwrap();
+        let logs = self.logs.lock().unwrap();
+        let values = self.values.lock().unwrap();
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                let exists = sets.get(key).is_some_and(|set| !set.is_empty())
+                    || logs.contains_key(key)
+                    || values.contains_key(key);
+                (key.clone(), exists)
+            })
+            .collect())
+    }
+
+    async fn pfadd(&self, key: &str, element: &str) -> Result<bool, errors::RedisError> {
+        Ok(self.pfadd_one(key, element))
+    }
+
+    async fn pfadd_expire_at(
+        &self,
+        keys: &[(String, i64)],
+        element: &str,
+    ) -> Result<(), errors::RedisError> {
+        for (key, _) in keys {
+            self.pfadd_one(key, element);
+        }
+
+        Ok(())
+    }
+
+    async fn pfmerge_raw(&self, key: &str, dump: &[u8]) -> Result<(), errors::RedisError> {
+        for element in String::from_utf8_lossy(dump).lines() {
+            self.pfadd_one(key, element);
+        }
+
+        Ok(())
+    }
+
+    async fn pfcount(&self, key: &str) -> Result<u64, errors::RedisError> {
+        Ok(self.pfcount_one(key))
+    }
+
+    async fn pfcount_many(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, u64>, errors::RedisError> {
+        Ok(keys
+            .iter()
+            .map(|key| (key.clone(), self.pfcount_one(key)))
+            .collect())
+    }
+
+    async fn sadd(&self, key: &str, member: &str) -> Result<bool, errors::RedisError> {
+        Ok(self
+            .sets
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .insert(member.to_string()))
+    }
+
+    async fn srem(&self, key: &str, member: &str) -> Result<bool, errors::RedisError> {
+        Ok(self
+            .sets
+            .lock()
+            .unwrap()
+            .get_mut(key)
+            .is_some_and(|set| set.remove(member)))
+    }
+
+    async fn sadd_exclusive(
+        &self,
+        key: &str,
+        others: &[String],
+        member: &str,
+    ) -> Result<bool, errors::RedisError> {
+        let mut sets = self.sets.lock().unwrap();
+        for other in others {
+            if let Some(set) = sets.get_mut(other) {
+                set.remove(member);
+            }
+        }
+
+        Ok(sets
+            .entry(key.to_string())
+            .or_default()
+            .insert(member.to_string()))
+    }
+
+    async fn fill_sets(
+        &self,
+        sets: &[(String, Vec<String>)],
+        marker: &str,
+    ) -> Result<(), errors::RedisError> {
+        let mut stored = self.sets.lock().unwrap();
+        for (key, members) in sets {
+            stored
+                .entry(key.clone())
+                .or_default()
+                .extend(members.iter().cloned());
+        }
+        self.values
+            .lock()
+            .unwrap()
+            .insert(marker.to_string(), b"1".to_vec());
+
+        Ok(())
+    }
+
+    async fn spop_many(&self, key: &str, count: usize) -> Result<Vec<String>, errors::RedisError> {
+        let mut sets = self.sets.lock().unwrap();
+        let Some(set) = sets.get_mut(key) else {
+            return Ok(Vec::new());
+        };
+
+        let popped: Vec<String> = set.iter().take(count).cloned().collect();
+        for member in &popped {
+            set.remove(member);
+        }
+
+        Ok(popped)
+    }
+
+    async fn smembers(&self, key: &str) -> Result<Vec<String>, errors::RedisError> {
+        Ok(self
+            .sets
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|set| set.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn scard_many(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, u64>, errors::RedisError> {
+        Ok(keys
+            .iter()
+            .map(|key| (key.clone(), self.scard(key)))
+            .collect())
+    }
+
+    async fn sismember_many(
+        &self,
+        keys: &[String],
+        member: &str,
+    ) -> Result<HashMap<String, bool>, errors::RedisError> {
+        let sets = self.sets.lock().unwrap();
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                let is_member = sets.get(key).is_some_and(|set| set.contains(member));
+                (key.clone(), is_member)
+            })
+            .collect())
+    }
+
+    async fn del(&self, keys: &[String]) -> Result<(), errors::RedisError> {
+        let mut sets = self.sets.lock().unwrap();
+        let mut logs = self.logs.lock().unwrap();
+        let mut values = self.values.lock().unwrap();
+        for key in keys {
+            sets.remove(key);
+            logs.remove(key);
+            values.remove(key);
+        }
+
+        Ok(())
+    }
+}
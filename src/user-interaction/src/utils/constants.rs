@@ -1,9 +1,17 @@
 ﻿pub const POSTGRES_CONNECTION_STRING: &str= "UserInteractions-Postgres--ConnectionString";
 pub const REDIS_CONNECTION_STRING: &str= "UserInteractions-Redis--ConnectionString";
-pub const REDIS_EXPIRE: &str= "Zylo-Redis--Expire";
 
 pub const GRPC_SERVER_ADDR: &str = "UserInteraction-gRPC--ServerAddr";
 
+pub const REPLIES_PAGE_SIZE: &str = "UserInteraction-Replies--PageSize";
+pub const REPLIES_MAX_PAGE_SIZE: &str = "UserInteraction-Replies--MaxPageSize";
+pub const REPLIES_MAX_DEPTH: &str = "UserInteraction-Replies--MaxDepth";
+pub const REPLIES_MAX_CHILDREN: &str = "UserInteraction-Replies--MaxChildren";
+
+pub const REACTION_KINDS: &str = "UserInteraction-Reactions--Kinds";
+
+pub const RATE_LIMIT_ROUTES: &str = "UserInteraction-RateLimit--Routes";
+
 pub const OTEL_SERVICE_NAME: &str = "user-interaction";
 pub const OTEL_COLLECTOR_ADDR: &str = "Zylo-OTEL--CollectorAddress";
 
@@ -11,9 +19,30 @@ pub const RABBITMQ_URL_SECRET: &str = "Zylo-RabbitMq--ConnectionString";
 
 pub const POST_EXCHANGE_NAME: &str = "post-exchange";
 pub const USER_EXCHANGE_NAME: &str = "user-exchange";
+pub const AUDIT_EXCHANGE_NAME: &str = "audit-exchange";
+
+pub const PROCESSED_MESSAGES_TTL_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+pub const VIEW_CHECKPOINT_INTERVAL_SECONDS: u64 = 60;
+pub const VIEW_CHECKPOINT_BATCH_SIZE: usize = 500;
+
+pub const ANALYTICS_HOURLY_RETENTION_SECONDS: i64 = 7 * 24 * 60 * 60;
+pub const ANALYTICS_DAILY_RETENTION_SECONDS: i64 = 90 * 24 * 60 * 60;
+
+pub const LIKES_PAGE_SIZE: u32 = 20;
+pub const LIKES_MAX_PAGE_SIZE: u32 = 100;
+
+pub const BOOKMARKS_PAGE_SIZE: u32 = 20;
+pub const BOOKMARKS_MAX_PAGE_SIZE: u32 = 100;
+
+/// Reaction kind of a like. Always accepted, whatever reaction kinds are configured.
+pub const LIKE_REACTION: &str = "like";
+
+pub const AMQ_HEALTH_CHECK_INTERVAL_SECONDS: u64 = 5;
+
 
-pub const JWT_SECRET: &str = "Zylo-Jwt--Secret";
-pub const JWT_ISSUER: &str = "Zylo-Jwt--Issuer";
-pub const JWT_AUDIENCE: &str = "Zylo-Jwt--Audience";
+pub const DELETED_REPLY_CONTENT: &str = "[deleted]";
+pub const REPLY_COMPACTION_INTERVAL_SECONDS: u64 = 10 * 60;
+pub const REPLY_COMPACTION_BATCH_SIZE: i64 = 500;
 
 pub const REQUEST_ID_HEADER: &str = "x-request-id";
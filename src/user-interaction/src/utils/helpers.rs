@@ -1,32 +1,28 @@
 ﻿use crate::errors;
 use crate::models::reply::{PostInteractionResponse, ReplyResponse};
 use std::collections::HashMap;
-use std::fs;
 use ulid::Ulid;
 
 pub trait Validate {
     fn validate(&self) -> Result<(), errors::ValidationError>;
 }
 
-pub fn get_container_id() -> Option<String> {
-    if let Ok(cgroup) = fs::read_to_string("/proc/self/cgroup") {
-        for line in cgroup.lines() {
-            if let Some(id) = line.split('/').last() {
-                if id.len() >= 12 {
-                    return Some(id.to_string());
-                }
-            }
-        }
-    }
-    None
-}
-
 pub struct PostInteractionResponseBuilder {
     post_id: Ulid,
     replies: Vec<ReplyResponse>,
     likes: u64,
     views: u64,
     user_interacted: Option<bool>,
+    reactions: HashMap<String, u64>,
+    user_reaction: Option<String>,
+    bookmarked: Option<bool>,
+    next_cursor: Option<String>,
+}
+
+impl Default for PostInteractionResponseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PostInteractionResponseBuilder {
@@ -37,6 +33,10 @@ impl PostInteractionResponseBuilder {
             likes: 0,
             views: 0,
             user_interacted: None,
+            reactions: HashMap::new(),
+            user_reaction: None,
+            bookmarked: None,
+            next_cursor: None,
         }
     }
 
@@ -65,6 +65,26 @@ impl PostInteractionResponseBuilder {
         self
     }
 
+    pub fn reactions(mut self, reactions: HashMap<String, u64>) -> Self {
+        self.reactions = reactions;
+        self
+    }
+
+    pub fn user_reaction(mut self, user_reaction: Option<String>) -> Self {
+        self.user_reaction = user_reaction;
+        self
+    }
+
+    pub fn bookmarked(mut self, bookmarked: Option<bool>) -> Self {
+        self.bookmarked = bookmarked;
+        self
+    }
+
+    pub fn next_cursor(mut self, next_cursor: Option<String>) -> Self {
+        self.next_cursor = next_cursor;
+        self
+    }
+
     pub fn build(self) -> PostInteractionResponse {
         PostInteractionResponse {
             post_id: self.post_id,
@@ -72,26 +92,66 @@ impl PostInteractionResponseBuilder {
             likes: self.likes,
             views: self.views,
             user_interacted: self.user_interacted,
+            reactions: self.reactions,
+            user_reaction: self.user_reaction,
+            bookmarked: self.bookmarked,
+            next_cursor: self.next_cursor,
         }
     }
 }
 
-fn find_deepest(
-    reply_map: &HashMap<Ulid, ReplyResponse>,
-    mut parent_reply: ReplyResponse,
-) -> ReplyResponse {
-    let nested_replies: Vec<ReplyResponse> = reply_map
-        .values()
-        .filter(|reply| {
-            reply.post_id == parent_reply.post_id && reply.reply_to_id == parent_reply.id
-        })
-        .map(|reply| find_deepest(reply_map, reply.clone()))
-        .collect();
-
-    parent_reply.nested_replies = nested_replies;
-    parent_reply
+/// Path of the parent of the reply at `path`, e.g. `/post/parent/` for `/post/parent/reply/`.
+fn parent_path(path: &str) -> &str {
+    let trimmed = path.strip_suffix('/').unwrap_or(path);
+    trimmed.rfind('/').map_or("", |end| &path[..=end])
 }
 
+/// Nests the replies of a single post by their materialized paths in linear time. Replies are
+/// attached bottom up, deepest level first, so every reply is complete before it is moved under
+/// its parent. Siblings and top level replies keep the order of `replies`; replies whose parent
+/// is not in `replies` become top level replies.
+fn build_tree(replies: Vec<ReplyResponse>) -> Vec<ReplyResponse> {
+    let parents: Vec<Option<usize>> = {
+        let by_path: HashMap<&str, usize> = replies
+            .iter()
+            .enumerate()
+            .map(|(index, reply)| (reply.path.as_str(), index))
+            .collect();
+
+        replies
+            .iter()
+            .map(|reply| by_path.get(parent_path(&reply.path)).copied())
+            .collect()
+    };
+
+    let mut levels: Vec<Vec<usize>> = Vec::new();
+    for (index, reply) in replies.iter().enumerate() {
+        let depth = reply.path.matches('/').count();
+        if levels.len() <= depth {
+            levels.resize_with(depth + 1, Vec::new);
+        }
+        levels[depth].push(index);
+    }
+
+    let mut slots: Vec<Option<ReplyResponse>> = replies.into_iter().map(Some).collect();
+    for level in levels.iter().rev() {
+        for &index in level {
+            let Some(parent) = parents[index] else {
+                continue;
+            };
+
+            if let Some(reply) = slots[index].take() {
+                if let Some(parent) = slots[parent].as_mut() {
+                    parent.nested_replies.push(reply);
+                }
+            }
+        }
+    }
+
+    slots.into_iter().flatten().collect()
+}
+
+/// Groups replies by post and nests each post's replies, see [`build_tree`].
 pub fn map_nested(replies: Vec<ReplyResponse>) -> HashMap<Ulid, Vec<ReplyResponse>> {
     let mut post_map: HashMap<Ulid, Vec<ReplyResponse>> = HashMap::new();
 
@@ -101,19 +161,184 @@ pub fn map_nested(replies: Vec<ReplyResponse>) -> HashMap<Ulid, Vec<ReplyRespons
 
     post_map
         .into_iter()
-        .map(|(post_id, replies)| {
-            let mut mapped = Vec::new();
-            let reply_map: HashMap<Ulid, ReplyResponse> =
-                replies.into_iter().map(|r| (r.id, r)).collect();
-
-            for reply in reply_map.values() {
-                if !reply_map.contains_key(&reply.reply_to_id) {
-                    let nested = find_deepest(&reply_map, reply.clone());
-                    mapped.push(nested);
-                }
-            }
+        .map(|(post_id, replies)| (post_id, build_tree(replies)))
+        .collect()
+}
 
-            (post_id, mapped)
-        })
+/// Nests the replies of truncated threads under their parents. Top level replies keep the order
+/// of `root_ids` and nested ones keep the order of `replies`. At most `max_children` replies are
+/// kept under each reply, and `has_more_children` is set whenever `child_counts` says a reply has
+/// more than were returned.
+pub fn build_threads(
+    root_ids: &[Ulid],
+    replies: Vec<ReplyResponse>,
+    child_counts: &HashMap<Ulid, u64>,
+    max_children: usize,
+) -> Vec<ReplyResponse> {
+    let mut children: HashMap<Ulid, Vec<Ulid>> = HashMap::new();
+    let mut nodes: HashMap<Ulid, ReplyResponse> = HashMap::with_capacity(replies.len());
+
+    for reply in replies {
+        children
+            .entry(reply.reply_to_id)
+            .or_default()
+            .push(reply.id);
+        nodes.insert(reply.id, reply);
+    }
+
+    fn attach(
+        id: Ulid,
+        nodes: &mut HashMap<Ulid, ReplyResponse>,
+        children: &mut HashMap<Ulid, Vec<Ulid>>,
+        child_counts: &HashMap<Ulid, u64>,
+        max_children: usize,
+    ) -> Option<ReplyResponse> {
+        let mut reply = nodes.remove(&id)?;
+        let child_ids = children.remove(&id).unwrap_or_default();
+
+        reply.nested_replies = child_ids
+            .into_iter()
+            .take(max_children)
+            .filter_map(|child_id| attach(child_id, nodes, children, child_counts, max_children))
+            .collect();
+        reply.has_more_children =
+            child_counts.get(&id).copied().unwrap_or(0) > reply.nested_replies.len() as u64;
+
+        Some(reply)
+    }
+
+    root_ids
+        .iter()
+        .filter_map(|id| attach(*id, &mut nodes, &mut children, child_counts, max_children))
         .collect()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::reply::Reply;
+    use chrono::Utc;
+    use proptest::prelude::*;
+
+    /// Tree shape that can be compared regardless of the other fields of a reply.
+    #[derive(Debug, PartialEq)]
+    struct Node {
+        id: Ulid,
+        children: Vec<Node>,
+    }
+
+    fn shape(replies: &[ReplyResponse]) -> Vec<Node> {
+        replies
+            .iter()
+            .map(|reply| Node {
+                id: reply.id,
+                children: shape(&reply.nested_replies),
+            })
+            .collect()
+    }
+
+    /// Straightforward quadratic nesting: the children of a reply are the replies whose path
+    /// extends its path by one segment, in input order.
+    fn reference_tree(replies: &[ReplyResponse]) -> Vec<Node> {
+        fn children(parent_path: &str, replies: &[ReplyResponse]) -> Vec<Node> {
+            replies
+                .iter()
+                .filter(|reply| super::parent_path(&reply.path) == parent_path)
+                .map(|reply| Node {
+                    id: reply.id,
+                    children: children(&reply.path, replies),
+                })
+                .collect()
+        }
+
+        replies
+            .iter()
+            .filter(|reply| {
+                let parent = super::parent_path(&reply.path);
+                !replies.iter().any(|other| other.path == parent)
+            })
+            .map(|reply| Node {
+                id: reply.id,
+                children: children(&reply.path, replies),
+            })
+            .collect()
+    }
+
+    fn hex(id: &Ulid) -> String {
+        id.to_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Replies of one post. `parents[i]` picks the parent of reply `i` among the replies before
+    /// it, `None` being the post itself; a parent that is missing from the result makes an
+    /// orphan, like a reply whose parent was compacted away.
+    fn thread(parents: &[(Option<usize>, bool)]) -> Vec<ReplyResponse> {
+        let post_id = Ulid::new();
+        let post_path = format!("/{}/", hex(&post_id));
+
+        let mut replies: Vec<ReplyResponse> = Vec::with_capacity(parents.len());
+        let mut missing = Vec::new();
+        for (index, (parent, keep)) in parents.iter().enumerate() {
+            let id = Ulid::new();
+            let (reply_to_id, parent_path) = match parent.map(|parent| parent % index.max(1)) {
+                Some(parent) if index > 0 => (replies[parent].id, replies[parent].path.clone()),
+                _ => (post_id, post_path.clone()),
+            };
+
+            replies.push(ReplyResponse::from(Reply {
+                id,
+                post_id,
+                reply_to_id,
+                user_id: Some(Ulid::new()),
+                content: String::from("reply"),
+                created_at: Utc::now().naive_utc(),
+                deleted_at: None,
+                path: format!("{}{}/", parent_path, hex(&id)),
+            }));
+            if !keep {
+                missing.push(index);
+            }
+        }
+
+        replies
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !missing.contains(index))
+            .map(|(_, reply)| reply)
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn build_tree_matches_the_reference(
+            parents in prop::collection::vec(
+                (prop::option::weighted(0.8, any::<usize>()), prop::bool::weighted(0.95)),
+                0..120,
+            ),
+            order in prop::collection::vec(any::<u32>(), 120),
+        ) {
+            // Rows come in any order, not only parents before their children.
+            let mut keyed: Vec<(ReplyResponse, u32)> = thread(&parents).into_iter().zip(order).collect();
+            keyed.sort_by_key(|(_, key)| *key);
+            let replies: Vec<ReplyResponse> = keyed.into_iter().map(|(reply, _)| reply).collect();
+
+            let expected = reference_tree(&replies);
+            prop_assert_eq!(shape(&build_tree(replies)), expected);
+        }
+    }
+
+    #[test]
+    fn map_nested_keeps_posts_apart() {
+        let first = thread(&[(None, true), (Some(0), true)]);
+        let second = thread(&[(None, true)]);
+        let (first_post, second_post) = (first[0].post_id, second[0].post_id);
+
+        let nested = map_nested(first.into_iter().chain(second).collect());
+
+        assert_eq!(nested[&first_post].len(), 1);
+        assert_eq!(nested[&first_post][0].nested_replies.len(), 1);
+        assert_eq!(nested[&second_post].len(), 1);
+    }
+}
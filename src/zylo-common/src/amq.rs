@@ -0,0 +1,1086 @@
+use crate::config::ConsumerSettings;
+use lapin::message::Delivery;
+use lapin::options::{
+    BasicAckOptions, BasicCancelOptions, BasicConsumeOptions, BasicGetOptions, BasicNackOptions,
+    BasicPublishOptions, BasicQosOptions, ConfirmSelectOptions, ExchangeDeclareOptions,
+    QueueBindOptions, QueueDeclareOptions, QueueDeleteOptions,
+};
+use lapin::publisher_confirm::Confirmation;
+use lapin::types::{AMQPValue, FieldTable, ShortString};
+use lapin::{Channel, Connection, ConnectionProperties, Consumer, ExchangeKind};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::{KeyValue, global};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tracing::{Span, error, field, info, info_span, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Header carrying how many times a message has already been retried.
+pub const RETRY_COUNT_HEADER: &str = "x-retry-count";
+const PARKING_ROUTING_KEY: &str = "parking";
+const CONSUMER_LAG_INTERVAL: Duration = Duration::from_secs(15);
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How often a failed message is redelivered before it is parked.
+///
+/// Attempt `n` waits `base_delay * 2^(n - 1)` in its own `<queue>.retry.<n>` queue, so a long
+/// delay never holds back a message that should be retried sooner.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay(&self, retry: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(retry.saturating_sub(1))
+    }
+}
+
+pub fn dead_letter_exchange(queue_name: &str) -> String {
+    format!("{}.dlx", queue_name)
+}
+
+pub fn parking_queue(queue_name: &str) -> String {
+    format!("{}.parking", queue_name)
+}
+
+fn retry_queue(queue_name: &str, retry: u32) -> String {
+    format!("{}.retry.{}", queue_name, retry)
+}
+
+fn retry_routing_key(retry: u32) -> String {
+    format!("retry.{}", retry)
+}
+
+/// Declares `queue_name` together with its dead-letter exchange, delayed retry queues and
+/// parking queue. Messages rejected from the main queue are dead-lettered straight to parking.
+///
+/// RabbitMQ refuses to redeclare an existing queue with different arguments, so a queue that
+/// was declared without a dead-letter exchange has to get a new name, see
+/// [`migrate_legacy_queue`].
+pub async fn declare_retryable_queue(
+    channel: &Channel,
+    queue_name: &str,
+    policy: &RetryPolicy,
+) -> Result<(), lapin::Error> {
+    let dlx = dead_letter_exchange(queue_name);
+    let durable_queue = QueueDeclareOptions {
+        durable: true,
+        ..Default::default()
+    };
+
+    channel
+        .exchange_declare(
+            &dlx,
+            ExchangeKind::Direct,
+            ExchangeDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let parking = parking_queue(queue_name);
+    channel
+        .queue_declare(&parking, durable_queue, FieldTable::default())
+        .await?;
+    channel
+        .queue_bind(
+            &parking,
+            &dlx,
+            PARKING_ROUTING_KEY,
+            QueueBindOptions::default(),
+            FieldTable::default(),
+        )
+        .await?;
+
+    for retry in 1..policy.max_attempts {
+        let mut arguments = FieldTable::default();
+        arguments.insert(
+            "x-message-ttl".into(),
+            AMQPValue::LongLongInt(policy.delay(retry).as_millis() as i64),
+        );
+        arguments.insert(
+            "x-dead-letter-exchange".into(),
+            AMQPValue::LongString("".into()),
+        );
+        arguments.insert(
+            "x-dead-letter-routing-key".into(),
+            AMQPValue::LongString(queue_name.into()),
+        );
+
+        let queue = retry_queue(queue_name, retry);
+        channel
+            .queue_declare(&queue, durable_queue, arguments)
+            .await?;
+        channel
+            .queue_bind(
+                &queue,
+                &dlx,
+                &retry_routing_key(retry),
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+    }
+
+    let mut arguments = FieldTable::default();
+    arguments.insert(
+        "x-dead-letter-exchange".into(),
+        AMQPValue::LongString(dlx.as_str().into()),
+    );
+    arguments.insert(
+        "x-dead-letter-routing-key".into(),
+        AMQPValue::LongString(PARKING_ROUTING_KEY.into()),
+    );
+    channel
+        .queue_declare(queue_name, durable_queue, arguments)
+        .await?;
+
+    Ok(())
+}
+
+/// A queue a service consumes and the exchange binding that feeds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueBinding {
+    pub queue: &'static str,
+    pub exchange: &'static str,
+    pub routing_key: &'static str,
+    /// Name the queue had before it was declared with a dead-letter exchange. Whatever is left
+    /// in it is moved to `queue` at startup.
+    pub legacy_queue: Option<&'static str>,
+}
+
+/// Moves a queue off the name it had before it was declared with a dead-letter exchange. The
+/// legacy queue is unbound so it receives nothing new, the messages left in it are republished
+/// to the new queue and it is deleted once no consumer uses it anymore. Instances of the
+/// previous release keep consuming it during a rolling deploy, in which case the deletion is
+/// retried on the next start. Returns how many messages were moved.
+pub async fn migrate_legacy_queue(
+    connection: &Connection,
+    legacy_queue: &str,
+    binding: &QueueBinding,
+) -> Result<u32, lapin::Error> {
+    // A passive declare of a missing queue closes the channel, so the migration uses its own.
+    let channel = connection.create_channel().await?;
+    let passive = QueueDeclareOptions {
+        passive: true,
+        ..Default::default()
+    };
+    if channel
+        .queue_declare(legacy_queue, passive, FieldTable::default())
+        .await
+        .is_err()
+    {
+        return Ok(0);
+    }
+
+    channel
+        .queue_unbind(
+            legacy_queue,
+            binding.exchange,
+            binding.routing_key,
+            FieldTable::default(),
+        )
+        .await?;
+    channel
+        .confirm_select(ConfirmSelectOptions::default())
+        .await?;
+
+    let mut moved = 0;
+    while let Some(message) = channel
+        .basic_get(legacy_queue, BasicGetOptions::default())
+        .await?
+    {
+        let confirmation = channel
+            .basic_publish(
+                "",
+                binding.queue,
+                BasicPublishOptions::default(),
+                &message.delivery.data,
+                message.delivery.properties.clone(),
+            )
+            .await?
+            .await?;
+
+        if confirmation.is_nack() {
+            message
+                .delivery
+                .nack(BasicNackOptions {
+                    requeue: true,
+                    ..Default::default()
+                })
+                .await?;
+            warn!(
+                "Broker refused a message moved from {}, stopping",
+                legacy_queue
+            );
+            return Ok(moved);
+        }
+
+        message.delivery.ack(BasicAckOptions::default()).await?;
+        moved += 1;
+    }
+
+    let delete = QueueDeleteOptions {
+        if_unused: true,
+        if_empty: true,
+        ..Default::default()
+    };
+    match channel.queue_delete(legacy_queue, delete).await {
+        Ok(_) => {
+            info!("Removed legacy queue {}", legacy_queue);
+            let _ = channel.close(200, "Migrated").await;
+        }
+        Err(e) => warn!(
+            "Legacy queue {} is still in use and will be removed on a later start: {:?}",
+            legacy_queue, e
+        ),
+    }
+
+    Ok(moved)
+}
+
+/// Every exchange and queue a service declares on the broker.
+#[derive(Debug, Clone, Copy)]
+pub struct Topology {
+    pub exchanges: &'static [&'static str],
+    pub queues: &'static [QueueBinding],
+}
+
+impl Topology {
+    /// Declares every exchange as a durable direct exchange.
+    pub async fn declare_exchanges(&self, channel: &Channel) -> Result<(), lapin::Error> {
+        let options = ExchangeDeclareOptions {
+            durable: true,
+            ..Default::default()
+        };
+
+        for exchange in self.exchanges {
+            channel
+                .exchange_declare(
+                    exchange,
+                    ExchangeKind::Direct,
+                    options,
+                    FieldTable::default(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Declares every queue with its retry and parking queues and binds it to its exchange.
+    pub async fn declare_queues(
+        &self,
+        channel: &Channel,
+        policy: &RetryPolicy,
+    ) -> Result<(), lapin::Error> {
+        for binding in self.queues {
+            declare_retryable_queue(channel, binding.queue, policy).await?;
+            channel
+                .queue_bind(
+                    binding.queue,
+                    binding.exchange,
+                    binding.routing_key,
+                    QueueBindOptions::default(),
+                    FieldTable::default(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves every queue that has a legacy name onto its new one, see [`migrate_legacy_queue`].
+    /// Must run after [`Topology::declare_queues`].
+    pub async fn migrate_legacy_queues(&self, connection: &Connection) -> Result<(), lapin::Error> {
+        for binding in self.queues {
+            let Some(legacy_queue) = binding.legacy_queue else {
+                continue;
+            };
+
+            let moved = migrate_legacy_queue(connection, legacy_queue, binding).await?;
+            if moved > 0 {
+                info!(
+                    "Moved {} messages from {} to {}",
+                    moved, legacy_queue, binding.queue
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every declared queue has a consumer and that nothing is consumed from a
+    /// queue this service does not declare. Returns a description of every mismatch.
+    pub fn verify(&self, consumed: &[&str]) -> Result<(), String> {
+        let mut problems = Vec::new();
+
+        for binding in self.queues {
+            if !consumed.contains(&binding.queue) {
+                problems.push(format!("{} has no consumer", binding.queue));
+            }
+        }
+
+        for queue in consumed {
+            if !self.queues.iter().any(|binding| binding.queue == *queue) {
+                problems.push(format!("{} is consumed but not declared", queue));
+            }
+        }
+
+        for exchange in self.queues.iter().map(|binding| binding.exchange) {
+            if !self.exchanges.contains(&exchange) {
+                problems.push(format!("{} is bound to but not declared", exchange));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join(", "))
+        }
+    }
+}
+
+/// What happened to a delivery whose handler failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposition {
+    Retried,
+    Parked,
+    /// The broker did not confirm the retry, so the delivery went back to its queue.
+    Requeued,
+}
+
+impl Disposition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Disposition::Retried => "retried",
+            Disposition::Parked => "parked",
+            Disposition::Requeued => "requeued",
+        }
+    }
+}
+
+/// Settles a delivery whose handler failed. Transient failures are republished to the next
+/// retry queue; permanent failures and exhausted messages are rejected into the parking queue.
+/// `channel` has to be in confirm mode: the delivery is only acknowledged once the broker has
+/// confirmed the retry, and is requeued otherwise.
+pub async fn retry_or_park(
+    channel: &Channel,
+    queue_name: &str,
+    delivery: &Delivery,
+    policy: &RetryPolicy,
+    permanent: bool,
+) -> Result<Disposition, lapin::Error> {
+    let retry = retry_count(delivery) + 1;
+    if permanent || retry >= policy.max_attempts {
+        delivery
+            .nack(BasicNackOptions {
+                requeue: false,
+                ..Default::default()
+            })
+            .await?;
+        return Ok(Disposition::Parked);
+    }
+
+    let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+    headers.insert(RETRY_COUNT_HEADER.into(), AMQPValue::LongUInt(retry));
+
+    let published = match channel
+        .basic_publish(
+            &dead_letter_exchange(queue_name),
+            &retry_routing_key(retry),
+            BasicPublishOptions::default(),
+            &delivery.data,
+            delivery.properties.clone().with_headers(headers),
+        )
+        .await
+    {
+        Ok(confirm) => confirm.await,
+        Err(err) => Err(err),
+    };
+
+    let requeue = BasicNackOptions {
+        requeue: true,
+        ..Default::default()
+    };
+    match published {
+        Ok(Confirmation::Ack(_)) => {
+            delivery.ack(BasicAckOptions::default()).await?;
+            Ok(Disposition::Retried)
+        }
+        Ok(confirmation) => {
+            warn!(
+                "Retry of a message from {} was not confirmed: {:?}",
+                queue_name, confirmation
+            );
+            delivery.nack(requeue).await?;
+            Ok(Disposition::Requeued)
+        }
+        Err(err) => {
+            delivery.nack(requeue).await?;
+            Err(err)
+        }
+    }
+}
+
+/// Number of retries recorded on the delivery, zero for a first delivery.
+pub fn retry_count(delivery: &Delivery) -> u32 {
+    let header = ShortString::from(RETRY_COUNT_HEADER);
+    let Some(value) = delivery
+        .properties
+        .headers()
+        .as_ref()
+        .and_then(|headers| headers.inner().get(&header))
+    else {
+        return 0;
+    };
+
+    match value {
+        AMQPValue::LongUInt(count) => *count,
+        AMQPValue::LongInt(count) => (*count).max(0) as u32,
+        AMQPValue::LongLongInt(count) => (*count).clamp(0, u32::MAX as i64) as u32,
+        AMQPValue::ShortUInt(count) => *count as u32,
+        AMQPValue::ShortShortUInt(count) => *count as u32,
+        _ => 0,
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut FieldTable);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0
+            .insert(key.into(), AMQPValue::LongString(value.as_str().into()));
+    }
+}
+
+struct HeaderExtractor<'a>(&'a FieldTable);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .inner()
+            .iter()
+            .find(|(name, _)| name.as_str() == key)
+            .and_then(|(_, value)| match value {
+                AMQPValue::LongString(value) => std::str::from_utf8(value.as_bytes()).ok(),
+                _ => None,
+            })
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.inner().keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Headers carrying the W3C trace context of the current span.
+pub fn trace_headers() -> FieldTable {
+    let mut headers = FieldTable::default();
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+
+    headers
+}
+
+/// Headers carrying a trace context captured earlier, e.g. in an event envelope.
+pub fn trace_headers_from(trace_context: &HashMap<String, String>) -> FieldTable {
+    let mut headers = FieldTable::default();
+    for (key, value) in trace_context {
+        HeaderInjector(&mut headers).set(key, value.clone());
+    }
+
+    headers
+}
+
+/// Span for processing a delivery, parented to the trace context found in its headers.
+///
+/// Callers record `messaging.outcome` and, on failure, `error.type` once the delivery is settled.
+pub fn consumer_span(queue_name: &str, delivery: &Delivery) -> Span {
+    let message_id = delivery
+        .properties
+        .message_id()
+        .as_ref()
+        .map(|id| id.as_str().to_string())
+        .unwrap_or_default();
+
+    let span = info_span!(
+        "",
+        "otel.name" = format!("{} process", queue_name),
+        "otel.kind" = "consumer",
+        "messaging.system" = "rabbitmq",
+        "messaging.operation.type" = "process",
+        "messaging.operation.name" = "consume",
+        "messaging.destination.name" = delivery.exchange.as_str(),
+        "messaging.destination.subscription.name" = queue_name,
+        "messaging.rabbitmq.destination.routing_key" = delivery.routing_key.as_str(),
+        "messaging.message.id" = message_id,
+        "messaging.message.body.size" = delivery.data.len() as u64,
+        "messaging.rabbitmq.message.redelivered" = delivery.redelivered,
+        "messaging.rabbitmq.message.retry_count" = retry_count(delivery),
+        "messaging.outcome" = field::Empty,
+        "error.type" = field::Empty,
+    );
+
+    if let Some(headers) = delivery.properties.headers() {
+        let parent_cx = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        span.set_parent(parent_cx);
+    }
+
+    span
+}
+
+#[derive(Default)]
+struct QueueGauges {
+    in_flight: AtomicU64,
+    lag: AtomicU64,
+}
+
+/// In-flight and lag gauges for every consumer of a service, labelled by queue.
+#[derive(Clone)]
+pub struct ConsumerMetrics {
+    queues: Arc<Mutex<HashMap<String, Arc<QueueGauges>>>>,
+}
+
+impl ConsumerMetrics {
+    pub fn new(service_name: &'static str) -> Self {
+        let queues: Arc<Mutex<HashMap<String, Arc<QueueGauges>>>> = Arc::default();
+        let meter = global::meter(service_name);
+
+        let in_flight_queues = queues.clone();
+        meter
+            .u64_observable_gauge("amq_consumer_in_flight")
+            .with_description("Messages currently being handled")
+            .with_callback(move |observer| {
+                for (queue, gauges) in in_flight_queues.lock().unwrap().iter() {
+                    observer.observe(
+                        gauges.in_flight.load(Ordering::Relaxed),
+                        &[KeyValue::new("queue", queue.clone())],
+                    );
+                }
+            })
+            .build();
+
+        let lag_queues = queues.clone();
+        meter
+            .u64_observable_gauge("amq_consumer_lag")
+            .with_description("Messages ready in the queue and not yet delivered")
+            .with_callback(move |observer| {
+                for (queue, gauges) in lag_queues.lock().unwrap().iter() {
+                    observer.observe(
+                        gauges.lag.load(Ordering::Relaxed),
+                        &[KeyValue::new("queue", queue.clone())],
+                    );
+                }
+            })
+            .build();
+
+        Self { queues }
+    }
+
+    fn register(&self, queue_name: &str) -> Arc<QueueGauges> {
+        self.queues
+            .lock()
+            .unwrap()
+            .entry(queue_name.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+/// Slot held while a delivery is being handled.
+pub struct InFlight {
+    _permit: OwnedSemaphorePermit,
+    gauges: Arc<QueueGauges>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.gauges.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A running queue consumer. Limits how many deliveries are handled at once and lets shutdown
+/// wait for the ones still in flight.
+#[derive(Clone)]
+pub struct ActiveConsumer {
+    queue_name: String,
+    consumer_tag: String,
+    channel: Channel,
+    slots: Arc<Semaphore>,
+    concurrency: u32,
+    gauges: Arc<QueueGauges>,
+}
+
+impl ActiveConsumer {
+    pub async fn start(
+        channel: Channel,
+        queue_name: &str,
+        settings: ConsumerSettings,
+        metrics: &ConsumerMetrics,
+    ) -> Result<(Self, Consumer), lapin::Error> {
+        channel
+            .basic_qos(settings.prefetch, BasicQosOptions::default())
+            .await?;
+
+        let consumer_tag = format!("{}-consumer", queue_name);
+        let consumer = channel
+            .basic_consume(
+                queue_name,
+                &consumer_tag,
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+
+        let concurrency = settings.concurrency.max(1);
+        let active = Self {
+            queue_name: queue_name.to_string(),
+            consumer_tag,
+            channel,
+            slots: Arc::new(Semaphore::new(concurrency as usize)),
+            concurrency,
+            gauges: metrics.register(queue_name),
+        };
+        active.spawn_lag_monitor();
+
+        Ok((active, consumer))
+    }
+
+    pub fn queue_name(&self) -> &str {
+        &self.queue_name
+    }
+
+    /// Waits for a free handler slot. The slot is released when the returned guard is dropped.
+    pub async fn begin(&self) -> InFlight {
+        let permit = self
+            .slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("consumer slots are never closed");
+        self.gauges.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        InFlight {
+            _permit: permit,
+            gauges: self.gauges.clone(),
+        }
+    }
+
+    /// Stops the broker from delivering further messages to this consumer.
+    pub async fn cancel(&self) -> Result<(), lapin::Error> {
+        self.channel
+            .basic_cancel(&self.consumer_tag, BasicCancelOptions::default())
+            .await
+    }
+
+    /// Waits until no handler is running or `deadline` passes. Returns whether it went idle.
+    pub async fn wait_idle(&self, deadline: Instant) -> bool {
+        matches!(
+            tokio::time::timeout_at(deadline, self.slots.acquire_many(self.concurrency)).await,
+            Ok(Ok(_))
+        )
+    }
+
+    fn spawn_lag_monitor(&self) {
+        let channel = self.channel.clone();
+        let queue_name = self.queue_name.clone();
+        let gauges = self.gauges.clone();
+        let passive = QueueDeclareOptions {
+            passive: true,
+            ..Default::default()
+        };
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CONSUMER_LAG_INTERVAL);
+            loop {
+                interval.tick().await;
+                if !channel.status().connected() {
+                    break;
+                }
+
+                match channel
+                    .queue_declare(&queue_name, passive, FieldTable::default())
+                    .await
+                {
+                    Ok(queue) => gauges
+                        .lag
+                        .store(queue.message_count() as u64, Ordering::Relaxed),
+                    Err(err) => {
+                        warn!("Failed to read the depth of {}: {}", queue_name, err);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Future that starts a consumer, returned by a [`ConsumerFactory`].
+pub type ConsumerStart = Pin<Box<dyn Future<Output = Result<(), lapin::Error>> + Send>>;
+
+/// Starts a consumer on the given channel. Kept by the client so every consumer can be started
+/// again on a fresh channel after a reconnect.
+pub type ConsumerFactory = Arc<dyn Fn(Channel) -> ConsumerStart + Send + Sync>;
+
+/// Delay before reconnect attempt `attempt` (zero based): one second, doubling up to 30 seconds.
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RECONNECT_MAX_DELAY)
+}
+
+/// A broker connection with the channels shared by all publishers.
+pub struct AmqLink {
+    connection: Connection,
+    publish_channel: Channel,
+    confirm_channel: Channel,
+}
+
+impl AmqLink {
+    /// Connects to the broker and opens the publish and confirm channels. `connection_lost` is
+    /// notified when the connection fails.
+    pub async fn connect(uri: &str, connection_lost: Arc<Notify>) -> Result<Self, lapin::Error> {
+        let connection = Connection::connect(uri, ConnectionProperties::default()).await?;
+        connection.on_error(move |err| {
+            error!("RabbitMQ connection lost: {:?}", err);
+            connection_lost.notify_one();
+        });
+
+        let publish_channel = connection.create_channel().await?;
+        let confirm_channel = connection.create_channel().await?;
+        confirm_channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
+
+        Ok(Self {
+            connection,
+            publish_channel,
+            confirm_channel,
+        })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub fn publish_channel(&self) -> &Channel {
+        &self.publish_channel
+    }
+
+    pub fn confirm_channel(&self) -> &Channel {
+        &self.confirm_channel
+    }
+
+    /// Whether the connection and both shared channels are still open.
+    pub fn is_healthy(&self) -> bool {
+        self.connection.status().connected()
+            && self.publish_channel.status().connected()
+            && self.confirm_channel.status().connected()
+    }
+
+    /// Closes the shared channels and the connection, logging anything that fails.
+    pub async fn close(&self) {
+        if let Err(e) = self.publish_channel.close(200, "Shutting down").await {
+            warn!("Failed to close publish channel: {:?}", e);
+        }
+
+        if let Err(e) = self.confirm_channel.close(200, "Shutting down").await {
+            warn!("Failed to close confirm channel: {:?}", e);
+        }
+
+        if let Err(e) = self.connection.close(200, "Shutting down").await {
+            warn!("Failed to close connection: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lapin::BasicProperties;
+
+    #[test]
+    fn retry_delay_doubles_per_attempt() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(5),
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_secs(5));
+        assert_eq!(policy.delay(2), Duration::from_secs(10));
+        assert_eq!(policy.delay(4), Duration::from_secs(40));
+    }
+
+    #[test]
+    fn retry_topology_is_named_after_the_queue() {
+        assert_eq!(dead_letter_exchange("posts"), "posts.dlx");
+        assert_eq!(parking_queue("posts"), "posts.parking");
+        assert_eq!(retry_queue("posts", 2), "posts.retry.2");
+        assert_eq!(retry_routing_key(2), "retry.2");
+    }
+
+    fn amqp_uri() -> String {
+        std::env::var("AMQP_URI").unwrap_or_else(|_| String::from("amqp://localhost:5672"))
+    }
+
+    fn test_name(prefix: &str) -> &'static str {
+        Box::leak(format!("{}-{}", prefix, ulid::Ulid::new()).into_boxed_str())
+    }
+
+    async fn next_message(channel: &Channel, queue: &str) -> Delivery {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(message) = channel
+                .basic_get(queue, BasicGetOptions::default())
+                .await
+                .unwrap()
+            {
+                return message.delivery;
+            }
+
+            assert!(Instant::now() < deadline, "no message arrived on {}", queue);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    async fn publish(channel: &Channel, exchange: &str, routing_key: &str, body: &[u8]) {
+        channel
+            .basic_publish(
+                exchange,
+                routing_key,
+                BasicPublishOptions::default(),
+                body,
+                BasicProperties::default(),
+            )
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+    }
+
+    async fn delete_retryable_queue(channel: &Channel, queue: &str, policy: &RetryPolicy) {
+        let mut queues = vec![queue.to_string(), parking_queue(queue)];
+        queues.extend((1..policy.max_attempts).map(|retry| retry_queue(queue, retry)));
+        for queue in queues {
+            channel
+                .queue_delete(&queue, QueueDeleteOptions::default())
+                .await
+                .unwrap();
+        }
+
+        channel
+            .exchange_delete(&dead_letter_exchange(queue), Default::default())
+            .await
+            .unwrap();
+    }
+
+    async fn connect() -> (Connection, Channel) {
+        let connection = Connection::connect(&amqp_uri(), ConnectionProperties::default())
+            .await
+            .unwrap();
+        let channel = connection.create_channel().await.unwrap();
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await
+            .unwrap();
+
+        (connection, channel)
+    }
+
+    #[tokio::test]
+    #[ignore = "requires RabbitMQ"]
+    async fn failed_message_is_retried_then_parked() {
+        let (_connection, channel) = connect().await;
+        let queue = test_name("retry-test");
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+        };
+        declare_retryable_queue(&channel, queue, &policy)
+            .await
+            .unwrap();
+        publish(&channel, "", queue, b"payload").await;
+
+        for expected_retries in 0..2 {
+            let delivery = next_message(&channel, queue).await;
+            assert_eq!(retry_count(&delivery), expected_retries);
+            let disposition = retry_or_park(&channel, queue, &delivery, &policy, false)
+                .await
+                .unwrap();
+            assert_eq!(disposition, Disposition::Retried);
+        }
+
+        let delivery = next_message(&channel, queue).await;
+        assert_eq!(retry_count(&delivery), 2);
+        let disposition = retry_or_park(&channel, queue, &delivery, &policy, false)
+            .await
+            .unwrap();
+        assert_eq!(disposition, Disposition::Parked);
+
+        let parked = next_message(&channel, &parking_queue(queue)).await;
+        assert_eq!(parked.data, b"payload");
+        parked.ack(BasicAckOptions::default()).await.unwrap();
+
+        delete_retryable_queue(&channel, queue, &policy).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires RabbitMQ"]
+    async fn permanent_failure_is_parked_without_retrying() {
+        let (_connection, channel) = connect().await;
+        let queue = test_name("permanent-test");
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+        };
+        declare_retryable_queue(&channel, queue, &policy)
+            .await
+            .unwrap();
+        publish(&channel, "", queue, b"payload").await;
+
+        let delivery = next_message(&channel, queue).await;
+        let disposition = retry_or_park(&channel, queue, &delivery, &policy, true)
+            .await
+            .unwrap();
+        assert_eq!(disposition, Disposition::Parked);
+
+        let parked = next_message(&channel, &parking_queue(queue)).await;
+        assert_eq!(retry_count(&parked), 0);
+        parked.ack(BasicAckOptions::default()).await.unwrap();
+
+        delete_retryable_queue(&channel, queue, &policy).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires RabbitMQ"]
+    async fn unconfirmed_retry_requeues_the_message() {
+        let (connection, channel) = connect().await;
+        let queue = test_name("unconfirmed-test");
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(50),
+        };
+        declare_retryable_queue(&channel, queue, &policy)
+            .await
+            .unwrap();
+        publish(&channel, "", queue, b"payload").await;
+
+        let unconfirmed = connection.create_channel().await.unwrap();
+        let delivery = next_message(&unconfirmed, queue).await;
+        let disposition = retry_or_park(&unconfirmed, queue, &delivery, &policy, false)
+            .await
+            .unwrap();
+        assert_eq!(disposition, Disposition::Requeued);
+
+        let requeued = next_message(&channel, queue).await;
+        assert!(requeued.redelivered);
+        assert_eq!(retry_count(&requeued), 0);
+        requeued.ack(BasicAckOptions::default()).await.unwrap();
+
+        delete_retryable_queue(&channel, queue, &policy).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires RabbitMQ"]
+    async fn legacy_queue_is_drained_into_its_replacement_and_removed() {
+        let (connection, channel) = connect().await;
+        let exchange = test_name("migration-exchange");
+        let legacy_queue = test_name("migration-test");
+        let binding = QueueBinding {
+            queue: test_name("migration-test-v2"),
+            exchange,
+            routing_key: "user.deleted",
+            legacy_queue: Some(legacy_queue),
+        };
+        let topology = Topology {
+            exchanges: Box::leak(Box::new([exchange])),
+            queues: Box::leak(Box::new([binding])),
+        };
+        let policy = RetryPolicy::default();
+
+        topology.declare_exchanges(&channel).await.unwrap();
+        let durable_queue = QueueDeclareOptions {
+            durable: true,
+            ..Default::default()
+        };
+        channel
+            .queue_declare(legacy_queue, durable_queue, FieldTable::default())
+            .await
+            .unwrap();
+        channel
+            .queue_bind(
+                legacy_queue,
+                exchange,
+                binding.routing_key,
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .unwrap();
+        for body in [b"first", b"other"] {
+            publish(&channel, exchange, binding.routing_key, body).await;
+        }
+
+        topology.declare_queues(&channel, &policy).await.unwrap();
+        assert_eq!(
+            migrate_legacy_queue(&connection, legacy_queue, &binding)
+                .await
+                .unwrap(),
+            2
+        );
+
+        for body in [b"first", b"other"] {
+            let delivery = next_message(&channel, binding.queue).await;
+            assert_eq!(delivery.data, body);
+            delivery.ack(BasicAckOptions::default()).await.unwrap();
+        }
+
+        let passive = QueueDeclareOptions {
+            passive: true,
+            ..Default::default()
+        };
+        let probe = connection.create_channel().await.unwrap();
+        assert!(
+            probe
+                .queue_declare(legacy_queue, passive, FieldTable::default())
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            migrate_legacy_queue(&connection, legacy_queue, &binding)
+                .await
+                .unwrap(),
+            0
+        );
+
+        delete_retryable_queue(&channel, binding.queue, &policy).await;
+        channel
+            .exchange_delete(exchange, Default::default())
+            .await
+            .unwrap();
+    }
+}
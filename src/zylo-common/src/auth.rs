@@ -0,0 +1,589 @@
+use crate::config::{Auth, JwksSource};
+use crate::errors::AuthError;
+use axum::extract::{FromRequestParts, OptionalFromRequestParts, Request, State};
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Header, Validation, decode, decode_header};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[allow(dead_code)]
+#[derive(Deserialize, Serialize)]
+struct Claims {
+    sub: String,
+    aud: String,
+    exp: usize,
+    iss: String,
+    nbf: usize,
+    email_verified: String,
+    #[serde(default, alias = "role", deserialize_with = "one_or_many")]
+    roles: Vec<String>,
+    #[serde(default)]
+    scope: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+/// Authenticated caller, available to handlers through the request extensions.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub user_id: String,
+    pub roles: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+impl Principal {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+impl From<Claims> for Principal {
+    fn from(claims: Claims) -> Self {
+        Self {
+            user_id: claims.sub,
+            roles: claims.roles,
+            scopes: claims.scope.split_whitespace().map(String::from).collect(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct VerificationKey {
+    key: DecodingKey,
+    algorithm: Option<Algorithm>,
+}
+
+/// Holds the keys used to verify access tokens. Asymmetric keys come from a JWKS document
+/// which is reloaded periodically, the shared secret is only used for HS256 tokens and only when
+/// `allow_hs256` is set.
+#[derive(Clone)]
+pub struct JwtKeyStore {
+    config: Auth,
+    hmac_key: Option<DecodingKey>,
+    keys: Arc<RwLock<HashMap<String, VerificationKey>>>,
+    last_refresh: Arc<Mutex<Option<Instant>>>,
+}
+
+impl JwtKeyStore {
+    pub async fn new(config: Auth) -> Self {
+        let hmac_key = config
+            .secret
+            .as_ref()
+            .filter(|_| config.allow_hs256)
+            .map(|secret| DecodingKey::from_secret(secret.as_ref()));
+
+        let key_store = Self {
+            config,
+            hmac_key,
+            keys: Arc::new(RwLock::new(HashMap::new())),
+            last_refresh: Arc::new(Mutex::new(None)),
+        };
+
+        if let Err(e) = key_store.refresh().await {
+            error!("Failed to load JWKS: {}", e);
+        }
+
+        key_store
+    }
+
+    pub fn spawn_refresh(&self) {
+        let Some(jwks) = self.config.jwks.as_ref() else {
+            return;
+        };
+
+        if jwks.url.is_none() || jwks.refresh_interval_seconds == 0 {
+            return;
+        }
+
+        let key_store = self.clone();
+        let period = Duration::from_secs(jwks.refresh_interval_seconds);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = key_store.refresh().await {
+                    warn!("Failed to refresh JWKS: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn refresh(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(source) = self.config.jwks.as_ref() else {
+            return Ok(());
+        };
+
+        *self.last_refresh.lock().await = Some(Instant::now());
+        let jwk_set = load_jwk_set(source).await?;
+
+        let mut keys = HashMap::with_capacity(jwk_set.keys.len());
+        for jwk in &jwk_set.keys {
+            let Some(kid) = jwk.common.key_id.clone() else {
+                warn!("Skipping JWK without a key id");
+                continue;
+            };
+
+            let algorithm = jwk
+                .common
+                .key_algorithm
+                .and_then(|alg| alg.to_string().parse::<Algorithm>().ok());
+
+            match DecodingKey::from_jwk(jwk) {
+                Ok(key) => {
+                    keys.insert(kid, VerificationKey { key, algorithm });
+                }
+                Err(e) => warn!("Skipping JWK {}: {}", kid, e),
+            }
+        }
+
+        info!("Loaded {} verification keys from JWKS", keys.len());
+        *self.keys.write().await = keys;
+        Ok(())
+    }
+
+    async fn refresh_on_unknown_kid(&self) {
+        let can_refresh = self
+            .config
+            .jwks
+            .as_ref()
+            .is_some_and(|jwks| jwks.url.is_some());
+        if !can_refresh {
+            return;
+        }
+
+        let last_refresh = *self.last_refresh.lock().await;
+        if last_refresh.is_some_and(|at| at.elapsed() < MIN_REFRESH_INTERVAL) {
+            return;
+        }
+
+        if let Err(e) = self.refresh().await {
+            warn!("Failed to refresh JWKS: {}", e);
+        }
+    }
+
+    async fn find_key(&self, kid: Option<&str>, algorithm: Algorithm) -> Option<DecodingKey> {
+        let keys = self.keys.read().await;
+        let key = match kid {
+            Some(kid) => keys.get(kid),
+            None if keys.len() == 1 => keys.values().next(),
+            None => None,
+        }?;
+
+        match key.algorithm {
+            Some(expected) if expected != algorithm => None,
+            _ => Some(key.key.clone()),
+        }
+    }
+
+    async fn decoding_key(&self, header: &Header) -> Result<DecodingKey, AuthError> {
+        match header.alg {
+            Algorithm::HS256 => self.hmac_key.clone().ok_or(AuthError::InvalidToken),
+            Algorithm::HS384 | Algorithm::HS512 => Err(AuthError::InvalidToken),
+            algorithm => {
+                let kid = header.kid.as_deref();
+                if let Some(key) = self.find_key(kid, algorithm).await {
+                    return Ok(key);
+                }
+
+                self.refresh_on_unknown_kid().await;
+                self.find_key(kid, algorithm)
+                    .await
+                    .ok_or(AuthError::InvalidToken)
+            }
+        }
+    }
+
+    /// Verifies a bearer token and returns the caller it was issued to.
+    pub async fn authorize(&self, auth_token: &str) -> Result<Principal, AuthError> {
+        let header = decode_header(auth_token).map_err(|_| AuthError::InvalidToken)?;
+        let decoding_key = self.decoding_key(&header).await?;
+
+        let claims = decode::<Claims>(auth_token, &decoding_key, &self.validation(header.alg))
+            .map_err(|_| AuthError::InvalidToken)?;
+
+        if claims.claims.email_verified.eq_ignore_ascii_case("false") {
+            return Err(AuthError::UnverifiedEmail);
+        }
+
+        Ok(Principal::from(claims.claims))
+    }
+
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        validation.set_audience(std::slice::from_ref(&self.config.audience));
+        validation.set_issuer(std::slice::from_ref(&self.config.issuer));
+
+        validation.set_required_spec_claims(&["sub", "aud", "iss", "nbf", "exp"]);
+
+        validation
+    }
+}
+
+async fn load_jwk_set(source: &JwksSource) -> Result<JwkSet, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(url) = source.url.as_ref() {
+        return Ok(reqwest::get(url).await?.error_for_status()?.json::<JwkSet>().await?);
+    }
+
+    if let Some(path) = source.path.as_ref() {
+        let content = tokio::fs::read_to_string(path).await?;
+        return Ok(serde_json::from_str(&content)?);
+    }
+
+    Err("JWKS source requires either a url or a path".into())
+}
+
+pub async fn authorization_middleware(
+    State(key_store): State<JwtKeyStore>,
+    mut req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, AuthError> {
+    let auth_token = req
+        .headers()
+        .get("Authorization")
+        .ok_or(AuthError::TokenNotFound)?
+        .to_str()
+        .map_err(|_| AuthError::TokenNotFound)?;
+
+    let auth_token = auth_token.trim_start_matches("Bearer ").trim();
+
+    let principal = key_store.authorize(auth_token).await?;
+    req.extensions_mut().insert(principal);
+
+    Ok(next.run(req).await)
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Principal>()
+            .cloned()
+            .ok_or(AuthError::TokenNotFound)
+    }
+}
+
+/// A role granted through the `roles` claim, see [`RequireRole`].
+pub trait Role: Send + Sync {
+    const NAME: &'static str;
+}
+
+/// Staff who may change or remove content of other users.
+pub struct Moderator;
+
+impl Role for Moderator {
+    const NAME: &'static str = "moderator";
+}
+
+/// Extracts the caller only when they hold the role `R`, everyone else is rejected with 403.
+/// `Option<RequireRole<R>>` is `None` instead, for routes where the role only extends what the
+/// caller is allowed to do.
+pub struct RequireRole<R: Role> {
+    pub principal: Principal,
+    role: PhantomData<R>,
+}
+
+impl<R: Role, S: Send + Sync> FromRequestParts<S> for RequireRole<R> {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await?
+            .ok_or_else(|| AuthError::MissingRole(R::NAME.to_string()))
+    }
+}
+
+impl<R: Role, S: Send + Sync> OptionalFromRequestParts<S> for RequireRole<R> {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        let principal =
+            <Principal as FromRequestParts<S>>::from_request_parts(parts, state).await?;
+
+        Ok(principal.has_role(R::NAME).then_some(Self {
+            principal,
+            role: PhantomData,
+        }))
+    }
+}
+
+/// Scope required by the routes the `require_scope` middleware is applied to.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireScope(pub &'static str);
+
+pub async fn require_scope(
+    State(RequireScope(scope)): State<RequireScope>,
+    principal: Principal,
+    req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, AuthError> {
+    if !principal.has_scope(scope) {
+        return Err(AuthError::MissingScope(scope.to_string()));
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, encode};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const JWKS: &str = include_str!("../tests/fixtures/jwks.json");
+    /// `test-key` and `other-key`, the public halves of `RSA_KEY` and `OTHER_RSA_KEY`.
+    const JWKS_TWO_KEYS: &str = include_str!("../tests/fixtures/jwks_two_keys.json");
+    const RSA_KEY: &[u8] = include_bytes!("../tests/fixtures/jwt_rs256.pem");
+    const OTHER_RSA_KEY: &[u8] = include_bytes!("../tests/fixtures/jwt_rs256_other.pem");
+    const SECRET: &str = "test_secret";
+
+    fn config(jwks_url: Option<String>, allow_hs256: bool) -> Auth {
+        Auth {
+            secret: Some(SECRET.to_string()),
+            allow_hs256,
+            issuer: "test_issuer".to_string(),
+            audience: "test_audience".to_string(),
+            jwks: jwks_url.map(|url| JwksSource {
+                url: Some(url),
+                path: None,
+                refresh_interval_seconds: 0,
+            }),
+        }
+    }
+
+    fn token(header: Header, key: &EncodingKey) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = json!({
+            "sub": "01HZY7XJ5QK9V8T4W2M3N6P1RS",
+            "aud": "test_audience",
+            "iss": "test_issuer",
+            "nbf": now - 60,
+            "exp": now + 600,
+            "email_verified": "true",
+            "roles": ["admin"],
+            "scope": "posts:write",
+        });
+
+        encode(&header, &claims, key).unwrap()
+    }
+
+    fn rs256_token(kid: &str) -> String {
+        signed_token(kid, RSA_KEY)
+    }
+
+    fn signed_token(kid: &str, pem: &[u8]) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(kid.to_string());
+        token(header, &EncodingKey::from_rsa_pem(pem).unwrap())
+    }
+
+    fn hmac_token(algorithm: Algorithm) -> String {
+        let key = EncodingKey::from_secret(SECRET.as_bytes());
+        token(Header::new(algorithm), &key)
+    }
+
+    async fn jwks_server() -> MockServer {
+        let server = MockServer::start().await;
+        serve_jwks(&server, JWKS).await;
+        server
+    }
+
+    /// Replaces what the server answers with, the way a key rotation would.
+    async fn serve_jwks(server: &MockServer, jwks: &'static str) {
+        server.reset().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/jwks.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(jwks, "application/json"))
+            .mount(server)
+            .await;
+    }
+
+    fn jwks_url(server: &MockServer) -> String {
+        format!("{}/.well-known/jwks.json", server.uri())
+    }
+
+    #[tokio::test]
+    async fn authorize_accepts_a_token_signed_with_a_key_from_the_jwks() {
+        let server = jwks_server().await;
+        let key_store = JwtKeyStore::new(config(Some(jwks_url(&server)), false)).await;
+
+        let principal = key_store.authorize(&rs256_token("test-key")).await.unwrap();
+
+        assert_eq!(principal.user_id, "01HZY7XJ5QK9V8T4W2M3N6P1RS");
+        assert!(principal.has_role("admin"));
+        assert!(principal.has_scope("posts:write"));
+    }
+
+    #[tokio::test]
+    async fn authorize_rejects_a_token_with_an_unknown_key_id() {
+        let server = jwks_server().await;
+        let key_store = JwtKeyStore::new(config(Some(jwks_url(&server)), false)).await;
+
+        let result = key_store.authorize(&rs256_token("rotated-key")).await;
+
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn authorize_verifies_each_token_with_the_key_of_its_key_id() {
+        let server = MockServer::start().await;
+        serve_jwks(&server, JWKS_TWO_KEYS).await;
+        let key_store = JwtKeyStore::new(config(Some(jwks_url(&server)), false)).await;
+
+        assert!(key_store.authorize(&rs256_token("test-key")).await.is_ok());
+        assert!(
+            key_store
+                .authorize(&signed_token("other-key", OTHER_RSA_KEY))
+                .await
+                .is_ok()
+        );
+
+        let result = key_store
+            .authorize(&signed_token("test-key", OTHER_RSA_KEY))
+            .await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn authorize_reloads_the_jwks_for_a_rotated_key() {
+        let server = jwks_server().await;
+        let key_store = JwtKeyStore::new(config(Some(jwks_url(&server)), false)).await;
+        serve_jwks(&server, JWKS_TWO_KEYS).await;
+        // The JWKS was loaded when the store was created, long enough ago to be reloaded.
+        *key_store.last_refresh.lock().await = Some(Instant::now() - MIN_REFRESH_INTERVAL);
+
+        let principal = key_store
+            .authorize(&signed_token("other-key", OTHER_RSA_KEY))
+            .await
+            .unwrap();
+
+        assert_eq!(principal.user_id, "01HZY7XJ5QK9V8T4W2M3N6P1RS");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn authorize_reloads_the_jwks_at_most_once_per_refresh_interval() {
+        let server = jwks_server().await;
+        let key_store = JwtKeyStore::new(config(Some(jwks_url(&server)), false)).await;
+        serve_jwks(&server, JWKS_TWO_KEYS).await;
+
+        for _ in 0..3 {
+            let result = key_store
+                .authorize(&signed_token("other-key", OTHER_RSA_KEY))
+                .await;
+            assert!(matches!(result, Err(AuthError::InvalidToken)));
+        }
+
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn authorize_rejects_hs256_unless_it_is_allowed() {
+        let key_store = JwtKeyStore::new(config(None, false)).await;
+
+        let result = key_store.authorize(&hmac_token(Algorithm::HS256)).await;
+
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+    }
+
+    #[tokio::test]
+    async fn authorize_accepts_hs256_when_it_is_allowed() {
+        let key_store = JwtKeyStore::new(config(None, true)).await;
+
+        let principal = key_store
+            .authorize(&hmac_token(Algorithm::HS256))
+            .await
+            .unwrap();
+
+        assert_eq!(principal.user_id, "01HZY7XJ5QK9V8T4W2M3N6P1RS");
+    }
+
+    #[tokio::test]
+    async fn authorize_rejects_other_hmac_algorithms_even_when_hs256_is_allowed() {
+        let key_store = JwtKeyStore::new(config(None, true)).await;
+
+        for algorithm in [Algorithm::HS384, Algorithm::HS512] {
+            let result = key_store.authorize(&hmac_token(algorithm)).await;
+            assert!(matches!(result, Err(AuthError::InvalidToken)));
+        }
+    }
+
+    fn parts_with_roles(roles: &[&str]) -> Parts {
+        let (mut parts, _) = axum::http::Request::new(()).into_parts();
+        parts.extensions.insert(Principal {
+            user_id: "01HZY7XJ5QK9V8T4W2M3N6P1RS".to_string(),
+            roles: roles.iter().map(|role| role.to_string()).collect(),
+            scopes: Vec::new(),
+        });
+        parts
+    }
+
+    #[tokio::test]
+    async fn require_role_extracts_a_caller_holding_the_role() {
+        let mut parts = parts_with_roles(&["moderator"]);
+
+        let moderator =
+            <RequireRole<Moderator> as FromRequestParts<()>>::from_request_parts(&mut parts, &())
+                .await
+                .unwrap();
+
+        assert_eq!(moderator.principal.user_id, "01HZY7XJ5QK9V8T4W2M3N6P1RS");
+    }
+
+    #[tokio::test]
+    async fn require_role_rejects_a_caller_without_the_role() {
+        let mut parts = parts_with_roles(&["admin"]);
+
+        let result =
+            <RequireRole<Moderator> as FromRequestParts<()>>::from_request_parts(&mut parts, &())
+                .await;
+
+        assert!(matches!(result, Err(AuthError::MissingRole(role)) if role == "moderator"));
+    }
+
+    #[tokio::test]
+    async fn optional_require_role_is_none_for_a_caller_without_the_role() {
+        let mut parts = parts_with_roles(&[]);
+
+        let result = <RequireRole<Moderator> as OptionalFromRequestParts<()>>::from_request_parts(
+            &mut parts,
+            &(),
+        )
+        .await;
+
+        assert!(matches!(result, Ok(None)));
+    }
+}
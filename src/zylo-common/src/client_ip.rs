@@ -0,0 +1,101 @@
+use axum::extract::{ConnectInfo, Request};
+use axum::http::HeaderMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Address of the client that made `req`. Behind `trusted_proxies` proxies it is taken from
+/// `X-Forwarded-For`, see [`forwarded_client_ip`]; with no trusted proxy, or when the header does
+/// not hold enough hops, it is the address of the peer.
+pub fn client_ip(req: &Request, trusted_proxies: usize) -> IpAddr {
+    if let Some(ip) = forwarded_client_ip(req.headers(), trusted_proxies) {
+        return ip;
+    }
+
+    req.extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+}
+
+/// Every proxy appends the address it received the request from, so only the last
+/// `trusted_proxies` hops were written by our own infrastructure. The hop the outermost trusted
+/// proxy appended is the client; anything left of it is whatever the client chose to send.
+pub fn forwarded_client_ip(headers: &HeaderMap, trusted_proxies: usize) -> Option<IpAddr> {
+    let hops: Vec<&str> = headers
+        .get_all(FORWARDED_FOR_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    if trusted_proxies == 0 || hops.len() < trusted_proxies {
+        return None;
+    }
+
+    hops[hops.len() - trusted_proxies].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::HeaderValue;
+
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(FORWARDED_FOR_HEADER, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn forwarded_client_ip_ignores_hops_spoofed_by_the_client() {
+        let headers = forwarded_for(&["1.1.1.1, 203.0.113.7"]);
+
+        assert_eq!(
+            forwarded_client_ip(&headers, 1),
+            Some("203.0.113.7".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn forwarded_client_ip_skips_the_trusted_proxies() {
+        let headers = forwarded_for(&["1.1.1.1, 203.0.113.7", "10.0.0.2"]);
+
+        assert_eq!(
+            forwarded_client_ip(&headers, 2),
+            Some("203.0.113.7".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn forwarded_client_ip_is_none_with_fewer_hops_than_trusted_proxies() {
+        let headers = forwarded_for(&["203.0.113.7"]);
+
+        assert_eq!(forwarded_client_ip(&headers, 2), None);
+        assert_eq!(forwarded_client_ip(&HeaderMap::new(), 1), None);
+    }
+
+    #[test]
+    fn forwarded_client_ip_is_none_for_an_invalid_hop() {
+        let headers = forwarded_for(&["203.0.113.7, unknown"]);
+
+        assert_eq!(forwarded_client_ip(&headers, 1), None);
+    }
+
+    #[test]
+    fn client_ip_uses_the_peer_without_trusted_proxies() {
+        let mut req = Request::builder()
+            .header(FORWARDED_FOR_HEADER, "1.1.1.1")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 3], 443))));
+
+        assert_eq!(client_ip(&req, 0), IpAddr::from([10, 0, 0, 3]));
+        assert_eq!(client_ip(&req, 1), IpAddr::from([1, 1, 1, 1]));
+    }
+}
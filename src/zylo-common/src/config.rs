@@ -0,0 +1,242 @@
+use crate::constants::{
+    AMQ_CONCURRENCY, AMQ_DRAIN_TIMEOUT, AMQ_PREFETCH, JWKS_PATH, JWKS_REFRESH_INTERVAL, JWKS_URL,
+    JWT_ALLOW_HS256, JWT_AUDIENCE, JWT_ISSUER, JWT_SECRET, RATE_LIMIT_TRUST_FORWARDED_FOR,
+    RATE_LIMIT_TRUSTED_PROXIES,
+};
+use crate::key_vault::KeyVault;
+use axum::http::Method;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwksSource {
+    pub url: Option<String>,
+    pub path: Option<String>,
+    pub refresh_interval_seconds: u64,
+}
+
+impl JwksSource {
+    pub async fn from_key_vault(key_vault: &KeyVault) -> Option<Self> {
+        let url = key_vault.get_secret(JWKS_URL).await.ok();
+        let path = key_vault.get_secret(JWKS_PATH).await.ok();
+        if url.is_none() && path.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            url,
+            path,
+            refresh_interval_seconds: key_vault
+                .get_secret(JWKS_REFRESH_INTERVAL)
+                .await
+                .ok()
+                .and_then(|interval| interval.parse().ok())
+                .unwrap_or(300),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Auth {
+    pub secret: Option<String>,
+    /// Also accepts HS256 tokens signed with `secret`. Meant for local development only, every
+    /// other environment verifies tokens against the JWKS.
+    #[serde(default)]
+    pub allow_hs256: bool,
+    pub issuer: String,
+    pub audience: String,
+    pub jwks: Option<JwksSource>,
+}
+
+impl Auth {
+    pub async fn from_key_vault(key_vault: &KeyVault) -> Self {
+        Self {
+            secret: key_vault.get_secret(JWT_SECRET).await.ok(),
+            allow_hs256: key_vault
+                .get_secret(JWT_ALLOW_HS256)
+                .await
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            issuer: key_vault.get_secret(JWT_ISSUER).await.unwrap(),
+            audience: key_vault.get_secret(JWT_AUDIENCE).await.unwrap(),
+            jwks: JwksSource::from_key_vault(key_vault).await,
+        }
+    }
+}
+
+/// Prefetch and handler concurrency of a single queue consumer.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ConsumerSettings {
+    pub prefetch: u16,
+    pub concurrency: u32,
+}
+
+impl Default for ConsumerSettings {
+    fn default() -> Self {
+        Self {
+            prefetch: 10,
+            concurrency: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AmqConsumers {
+    #[serde(default)]
+    pub default: ConsumerSettings,
+    /// Overrides keyed by queue name.
+    #[serde(default)]
+    pub queues: HashMap<String, ConsumerSettings>,
+    #[serde(default = "default_drain_timeout_seconds")]
+    pub drain_timeout_seconds: u64,
+}
+
+fn default_drain_timeout_seconds() -> u64 {
+    20
+}
+
+impl Default for AmqConsumers {
+    fn default() -> Self {
+        Self {
+            default: ConsumerSettings::default(),
+            queues: HashMap::new(),
+            drain_timeout_seconds: default_drain_timeout_seconds(),
+        }
+    }
+}
+
+impl AmqConsumers {
+    pub fn for_queue(&self, queue_name: &str) -> ConsumerSettings {
+        self.queues.get(queue_name).copied().unwrap_or(self.default)
+    }
+
+    pub async fn from_key_vault(key_vault: &KeyVault) -> Self {
+        let defaults = Self::default();
+        let secret = |name: &'static str| async move {
+            key_vault
+                .get_secret(name)
+                .await
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+
+        Self {
+            default: ConsumerSettings {
+                prefetch: secret(AMQ_PREFETCH)
+                    .await
+                    .map_or(defaults.default.prefetch, |value| value as u16),
+                concurrency: secret(AMQ_CONCURRENCY)
+                    .await
+                    .map_or(defaults.default.concurrency, |value| value as u32),
+            },
+            queues: HashMap::new(),
+            drain_timeout_seconds: secret(AMQ_DRAIN_TIMEOUT)
+                .await
+                .unwrap_or(defaults.drain_timeout_seconds),
+        }
+    }
+}
+
+/// Requests a caller may make to a route within a sliding window. `path` is the route as it is
+/// registered with the router, e.g. `/api/posts/{postId}/replies`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteRateLimit {
+    pub method: String,
+    pub path: String,
+    pub max_requests: u32,
+    pub window_seconds: u64,
+}
+
+impl RouteRateLimit {
+    /// Parses `METHOD /path max_requests window_seconds`.
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+        let (Some(method), Some(path), Some(max_requests), Some(window_seconds), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return None;
+        };
+
+        Some(Self {
+            method: method.to_uppercase(),
+            path: path.to_string(),
+            max_requests: max_requests.parse().ok()?,
+            window_seconds: window_seconds.parse().ok()?,
+        })
+    }
+}
+
+/// Per route limits, applied separately to every authenticated user and client ip address.
+/// Routes without a limit are not limited.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimits {
+    #[serde(default)]
+    pub routes: Vec<RouteRateLimit>,
+    /// Takes the client ip address from `X-Forwarded-For`, for services behind a proxy.
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+    /// Proxies in front of the service that append to `X-Forwarded-For`.
+    #[serde(default = "default_trusted_proxies")]
+    pub trusted_proxies: usize,
+}
+
+fn default_trusted_proxies() -> usize {
+    1
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            trust_forwarded_for: false,
+            trusted_proxies: default_trusted_proxies(),
+        }
+    }
+}
+
+impl RateLimits {
+    /// Proxies whose `X-Forwarded-For` hops are trusted, none unless `trust_forwarded_for` is set.
+    pub fn forwarded_hops(&self) -> usize {
+        if self.trust_forwarded_for {
+            self.trusted_proxies
+        } else {
+            0
+        }
+    }
+
+    pub fn for_route(&self, method: &Method, path: &str) -> Option<&RouteRateLimit> {
+        self.routes
+            .iter()
+            .find(|route| route.method.eq_ignore_ascii_case(method.as_str()) && route.path == path)
+    }
+
+    /// Reads the limits from the comma separated list in the `routes` secret, each entry in the
+    /// `METHOD /path max_requests window_seconds` format.
+    pub async fn from_key_vault(key_vault: &KeyVault, routes: &str) -> Self {
+        let routes = key_vault.get_secret(routes).await.unwrap_or_default();
+
+        Self {
+            routes: routes
+                .split(',')
+                .filter_map(RouteRateLimit::parse)
+                .collect(),
+            trust_forwarded_for: key_vault
+                .get_secret(RATE_LIMIT_TRUST_FORWARDED_FOR)
+                .await
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            trusted_proxies: key_vault
+                .get_secret(RATE_LIMIT_TRUSTED_PROXIES)
+                .await
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(default_trusted_proxies),
+        }
+    }
+}
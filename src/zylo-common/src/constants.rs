@@ -0,0 +1,14 @@
+pub const JWT_SECRET: &str = "Zylo-Jwt--Secret";
+pub const JWT_ALLOW_HS256: &str = "Zylo-Jwt--AllowHs256";
+pub const JWT_ISSUER: &str = "Zylo-Jwt--Issuer";
+pub const JWT_AUDIENCE: &str = "Zylo-Jwt--Audience";
+pub const JWKS_URL: &str = "Zylo-Jwt--JwksUrl";
+pub const JWKS_PATH: &str = "Zylo-Jwt--JwksPath";
+pub const JWKS_REFRESH_INTERVAL: &str = "Zylo-Jwt--JwksRefreshInterval";
+
+pub const AMQ_PREFETCH: &str = "Zylo-RabbitMq--Prefetch";
+pub const AMQ_CONCURRENCY: &str = "Zylo-RabbitMq--Concurrency";
+pub const AMQ_DRAIN_TIMEOUT: &str = "Zylo-RabbitMq--DrainTimeoutSeconds";
+
+pub const RATE_LIMIT_TRUST_FORWARDED_FOR: &str = "Zylo-RateLimit--TrustForwardedFor";
+pub const RATE_LIMIT_TRUSTED_PROXIES: &str = "Zylo-RateLimit--TrustedProxies";
@@ -1,6 +1,7 @@
+use crate::errors::ProblemResponse;
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use thiserror::Error;
-use crate::errors::ProblemResponse;
 
 #[derive(Error, Debug)]
 pub enum AuthError {
@@ -12,15 +13,27 @@ pub enum AuthError {
 
     #[error("Email is not confirmed")]
     UnverifiedEmail,
+
+    #[error("Missing required scope: {0}")]
+    MissingScope(String),
+
+    #[error("Missing required role: {0}")]
+    MissingRole(String),
 }
 
 impl ProblemResponse for AuthError {
     fn status_code(&self) -> StatusCode {
-        StatusCode::UNAUTHORIZED
+        match self {
+            AuthError::MissingScope(_) | AuthError::MissingRole(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        }
     }
 
     fn title(&self) -> &'static str {
-        "Authentication Error"
+        match self {
+            AuthError::MissingScope(_) | AuthError::MissingRole(_) => "Forbidden",
+            _ => "Authentication Error",
+        }
     }
 
     fn detail(&self) -> String {
@@ -32,6 +45,15 @@ impl ProblemResponse for AuthError {
             AuthError::TokenNotFound => String::from("Bearer token not found"),
             AuthError::InvalidToken => String::from("Invalid token"),
             AuthError::UnverifiedEmail => String::from("Email is not confirmed"),
+            AuthError::MissingScope(_) | AuthError::MissingRole(_) => {
+                String::from("You are not allowed to perform this action")
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        self.to_response()
+    }
+}
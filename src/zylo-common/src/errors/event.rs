@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EventError {
+    #[error("Malformed event: {0}")]
+    Malformed(#[from] serde_json::Error),
+    #[error("Expected event of type {expected}, got {actual}")]
+    UnexpectedType { expected: String, actual: String },
+    #[error("Unsupported version {version} of event {event_type}")]
+    UnsupportedVersion { event_type: String, version: u32 },
+}
@@ -0,0 +1,10 @@
+use std::env::VarError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum KeyVaultError {
+    #[error("Error making the request: {0}")]
+    ReqwestError(#[from] reqwest::Error),
+    #[error("Env error: {0}")]
+    EnvironmentVariableNotFound(#[from] VarError),
+}
@@ -0,0 +1,11 @@
+mod auth;
+mod event;
+mod key_vault;
+mod problem;
+mod rate_limit;
+
+pub use auth::AuthError;
+pub use event::EventError;
+pub use key_vault::KeyVaultError;
+pub use problem::ProblemResponse;
+pub use rate_limit::RateLimitError;
@@ -0,0 +1,31 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use opentelemetry::trace::TraceContextExt;
+use serde_json::json;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+pub trait ProblemResponse {
+    fn status_code(&self) -> StatusCode;
+    fn title(&self) -> &str;
+    fn detail(&self) -> String;
+
+    fn public_detail(&self) -> String {
+        String::from("An unexpected server error occurred. Please try again later.")
+    }
+
+    fn to_response(&self) -> Response {
+        let context = Span::current().context();
+        let trace_id = context.span().span_context().trace_id().to_string();
+        let body = json!({
+            "type": format!("https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/{}", self.status_code()),
+            "title": self.title(),
+            "status": self.status_code().as_u16(),
+            "detail": self.public_detail(),
+            "traceId": trace_id
+        });
+
+        (self.status_code(), Json(body)).into_response()
+    }
+}
@@ -0,0 +1,47 @@
+use crate::errors::ProblemResponse;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RateLimitError {
+    #[error("Rate limit exceeded, retry after {retry_after} seconds")]
+    TooManyRequests { retry_after: u64 },
+}
+
+impl RateLimitError {
+    pub fn retry_after(&self) -> u64 {
+        match self {
+            RateLimitError::TooManyRequests { retry_after } => *retry_after,
+        }
+    }
+}
+
+impl ProblemResponse for RateLimitError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn title(&self) -> &'static str {
+        "Too Many Requests"
+    }
+
+    fn detail(&self) -> String {
+        self.to_string()
+    }
+
+    fn public_detail(&self) -> String {
+        String::from("Too many requests. Please try again later.")
+    }
+}
+
+impl IntoResponse for RateLimitError {
+    fn into_response(self) -> Response {
+        let mut response = self.to_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(self.retry_after()));
+
+        response
+    }
+}
@@ -0,0 +1,120 @@
+use crate::errors::EventError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use opentelemetry::global;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use ulid::Ulid;
+
+/// Payload of a message exchanged between services. `TYPE` and `VERSION` identify the JSON
+/// Schema under `src/schemas/events` that the payload has to match.
+pub trait Event {
+    const TYPE: &'static str;
+    const VERSION: u32;
+}
+
+/// Formats a point in time the way events carry it, e.g. `2026-10-19T10:00:00.000Z`.
+pub fn format_timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Envelope wrapping every published event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope<T> {
+    pub id: Ulid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub version: u32,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: String,
+    #[serde(
+        rename = "traceContext",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub trace_context: HashMap<String, String>,
+    pub payload: T,
+}
+
+impl<T: Event> EventEnvelope<T> {
+    /// Wraps `payload`, capturing the trace context of the current span.
+    pub fn new(payload: T) -> Self {
+        let mut trace_context = HashMap::new();
+        let context = tracing::Span::current().context();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut trace_context)
+        });
+
+        Self {
+            id: Ulid::new(),
+            event_type: T::TYPE.to_string(),
+            version: T::VERSION,
+            occurred_at: format_timestamp(Utc::now()),
+            trace_context,
+            payload,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IncomingEvent {
+    Envelope(EventEnvelope<Value>),
+    Bare(Value),
+}
+
+/// Decodes a message into an envelope of `T`, rejecting other types and unknown versions.
+///
+/// Messages from producers that do not use the envelope yet are treated as version 1 of the
+/// expected type.
+pub fn decode<T: Event + DeserializeOwned>(data: &[u8]) -> Result<EventEnvelope<T>, EventError> {
+    let envelope = match serde_json::from_slice(data)? {
+        IncomingEvent::Envelope(envelope) => envelope,
+        IncomingEvent::Bare(payload) => EventEnvelope {
+            id: Ulid::new(),
+            event_type: T::TYPE.to_string(),
+            version: 1,
+            occurred_at: format_timestamp(Utc::now()),
+            trace_context: HashMap::new(),
+            payload,
+        },
+    };
+
+    if envelope.event_type != T::TYPE {
+        return Err(EventError::UnexpectedType {
+            expected: T::TYPE.to_string(),
+            actual: envelope.event_type,
+        });
+    }
+
+    if envelope.version != T::VERSION {
+        return Err(EventError::UnsupportedVersion {
+            event_type: envelope.event_type,
+            version: envelope.version,
+        });
+    }
+
+    Ok(EventEnvelope {
+        id: envelope.id,
+        event_type: envelope.event_type,
+        version: envelope.version,
+        occurred_at: envelope.occurred_at,
+        trace_context: envelope.trace_context,
+        payload: serde_json::from_value(envelope.payload)?,
+    })
+}
+
+#[derive(Deserialize)]
+struct TraceContextOnly {
+    #[serde(rename = "traceContext", default)]
+    trace_context: HashMap<String, String>,
+}
+
+/// Trace context stored in a serialized envelope, empty when there is none.
+pub fn trace_context_of(envelope: &[u8]) -> HashMap<String, String> {
+    serde_json::from_slice::<TraceContextOnly>(envelope)
+        .map(|envelope| envelope.trace_context)
+        .unwrap_or_default()
+}
@@ -0,0 +1,50 @@
+use opentelemetry::global;
+use opentelemetry::propagation::{Extractor, Injector};
+use tonic::Request;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Reads the trace context propagated by gRPC clients.
+pub struct MetadataMap<'a>(pub &'a tonic::metadata::MetadataMap);
+
+impl Extractor for MetadataMap<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|metadata| metadata.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .keys()
+            .map(|key| match key {
+                tonic::metadata::KeyRef::Ascii(v) => v.as_str(),
+                tonic::metadata::KeyRef::Binary(v) => v.as_str(),
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Writes the current trace context into outgoing gRPC requests.
+pub struct MetadataMapMut<'a>(pub &'a mut tonic::metadata::MetadataMap);
+
+impl Injector for MetadataMapMut<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let Ok(key) = tonic::metadata::MetadataKey::from_bytes(key.as_bytes())
+            && let Ok(val) = tonic::metadata::MetadataValue::try_from(&value)
+        {
+            self.0.insert(key, val);
+        }
+    }
+}
+
+pub trait InjectTraceContext {
+    fn inject_trace_context(self) -> Self;
+}
+
+impl<T> InjectTraceContext for Request<T> {
+    fn inject_trace_context(mut self) -> Self {
+        let cx = tracing::Span::current().context();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut MetadataMapMut(self.metadata_mut()))
+        });
+        self
+    }
+}
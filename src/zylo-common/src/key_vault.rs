@@ -21,7 +21,7 @@ pub struct KeyVault {
 }
 
 impl KeyVault {
-    pub async fn new() -> Result<Self, errors::AppError> {
+    pub async fn new() -> Result<Self, errors::KeyVaultError> {
         let client_id = env::var("AZURE_CLIENT_ID")?;
         let tenant_id = env::var("AZURE_TENANT_ID")?;
         let client_secret = env::var("AZURE_CLIENT_SECRET")?;
@@ -53,7 +53,7 @@ impl KeyVault {
         })
     }
 
-    pub async fn get_secret(&self, secret_name: &str) -> Result<String, errors::AppError> {
+    pub async fn get_secret(&self, secret_name: &str) -> Result<String, errors::KeyVaultError> {
         let vault_url = env::var("AZURE_KEY_VAULT_URL")?;
         let secret_url = format!("{}/secrets/{}?api-version=7.4", vault_url, secret_name);
 
@@ -0,0 +1,13 @@
+pub mod amq;
+pub mod auth;
+pub mod client_ip;
+pub mod config;
+pub mod constants;
+pub mod errors;
+pub mod events;
+pub mod grpc;
+pub mod key_vault;
+pub mod outbox;
+pub mod rate_limit;
+pub mod telemetry;
+pub mod utils;
@@ -0,0 +1,268 @@
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const BATCH_SIZE: usize = 50;
+const LOCK_DURATION: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+const RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Event stored in a service's outbox until the relay publishes it.
+pub trait OutboxEntry: Send + Sync {
+    fn id(&self) -> Ulid;
+    fn routing_key(&self) -> &str;
+    /// Delivery attempts so far, including the one in progress.
+    fn attempts(&self) -> u32;
+}
+
+/// Storage of the outbox, written in the same transaction as the change an event describes.
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    type Message: OutboxEntry;
+    type Error: fmt::Debug + Send;
+
+    /// Leases up to `limit` pending messages for `lock_for`, skipping those other relays hold.
+    async fn claim_batch(
+        &self,
+        limit: usize,
+        lock_for: Duration,
+    ) -> Result<Vec<Self::Message>, Self::Error>;
+    async fn mark_sent(&self, id: &Ulid) -> Result<(), Self::Error>;
+    async fn mark_failed(
+        &self,
+        id: &Ulid,
+        error: &str,
+        retry_in: Duration,
+    ) -> Result<(), Self::Error>;
+    async fn delete_sent_before(&self, older_than: Duration) -> Result<u64, Self::Error>;
+}
+
+#[async_trait]
+pub trait OutboxPublisher<M>: Send + Sync {
+    type Error: fmt::Display + Send;
+
+    /// Publishes a stored message and waits until the broker confirms it.
+    async fn publish_confirmed(&self, message: &M) -> Result<(), Self::Error>;
+}
+
+/// Publishes events stored in the outbox. A message is only marked as sent after the broker
+/// confirms it, so consumers may receive the same event more than once. A message that fails
+/// is retried with an exponential backoff without holding up the rest of its batch.
+pub struct OutboxRelay<S, P> {
+    store: Arc<S>,
+    publisher: Arc<P>,
+}
+
+impl<S, P> OutboxRelay<S, P>
+where
+    S: OutboxStore + 'static,
+    P: OutboxPublisher<S::Message> + 'static,
+{
+    pub fn new(store: Arc<S>, publisher: Arc<P>) -> Self {
+        Self { store, publisher }
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            info!("Outbox relay started");
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            let mut last_cleanup = Instant::now();
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.relay_pending().await {
+                    error!("Failed to relay outbox messages: {:?}", e);
+                }
+
+                if last_cleanup.elapsed() >= CLEANUP_INTERVAL {
+                    last_cleanup = Instant::now();
+                    self.cleanup().await;
+                }
+            }
+        });
+    }
+
+    /// Publishes one batch of pending messages and returns how many of them were sent.
+    pub async fn relay_pending(&self) -> Result<usize, S::Error> {
+        let batch = self.store.claim_batch(BATCH_SIZE, LOCK_DURATION).await?;
+
+        let mut sent = 0;
+        for message in batch {
+            if let Err(e) = self.publisher.publish_confirmed(&message).await {
+                warn!(
+                    "Failed to publish outbox message {} ({}), attempt {}: {}",
+                    message.id(),
+                    message.routing_key(),
+                    message.attempts(),
+                    e
+                );
+
+                self.store
+                    .mark_failed(
+                        &message.id(),
+                        &e.to_string(),
+                        retry_delay(message.attempts()),
+                    )
+                    .await?;
+                continue;
+            }
+
+            self.store.mark_sent(&message.id()).await?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+
+    async fn cleanup(&self) {
+        match self.store.delete_sent_before(RETENTION).await {
+            Ok(deleted) if deleted > 0 => info!("Removed {} sent outbox messages", deleted),
+            Ok(_) => {}
+            Err(e) => error!("Failed to remove sent outbox messages: {:?}", e),
+        }
+    }
+}
+
+fn retry_delay(attempts: u32) -> Duration {
+    let seconds = 2u64.saturating_pow(attempts.min(16));
+    Duration::from_secs(seconds).min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    struct Message {
+        id: Ulid,
+        attempts: u32,
+    }
+
+    impl OutboxEntry for Message {
+        fn id(&self) -> Ulid {
+            self.id
+        }
+
+        fn routing_key(&self) -> &str {
+            "post.created"
+        }
+
+        fn attempts(&self) -> u32 {
+            self.attempts
+        }
+    }
+
+    #[derive(Default)]
+    struct Store {
+        pending: Mutex<Vec<(Ulid, u32)>>,
+        sent: Mutex<Vec<Ulid>>,
+        failed: Mutex<Vec<(Ulid, Duration)>>,
+    }
+
+    #[async_trait]
+    impl OutboxStore for Store {
+        type Message = Message;
+        type Error = String;
+
+        async fn claim_batch(
+            &self,
+            limit: usize,
+            _lock_for: Duration,
+        ) -> Result<Vec<Message>, String> {
+            let mut pending = self.pending.lock().unwrap();
+            let claimed = pending.len().min(limit);
+            Ok(pending
+                .drain(..claimed)
+                .map(|(id, attempts)| Message {
+                    id,
+                    attempts: attempts + 1,
+                })
+                .collect())
+        }
+
+        async fn mark_sent(&self, id: &Ulid) -> Result<(), String> {
+            self.sent.lock().unwrap().push(*id);
+            Ok(())
+        }
+
+        async fn mark_failed(
+            &self,
+            id: &Ulid,
+            _error: &str,
+            retry_in: Duration,
+        ) -> Result<(), String> {
+            self.failed.lock().unwrap().push((*id, retry_in));
+            Ok(())
+        }
+
+        async fn delete_sent_before(&self, _older_than: Duration) -> Result<u64, String> {
+            Ok(0)
+        }
+    }
+
+    struct Publisher {
+        rejected: HashSet<Ulid>,
+    }
+
+    #[async_trait]
+    impl OutboxPublisher<Message> for Publisher {
+        type Error = String;
+
+        async fn publish_confirmed(&self, message: &Message) -> Result<(), String> {
+            match self.rejected.contains(&message.id) {
+                true => Err(String::from("not confirmed")),
+                false => Ok(()),
+            }
+        }
+    }
+
+    fn relay(pending: Vec<(Ulid, u32)>, rejected: &[Ulid]) -> OutboxRelay<Store, Publisher> {
+        let store = Store {
+            pending: Mutex::new(pending),
+            ..Default::default()
+        };
+        let publisher = Publisher {
+            rejected: rejected.iter().copied().collect(),
+        };
+
+        OutboxRelay::new(Arc::new(store), Arc::new(publisher))
+    }
+
+    #[tokio::test]
+    async fn relay_pending_keeps_publishing_after_a_failed_message() {
+        let ids: Vec<Ulid> = (0..3).map(|_| Ulid::new()).collect();
+        let relay = relay(ids.iter().map(|id| (*id, 0)).collect(), &[ids[0]]);
+
+        let sent = relay.relay_pending().await.unwrap();
+
+        assert_eq!(sent, 2);
+        assert_eq!(*relay.store.sent.lock().unwrap(), vec![ids[1], ids[2]]);
+        assert_eq!(
+            *relay.store.failed.lock().unwrap(),
+            vec![(ids[0], Duration::from_secs(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn relay_pending_claims_at_most_one_batch() {
+        let pending = (0..BATCH_SIZE + 5).map(|_| (Ulid::new(), 0)).collect();
+        let relay = relay(pending, &[]);
+
+        assert_eq!(relay.relay_pending().await.unwrap(), BATCH_SIZE);
+        assert_eq!(relay.relay_pending().await.unwrap(), 5);
+        assert_eq!(relay.relay_pending().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn retry_delay_backs_off_exponentially_up_to_the_maximum() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(4), Duration::from_secs(16));
+        assert_eq!(retry_delay(9), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
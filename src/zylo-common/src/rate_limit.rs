@@ -0,0 +1,262 @@
+use crate::auth::Principal;
+use crate::client_ip::client_ip;
+use crate::config::{RateLimits, RouteRateLimit};
+use crate::errors::RateLimitError;
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use redis::{Client, RedisError, Script};
+use std::sync::Arc;
+use tracing::warn;
+use ulid::Ulid;
+
+/// Keeps the time of every request made in the window in a sorted set per key. Returns 0 and
+/// records the request in every window when they all have room, otherwise the milliseconds until
+/// each full window has room again, recording nothing. Redis' clock is used so every instance of
+/// a service agrees on the window.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window = tonumber(ARGV[1])
+local retry_after = 0
+
+for _, key in ipairs(KEYS) do
+    redis.call('ZREMRANGEBYSCORE', key, '-inf', now - window)
+    if redis.call('ZCARD', key) >= tonumber(ARGV[2]) then
+        local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+        retry_after = math.max(retry_after, tonumber(oldest[2]) + window - now, 1)
+    end
+end
+
+if retry_after > 0 then
+    return retry_after
+end
+
+for _, key in ipairs(KEYS) do
+    redis.call('ZADD', key, now, ARGV[3])
+    redis.call('PEXPIRE', key, window)
+end
+return 0
+"#;
+
+/// Sliding window rate limiter shared by every instance of a service through Redis. Requests
+/// count against both the authenticated user and the client ip address. When Redis can not be
+/// reached requests are let through rather than failed.
+#[derive(Clone)]
+pub struct RateLimiter {
+    redis: Client,
+    script: Script,
+    limits: Arc<RateLimits>,
+    namespace: String,
+}
+
+impl RateLimiter {
+    pub fn new(redis_uri: &str, limits: RateLimits, namespace: &str) -> Result<Self, RedisError> {
+        Ok(Self {
+            redis: Client::open(redis_uri)?,
+            script: Script::new(SLIDING_WINDOW_SCRIPT),
+            limits: Arc::new(limits),
+            namespace: namespace.to_string(),
+        })
+    }
+
+    /// Records a request to `route` against every one of `subjects`, or returns the seconds to
+    /// wait when the window of any of them is full. A rejected request counts against none.
+    async fn acquire(
+        &self,
+        route: &RouteRateLimit,
+        subjects: &[String],
+    ) -> Result<Option<u64>, RedisError> {
+        let mut conn = self.redis.get_multiplexed_async_connection().await?;
+        let keys: Vec<String> = subjects
+            .iter()
+            .map(|subject| {
+                format!(
+                    "rate:{}:{}:{}:{}",
+                    self.namespace, route.method, route.path, subject
+                )
+            })
+            .collect();
+
+        let retry_after_ms: u64 = self
+            .script
+            .key(keys)
+            .arg(route.window_seconds * 1000)
+            .arg(route.max_requests)
+            .arg(Ulid::new().to_string())
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok((retry_after_ms > 0).then(|| retry_after_ms.div_ceil(1000)))
+    }
+}
+
+pub async fn rate_limit_middleware(
+    State(limiter): State<RateLimiter>,
+    req: Request,
+    next: Next,
+) -> Result<impl IntoResponse, RateLimitError> {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| limiter.limits.for_route(req.method(), path.as_str()))
+        .cloned();
+
+    let Some(route) = route else {
+        return Ok(next.run(req).await);
+    };
+
+    let mut subjects = vec![format!(
+        "ip:{}",
+        client_ip(&req, limiter.limits.forwarded_hops())
+    )];
+    if let Some(principal) = req.extensions().get::<Principal>() {
+        subjects.push(format!("user:{}", principal.user_id));
+    }
+
+    match limiter.acquire(&route, &subjects).await {
+        Ok(None) => {}
+        Ok(Some(retry_after)) => {
+            return Err(RateLimitError::TooManyRequests { retry_after });
+        }
+        Err(e) => {
+            warn!(
+                "Rate limiter unavailable, letting the request through: {}",
+                e
+            );
+        }
+    }
+
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{StatusCode, header};
+    use axum::middleware::from_fn_with_state;
+    use axum::routing::post;
+    use tower::ServiceExt;
+
+    fn redis_uri() -> String {
+        std::env::var("REDIS_URI").unwrap_or_else(|_| "redis://localhost:6379".to_string())
+    }
+
+    fn route(max_requests: u32) -> RouteRateLimit {
+        RouteRateLimit {
+            method: "POST".to_string(),
+            path: "/limited".to_string(),
+            max_requests,
+            window_seconds: 60,
+        }
+    }
+
+    fn limiter(max_requests: u32) -> RateLimiter {
+        let limits = RateLimits {
+            routes: vec![route(max_requests)],
+            ..RateLimits::default()
+        };
+
+        RateLimiter::new(&redis_uri(), limits, &Ulid::new().to_string()).unwrap()
+    }
+
+    #[test]
+    fn too_many_requests_sets_retry_after() {
+        let response = RateLimitError::TooManyRequests { retry_after: 42 }.into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "42");
+    }
+
+    fn subjects(subjects: &[&str]) -> Vec<String> {
+        subjects.iter().map(|subject| subject.to_string()).collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Redis"]
+    async fn window_allows_max_requests_then_returns_retry_after() {
+        let limiter = limiter(3);
+        let ip = subjects(&["ip:10.0.0.1"]);
+
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire(&route(3), &ip).await.unwrap(), None);
+        }
+
+        let retry_after = limiter.acquire(&route(3), &ip).await.unwrap();
+        assert!(matches!(retry_after, Some(1..=60)), "{retry_after:?}");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Redis"]
+    async fn window_is_kept_per_subject() {
+        let limiter = limiter(1);
+        let first = subjects(&["ip:10.0.0.1"]);
+
+        assert_eq!(limiter.acquire(&route(1), &first).await.unwrap(), None);
+        assert!(limiter.acquire(&route(1), &first).await.unwrap().is_some());
+        assert_eq!(
+            limiter
+                .acquire(&route(1), &subjects(&["ip:10.0.0.2"]))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Redis"]
+    async fn rejected_request_counts_against_no_window() {
+        let limiter = limiter(1);
+
+        assert_eq!(
+            limiter
+                .acquire(&route(1), &subjects(&["user:a"]))
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(
+            limiter
+                .acquire(&route(1), &subjects(&["ip:10.0.0.1", "user:a"]))
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            limiter
+                .acquire(&route(1), &subjects(&["ip:10.0.0.1"]))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Redis"]
+    async fn middleware_answers_429_with_retry_after() {
+        let limiter = limiter(1);
+        let app = Router::new()
+            .route("/limited", post(|| async { StatusCode::NO_CONTENT }))
+            .route("/open", post(|| async { StatusCode::NO_CONTENT }))
+            .layer(from_fn_with_state(limiter, rate_limit_middleware));
+
+        let request = |uri: &str| Request::post(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(request("/limited")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app.clone().oneshot(request("/limited")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+
+        let response = app.clone().oneshot(request("/open")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+}
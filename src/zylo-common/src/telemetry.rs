@@ -0,0 +1,176 @@
+use crate::utils::get_container_id;
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{KeyValue, global};
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry_otlp::{LogExporter, MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, fmt};
+
+#[derive(Clone)]
+pub struct ServerMetrics {
+    pub request_counter: Counter<u64>,
+    pub request_latency: Histogram<f64>,
+    pub active_requests: Arc<AtomicU64>,
+    pub attributes: Vec<KeyValue>,
+}
+
+impl ServerMetrics {
+    pub fn new(service_name: &'static str) -> Self {
+        let meter = global::meter(service_name);
+        let boundaries = vec![
+            0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+        ];
+
+        let request_counter = meter
+            .u64_counter("http_server_requests_total")
+            .with_description("Total number of HTTP requests")
+            .build();
+
+        let request_latency = meter
+            .f64_histogram("http_server_request_duration_seconds")
+            .with_description("HTTP request duration")
+            .with_boundaries(boundaries)
+            .build();
+
+        let active_requests = Arc::new(AtomicU64::new(0));
+        let active_requests_clone = active_requests.clone();
+
+        let host_name = get_container_id().unwrap_or(String::from("0.0.0.0"));
+        let attributes = vec![
+            KeyValue::new("service", service_name),
+            KeyValue::new("instance", host_name),
+        ];
+        let attributes_clone = attributes.clone();
+
+        meter
+            .u64_observable_gauge("http_server_active_requests")
+            .with_description("Active HTTP requests")
+            .with_callback(move |observer| {
+                let value = active_requests_clone.load(Ordering::Relaxed);
+                observer.observe(value, &attributes_clone);
+            })
+            .build();
+
+        Self {
+            request_counter,
+            request_latency,
+            active_requests,
+            attributes,
+        }
+    }
+}
+
+fn get_resource(service_name: &'static str) -> Resource {
+    static RESOURCE: OnceLock<Resource> = OnceLock::new();
+    RESOURCE
+        .get_or_init(|| Resource::builder().with_service_name(service_name).build())
+        .clone()
+}
+
+pub fn init_traces(service_name: &'static str, endpoint: &str) -> SdkTracerProvider {
+    let exporter = SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .expect("Failed to create span exporter");
+
+    let provider = SdkTracerProvider::builder()
+        .with_resource(get_resource(service_name))
+        .with_batch_exporter(exporter)
+        .build();
+
+    global::set_tracer_provider(provider.clone());
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    provider
+}
+
+pub fn init_tracing(logger_provider: &SdkLoggerProvider, tracer_provider: &SdkTracerProvider) {
+    tracing_subscriber::registry()
+        .with(OpenTelemetryTracingBridge::new(logger_provider))
+        .with(OpenTelemetryLayer::new(
+            tracer_provider.tracer("tracing-jaeger"),
+        ))
+        .with(fmt::layer().pretty())
+        .with(EnvFilter::from_default_env())
+        .init();
+}
+
+pub fn init_metrics(service_name: &'static str, endpoint: &str) -> SdkMeterProvider {
+    let exporter = MetricExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .expect("Failed to create metric exporter");
+
+    let reader = opentelemetry_sdk::metrics::PeriodicReader::builder(exporter)
+        .with_interval(Duration::from_secs(10))
+        .build();
+
+    let provider = SdkMeterProvider::builder()
+        .with_reader(reader)
+        .with_resource(get_resource(service_name))
+        .build();
+
+    global::set_meter_provider(provider.clone());
+    provider
+}
+
+pub fn init_logs(service_name: &'static str, endpoint: &str) -> SdkLoggerProvider {
+    let exporter = LogExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .expect("Failed to create log exporter");
+
+    SdkLoggerProvider::builder()
+        .with_resource(get_resource(service_name))
+        .with_batch_exporter(exporter)
+        .build()
+}
+
+pub async fn track_metrics(
+    State(metrics): State<Arc<ServerMetrics>>,
+    req: Request,
+    next: Next,
+) -> impl IntoResponse {
+    let path = if let Some(matched_path) = req.extensions().get::<MatchedPath>() {
+        matched_path.as_str().to_owned()
+    } else {
+        req.uri().path().to_owned()
+    };
+    let method = req.method().clone();
+    let start = Instant::now();
+    metrics.active_requests.fetch_add(1, Ordering::Relaxed);
+
+    let response = next.run(req).await;
+
+    metrics.active_requests.fetch_sub(1, Ordering::Relaxed);
+    let latency = start.elapsed().as_secs_f64();
+    let status = response.status().as_u16().to_string();
+
+    let mut labels = vec![
+        KeyValue::new("method", method.to_string()),
+        KeyValue::new("path", path),
+        KeyValue::new("status", status),
+    ];
+    labels.extend_from_slice(&metrics.attributes);
+
+    metrics.request_counter.add(1, &labels);
+    metrics.request_latency.record(latency, &labels);
+    response
+}
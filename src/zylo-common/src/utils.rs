@@ -0,0 +1,14 @@
+use std::fs;
+
+pub fn get_container_id() -> Option<String> {
+    if let Ok(cgroup) = fs::read_to_string("/proc/self/cgroup") {
+        for line in cgroup.lines() {
+            if let Some(id) = line.split('/').next_back()
+                && id.len() >= 12
+            {
+                return Some(id.to_string());
+            }
+        }
+    }
+    None
+}